@@ -0,0 +1,400 @@
+//! Ed25519 agent keys: `did:key` identifiers, short-lived JWTs, and
+//! passphrase-encrypted persistence.
+//!
+//! An [`AgentKey`] is addressed by its `did:key` (the multibase-encoded
+//! public key), so a token's issuer is enough to find the key that must
+//! have signed it — agents authenticate to each other and to downstream
+//! services without sharing secrets. On disk the secret seed is sealed
+//! with ChaCha20-Poly1305 under a key derived from a passphrase with
+//! Argon2id; the DID is stored in the clear so a key file can be
+//! identified without unlocking it.
+
+use std::path::Path;
+
+use aios_protocol::{AgentId, AgentIdentityProvider, SoulProfile};
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+/// Multicodec prefix of an Ed25519 public key (`ed25519-pub`, varint 0xed).
+const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+const KEY_FILE_VERSION: u32 = 1;
+/// Seconds of clock skew tolerated when checking `iat` and `exp`.
+const JWT_LEEWAY_SECS: i64 = 30;
+
+/// An Ed25519 signing key and the `did:key` it is known by.
+#[derive(Clone)]
+pub struct AgentKey {
+    signing_key: SigningKey,
+    did: String,
+}
+
+impl std::fmt::Debug for AgentKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentKey")
+            .field("did", &self.did)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encrypted key file layout.
+#[derive(Serialize, Deserialize)]
+struct SealedKey {
+    version: u32,
+    did: String,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl AgentKey {
+    /// A fresh key from the operating system's RNG.
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        Self::from_seed(seed)
+    }
+
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let signing_key = SigningKey::from_bytes(&seed);
+        let did = did_from_verifying_key(&signing_key.verifying_key());
+        Self { signing_key, did }
+    }
+
+    /// `did:key:z6Mk...` for this key.
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.signing_key.sign(message)
+    }
+
+    /// Write the key to `path`, sealed under `passphrase`.
+    pub async fn save(&self, path: &Path, passphrase: &str) -> Result<()> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                self.signing_key.to_bytes().as_slice(),
+            )
+            .map_err(|_| anyhow!("failed sealing agent key"))?;
+        let sealed = SealedKey {
+            version: KEY_FILE_VERSION,
+            did: self.did.clone(),
+            kdf: "argon2id".to_owned(),
+            salt: STANDARD.encode(salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create key dir {parent:?}"))?;
+        }
+        let temp = path.with_extension(format!("tmp-{}", std::process::id()));
+        fs::write(&temp, serde_json::to_vec_pretty(&sealed)?).await?;
+        restrict_permissions(&temp).await?;
+        fs::rename(&temp, path)
+            .await
+            .with_context(|| format!("failed writing agent key {path:?}"))
+    }
+
+    /// Read a key written by [`Self::save`]. A wrong passphrase or a
+    /// tampered file fails authentication rather than yielding a key.
+    pub async fn load(path: &Path, passphrase: &str) -> Result<Self> {
+        let raw = fs::read(path)
+            .await
+            .with_context(|| format!("failed reading agent key {path:?}"))?;
+        let sealed: SealedKey = serde_json::from_slice(&raw)
+            .with_context(|| format!("failed parsing agent key {path:?}"))?;
+        ensure!(
+            sealed.version == KEY_FILE_VERSION && sealed.kdf == "argon2id",
+            "unsupported agent key file format in {path:?}"
+        );
+        let salt = STANDARD.decode(&sealed.salt)?;
+        let nonce = STANDARD.decode(&sealed.nonce)?;
+        ensure!(nonce.len() == 12, "malformed nonce in {path:?}");
+        let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+        let seed = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                STANDARD.decode(&sealed.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| anyhow!("wrong passphrase or corrupt agent key {path:?}"))?;
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| anyhow!("malformed agent key {path:?}"))?;
+        let key = Self::from_seed(seed);
+        ensure!(
+            key.did == sealed.did,
+            "agent key {path:?} does not match its DID {}",
+            sealed.did
+        );
+        Ok(key)
+    }
+
+    /// Load the key at `path`, generating and saving one if there is none.
+    pub async fn load_or_generate(path: &Path, passphrase: &str) -> Result<Self> {
+        if fs::try_exists(path).await? {
+            return Self::load(path, passphrase).await;
+        }
+        let key = Self::generate();
+        key.save(path, passphrase).await?;
+        Ok(key)
+    }
+
+    /// A compact EdDSA JWT issued and subject-bound to this key's DID, for
+    /// `audience`, expiring `ttl_secs` from now.
+    pub fn sign_jwt(&self, audience: &str, ttl_secs: u64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = JwtClaims {
+            iss: self.did.clone(),
+            sub: self.did.clone(),
+            aud: audience.to_owned(),
+            iat: now,
+            exp: now.saturating_add(i64::try_from(ttl_secs).unwrap_or(i64::MAX)),
+            jti: uuid::Uuid::new_v4().to_string(),
+        };
+        let header = serde_json::json!({
+            "alg": "EdDSA",
+            "typ": "JWT",
+            "kid": format!("{}#{}", self.did, self.did.trim_start_matches("did:key:")),
+        });
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).expect("claims serialize")),
+        );
+        let signature = self.sign(signing_input.as_bytes());
+        format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+}
+
+/// Claims of a JWT issued by [`AgentKey::sign_jwt`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// Issuing agent's `did:key`.
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: String,
+}
+
+/// Verify a JWT issued by another agent for `audience`.
+///
+/// The signing key is resolved from the `did:key` in `iss`; the token must
+/// use `EdDSA`, name `audience`, and be within its validity window (with a
+/// small allowance for clock skew).
+pub fn verify_jwt(token: &str, audience: &str) -> Result<JwtClaims> {
+    let Some((signing_input, signature)) = token.rsplit_once('.') else {
+        bail!("malformed JWT");
+    };
+    let Some((header, payload)) = signing_input.split_once('.') else {
+        bail!("malformed JWT");
+    };
+
+    let header: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?).context("malformed JWT header")?;
+    ensure!(header["alg"] == "EdDSA", "unsupported JWT algorithm");
+    let claims: JwtClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)
+        .context("malformed JWT claims")?;
+
+    let verifying_key = verifying_key_from_did(&claims.iss)?;
+    let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature)?)
+        .context("malformed JWT signature")?;
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .map_err(|_| anyhow!("JWT signature does not match issuer {}", claims.iss))?;
+
+    ensure!(
+        claims.aud == audience,
+        "JWT audience {} is not {audience}",
+        claims.aud
+    );
+    let now = chrono::Utc::now().timestamp();
+    ensure!(claims.exp + JWT_LEEWAY_SECS > now, "JWT expired");
+    ensure!(
+        claims.iat - JWT_LEEWAY_SECS <= now,
+        "JWT issued in the future"
+    );
+    Ok(claims)
+}
+
+/// `did:key` of an Ed25519 public key.
+pub fn did_from_verifying_key(key: &VerifyingKey) -> String {
+    let mut bytes = Vec::with_capacity(34);
+    bytes.extend_from_slice(&ED25519_MULTICODEC);
+    bytes.extend_from_slice(key.as_bytes());
+    format!("did:key:z{}", bs58::encode(bytes).into_string())
+}
+
+/// Ed25519 public key named by a `did:key`.
+pub fn verifying_key_from_did(did: &str) -> Result<VerifyingKey> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| anyhow!("{did} is not a base58btc did:key"))?;
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .with_context(|| format!("{did} is not valid base58"))?;
+    let key = bytes
+        .strip_prefix(&ED25519_MULTICODEC)
+        .ok_or_else(|| anyhow!("{did} is not an Ed25519 key"))?;
+    let key: [u8; 32] = key
+        .try_into()
+        .map_err(|_| anyhow!("{did} has a malformed Ed25519 key"))?;
+    VerifyingKey::from_bytes(&key).with_context(|| format!("{did} is not a valid Ed25519 key"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|error| anyhow!("failed deriving key-file key: {error}"))?;
+    Ok(key)
+}
+
+#[cfg(unix)]
+async fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .await
+        .with_context(|| format!("failed restricting permissions of {path:?}"))
+}
+
+#[cfg(not(unix))]
+async fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// [`AgentIdentityProvider`] backed by an [`AgentKey`]: exposes the
+/// key's DID and signs JWTs with it.
+#[derive(Debug, Clone)]
+pub struct KeyedIdentity {
+    agent_id: AgentId,
+    soul: SoulProfile,
+    key: AgentKey,
+    capabilities: Vec<String>,
+}
+
+impl KeyedIdentity {
+    pub fn new(agent_id: AgentId, soul: SoulProfile, key: AgentKey) -> Self {
+        Self {
+            agent_id,
+            soul,
+            key,
+            capabilities: Vec::new(),
+        }
+    }
+
+    pub fn with_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn key(&self) -> &AgentKey {
+        &self.key
+    }
+}
+
+impl AgentIdentityProvider for KeyedIdentity {
+    fn agent_id(&self) -> &AgentId {
+        &self.agent_id
+    }
+
+    fn soul_profile(&self) -> &SoulProfile {
+        &self.soul
+    }
+
+    fn did(&self) -> Option<&str> {
+        Some(self.key.did())
+    }
+
+    fn sign_jwt(&self, audience: &str, ttl_secs: u64) -> Option<String> {
+        Some(self.key.sign_jwt(audience, ttl_secs))
+    }
+
+    fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn did_key_round_trips_the_public_key() {
+        let key = AgentKey::from_seed([7; 32]);
+        assert!(key.did().starts_with("did:key:z6Mk"), "{}", key.did());
+        assert_eq!(
+            verifying_key_from_did(key.did()).unwrap(),
+            key.verifying_key()
+        );
+        assert!(verifying_key_from_did("did:web:example.com").is_err());
+    }
+
+    #[test]
+    fn jwts_verify_for_their_audience_only() {
+        let issuer = AgentKey::generate();
+        let token = issuer.sign_jwt("agent:billing", 60);
+
+        let claims = verify_jwt(&token, "agent:billing").unwrap();
+        assert_eq!(claims.iss, issuer.did());
+        assert_eq!(claims.exp - claims.iat, 60);
+
+        assert!(verify_jwt(&token, "agent:other").is_err());
+
+        // Claims re-attributed to another agent no longer match the signature.
+        let mut forged_claims = claims;
+        forged_claims.iss = AgentKey::generate().did().to_owned();
+        let mut parts: Vec<_> = token.split('.').map(str::to_owned).collect();
+        parts[1] = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged_claims).unwrap());
+        assert!(verify_jwt(&parts.join("."), "agent:billing").is_err());
+    }
+
+    #[tokio::test]
+    async fn keys_persist_sealed_under_a_passphrase() {
+        let path = std::env::temp_dir()
+            .join(format!("aios-keys-{}", uuid::Uuid::new_v4()))
+            .join("key.json");
+        let key = AgentKey::load_or_generate(&path, "correct horse")
+            .await
+            .unwrap();
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains(key.did()));
+        assert!(!raw.contains(&STANDARD.encode(key.signing_key.to_bytes())));
+
+        let again = AgentKey::load_or_generate(&path, "correct horse")
+            .await
+            .unwrap();
+        assert_eq!(again.did(), key.did());
+        assert!(AgentKey::load(&path, "battery staple").await.is_err());
+
+        let identity = KeyedIdentity::new(AgentId::default(), SoulProfile::default(), again);
+        let token = identity.sign_jwt("svc", 30).unwrap();
+        assert_eq!(verify_jwt(&token, "svc").unwrap().sub, key.did());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
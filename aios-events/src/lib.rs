@@ -2,18 +2,35 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use aios_protocol::blob::{BlobHash, BlobMetadata};
+use aios_protocol::ports::{BlobStorePort, IdentityPort};
 use aios_protocol::{
-    BranchId, EventRecord, EventRecordStream, EventStorePort, KernelError, SessionId,
+    AgentId, Belief, BeliefFilter, BranchId, EventRecord, EventRecordStream, EventStorePort,
+    KernelError, SessionId, SoulProfile, SoulUpdate,
 };
 use anyhow::{Context, Result, bail};
 use async_trait::async_trait;
 use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
 use tokio::fs::{self, OpenOptions};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::sync::broadcast;
 use tokio_stream::wrappers::BroadcastStream;
 use tracing::{debug, instrument, warn};
 
+pub mod keys;
+pub mod signing;
+pub mod world;
+
+pub use keys::{
+    AgentKey, JwtClaims, KeyedIdentity, did_from_verifying_key, verify_jwt, verifying_key_from_did,
+};
+pub use signing::{
+    EventSigner, SignatureReport, SigningEventStore, TrustedSigners, verify_record,
+    verify_signatures,
+};
+pub use world::{FileWorldStore, WorldCommit};
+
 fn to_kernel_error(error: anyhow::Error) -> KernelError {
     KernelError::Runtime(error.to_string())
 }
@@ -314,6 +331,21 @@ impl EventJournal {
     pub fn subscribe(&self) -> broadcast::Receiver<EventRecord> {
         self.stream.subscribe()
     }
+
+    /// Check the signature of every record of the session (or one of its
+    /// branches). See [`verify_signatures`].
+    pub async fn verify_signatures(
+        &self,
+        session_id: SessionId,
+        branch_id: Option<BranchId>,
+        trusted: &TrustedSigners,
+    ) -> Result<SignatureReport> {
+        let records = self
+            .store
+            .read_from(session_id, branch_id, 0, usize::MAX)
+            .await?;
+        Ok(verify_signatures(&records, trusted))
+    }
 }
 
 #[async_trait]
@@ -397,6 +429,426 @@ impl EventStorePort for EventJournal {
     }
 }
 
+/// Content-addressed blob store on the local filesystem.
+///
+/// Payloads live at `<root>/<hash[..2]>/<hash>` (SHA-256 hex) with a
+/// `<hash>.json` sidecar holding their [`BlobMetadata`]. Blobs are
+/// immutable: putting the same bytes twice is a no-op returning the same
+/// hash. Payload and sidecar are each written to a uniquely named temp file
+/// and renamed into place — payload first — so readers never see a partial
+/// file and concurrent puts of the same bytes cannot trample each other.
+#[derive(Debug, Clone)]
+pub struct FileBlobStore {
+    root: PathBuf,
+}
+
+impl FileBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn blob_path(&self, hash: &BlobHash) -> Result<PathBuf> {
+        let hex = hash.as_str();
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("invalid blob hash: {hex}");
+        }
+        Ok(self.root.join(&hex[..2]).join(hex))
+    }
+
+    async fn put_blob(&self, payload: &[u8], content_type: Option<String>) -> Result<BlobHash> {
+        let hash = BlobHash::from_sha256_hex(hex::encode(Sha256::digest(payload)));
+        let path = self.blob_path(&hash)?;
+        let metadata_path = path.with_extension("json");
+        if fs::try_exists(&path).await.unwrap_or(false)
+            && fs::try_exists(&metadata_path).await.unwrap_or(false)
+        {
+            return Ok(hash);
+        }
+        Self::ensure_blob_dir(&path).await?;
+
+        let metadata = BlobMetadata::new(
+            hash.clone(),
+            payload.len() as u64,
+            content_type,
+            chrono::Utc::now(),
+        );
+        Self::write_atomically(&path, payload).await?;
+        Self::write_atomically(&metadata_path, &serde_json::to_vec_pretty(&metadata)?).await?;
+        debug!(hash = %hash, size_bytes = payload.len(), "blob stored");
+        Ok(hash)
+    }
+
+    /// Write `bytes` to `path` via a uniquely named temp file and a rename.
+    /// Losing the rename to a concurrent writer that already committed
+    /// `path` counts as success: blob files are content-addressed.
+    async fn write_atomically(path: &Path, bytes: &[u8]) -> Result<()> {
+        let temp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        fs::write(&temp, bytes)
+            .await
+            .with_context(|| format!("failed writing blob {temp:?}"))?;
+        match fs::rename(&temp, path).await {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                let _ = fs::remove_file(&temp).await;
+                let lost_race = matches!(
+                    error.kind(),
+                    std::io::ErrorKind::AlreadyExists | std::io::ErrorKind::NotFound
+                ) && fs::try_exists(path).await.unwrap_or(false);
+                if lost_race {
+                    Ok(())
+                } else {
+                    Err(error).with_context(|| format!("failed committing blob {path:?}"))
+                }
+            }
+        }
+    }
+
+    async fn ensure_blob_dir(path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create blob dir {parent:?}"))?;
+        }
+        Ok(())
+    }
+
+    async fn get_blob(&self, hash: &BlobHash) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash)?;
+        fs::read(&path)
+            .await
+            .with_context(|| format!("blob not found: {hash}"))
+    }
+
+    async fn head_blob(&self, hash: &BlobHash) -> Result<BlobMetadata> {
+        let path = self.blob_path(hash)?.with_extension("json");
+        let raw = fs::read(&path)
+            .await
+            .with_context(|| format!("blob not found: {hash}"))?;
+        serde_json::from_slice(&raw)
+            .with_context(|| format!("failed parsing blob metadata {path:?}"))
+    }
+}
+
+#[async_trait]
+impl BlobStorePort for FileBlobStore {
+    async fn put(
+        &self,
+        payload: bytes::Bytes,
+        content_type: Option<String>,
+    ) -> std::result::Result<BlobHash, KernelError> {
+        self.put_blob(&payload, content_type)
+            .await
+            .map_err(to_kernel_error)
+    }
+
+    async fn get(&self, hash: BlobHash) -> std::result::Result<bytes::Bytes, KernelError> {
+        self.get_blob(&hash)
+            .await
+            .map(bytes::Bytes::from)
+            .map_err(to_kernel_error)
+    }
+
+    async fn head(&self, hash: BlobHash) -> std::result::Result<BlobMetadata, KernelError> {
+        self.head_blob(&hash).await.map_err(to_kernel_error)
+    }
+}
+
+/// Identity service on the local filesystem, one directory per agent.
+///
+/// `<root>/<agent>/soul.json` holds the current [`SoulProfile`] (an agent
+/// without one gets the default), `soul_history.jsonl` every
+/// [`SoulUpdate`] that changed it, `beliefs.json` the belief store, and
+/// `key.json` the agent's sealed signing key (see [`Self::keyed_identity`]).
+/// Contradiction links are kept symmetric; a new belief that negates an
+/// existing belief about the same subject is linked to it automatically
+/// and, when the existing belief is held with at least
+/// `contradiction_threshold` confidence, reported by [`Self::add_belief`].
+#[derive(Debug, Clone)]
+pub struct FileIdentityStore {
+    root: PathBuf,
+    contradiction_threshold: f32,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// One applied [`SoulUpdate`] and the profile it produced.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SoulRevision {
+    pub revision: u64,
+    pub update: SoulUpdate,
+    pub profile: SoulProfile,
+}
+
+/// Result of [`FileIdentityStore::add_belief`].
+#[derive(Debug, Clone)]
+pub struct BeliefAdded {
+    /// The stored belief, with its links completed.
+    pub belief: Belief,
+    /// Existing high-confidence beliefs the new one contradicts.
+    pub contradictions: Vec<Belief>,
+}
+
+impl FileIdentityStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            contradiction_threshold: 0.7,
+            lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Confidence at which a contradicted belief is reported (default 0.7).
+    pub fn with_contradiction_threshold(mut self, threshold: f32) -> Self {
+        self.contradiction_threshold = threshold;
+        self
+    }
+
+    fn agent_dir(&self, agent: &AgentId) -> PathBuf {
+        let segment: String = agent
+            .as_str()
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_') {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.root.join(segment)
+    }
+
+    async fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+        match fs::read(path).await {
+            Ok(raw) => Ok(Some(
+                serde_json::from_slice(&raw).with_context(|| format!("failed parsing {path:?}"))?,
+            )),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error).with_context(|| format!("failed reading {path:?}")),
+        }
+    }
+
+    async fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create identity dir {parent:?}"))?;
+        }
+        let temp = path.with_extension(format!("tmp-{}", std::process::id()));
+        fs::write(&temp, serde_json::to_vec_pretty(value)?).await?;
+        fs::rename(&temp, path)
+            .await
+            .with_context(|| format!("failed writing {path:?}"))
+    }
+
+    async fn load_soul(&self, agent: &AgentId) -> Result<SoulProfile> {
+        Ok(Self::read_json(&self.agent_dir(agent).join("soul.json"))
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn load_beliefs(&self, agent: &AgentId) -> Result<Vec<Belief>> {
+        Ok(Self::read_json(&self.agent_dir(agent).join("beliefs.json"))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Apply `update`, recording a [`SoulRevision`] when it changes the
+    /// profile.
+    pub async fn apply_soul_update(
+        &self,
+        agent: &AgentId,
+        update: SoulUpdate,
+    ) -> Result<SoulProfile> {
+        let _guard = self.lock.lock().await;
+        let mut soul = self.load_soul(agent).await?;
+        if !update.apply_to(&mut soul) {
+            return Ok(soul);
+        }
+        let dir = self.agent_dir(agent);
+        let revision = self.soul_history(agent).await?.len() as u64 + 1;
+        let mut line = serde_json::to_string(&SoulRevision {
+            revision,
+            update,
+            profile: soul.clone(),
+        })?;
+        line.push('\n');
+        fs::create_dir_all(&dir).await?;
+        let mut history = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("soul_history.jsonl"))
+            .await?;
+        history.write_all(line.as_bytes()).await?;
+        history.flush().await?;
+        Self::write_json(&dir.join("soul.json"), &soul).await?;
+        debug!(agent = %agent, revision, "soul updated");
+        Ok(soul)
+    }
+
+    /// Every change made to the agent's soul, oldest first.
+    pub async fn soul_history(&self, agent: &AgentId) -> Result<Vec<SoulRevision>> {
+        let path = self.agent_dir(agent).join("soul_history.jsonl");
+        let file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error).with_context(|| format!("failed reading {path:?}")),
+        };
+        let mut lines = BufReader::new(file).lines();
+        let mut history = Vec::new();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            history.push(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("corrupt soul revision in {path:?}"))?,
+            );
+        }
+        Ok(history)
+    }
+
+    /// Store `belief`. Its `supports`/`contradicts` ids must name stored
+    /// beliefs; contradictions, explicit or detected, are linked both ways.
+    pub async fn add_belief(&self, agent: &AgentId, mut belief: Belief) -> Result<BeliefAdded> {
+        let _guard = self.lock.lock().await;
+        let mut beliefs = self.load_beliefs(agent).await?;
+        if beliefs.iter().any(|existing| existing.id == belief.id) {
+            bail!("belief {} already exists", belief.id);
+        }
+        for id in belief.supports.iter().chain(&belief.contradicts) {
+            if !beliefs.iter().any(|existing| &existing.id == id) {
+                bail!("belief {} links to unknown belief {id}", belief.id);
+            }
+        }
+        belief.confidence = belief.confidence.clamp(0.0, 1.0);
+
+        let stance = Stance::of(&belief.proposition);
+        for existing in &beliefs {
+            if existing.subject.eq_ignore_ascii_case(&belief.subject)
+                && stance.opposes(&Stance::of(&existing.proposition))
+                && !belief.contradicts.contains(&existing.id)
+            {
+                belief.contradicts.push(existing.id.clone());
+            }
+        }
+        let mut contradictions = Vec::new();
+        for existing in &mut beliefs {
+            if !belief.contradicts.contains(&existing.id) {
+                continue;
+            }
+            if !existing.contradicts.contains(&belief.id) {
+                existing.contradicts.push(belief.id.clone());
+            }
+            if existing.confidence >= self.contradiction_threshold {
+                contradictions.push(existing.clone());
+            }
+        }
+        if !contradictions.is_empty() {
+            warn!(
+                agent = %agent,
+                belief = %belief.id,
+                contradicts = ?contradictions.iter().map(|b| b.id.as_str()).collect::<Vec<_>>(),
+                "new belief contradicts high-confidence beliefs"
+            );
+        }
+
+        beliefs.push(belief.clone());
+        Self::write_json(&self.agent_dir(agent).join("beliefs.json"), &beliefs).await?;
+        Ok(BeliefAdded {
+            belief,
+            contradictions,
+        })
+    }
+
+    /// The agent's current soul with its signing key, `<agent>/key.json`,
+    /// unsealed with `passphrase`. A key is generated on first use.
+    pub async fn keyed_identity(&self, agent: &AgentId, passphrase: &str) -> Result<KeyedIdentity> {
+        let key =
+            AgentKey::load_or_generate(&self.agent_dir(agent).join("key.json"), passphrase).await?;
+        let soul = self.load_soul(agent).await?;
+        Ok(KeyedIdentity::new(agent.clone(), soul, key))
+    }
+
+    /// Beliefs passing `filter`, most recently observed first.
+    pub async fn beliefs(&self, agent: &AgentId, filter: &BeliefFilter) -> Result<Vec<Belief>> {
+        let _guard = self.lock.lock().await;
+        let mut beliefs = self.load_beliefs(agent).await?;
+        beliefs.retain(|belief| filter.matches(belief));
+        beliefs.sort_by_key(|belief| std::cmp::Reverse(belief.observed_at));
+        if let Some(limit) = filter.limit {
+            beliefs.truncate(limit as usize);
+        }
+        Ok(beliefs)
+    }
+}
+
+/// A proposition reduced to its words minus negations, plus whether it
+/// was negated: "the build is not green" and "the build is green" share
+/// words and differ in polarity.
+#[derive(Debug, PartialEq)]
+struct Stance {
+    words: Vec<String>,
+    negated: bool,
+}
+
+impl Stance {
+    fn of(proposition: &str) -> Self {
+        let mut words = Vec::new();
+        let mut negated = false;
+        for raw in proposition
+            .split(|ch: char| !(ch.is_alphanumeric() || ch == '\''))
+            .filter(|word| !word.is_empty())
+        {
+            let word = raw.to_lowercase();
+            match word.as_str() {
+                "not" | "no" | "never" | "cannot" => negated = !negated,
+                _ => match word.strip_suffix("n't") {
+                    Some(stem) => {
+                        negated = !negated;
+                        let stem = match stem {
+                            "won" => "will",
+                            "can" | "ca" => "can",
+                            other => other,
+                        };
+                        words.push(stem.to_owned());
+                    }
+                    None => words.push(word),
+                },
+            }
+        }
+        Self { words, negated }
+    }
+
+    fn opposes(&self, other: &Self) -> bool {
+        !self.words.is_empty() && self.words == other.words && self.negated != other.negated
+    }
+}
+
+#[async_trait]
+impl IdentityPort for FileIdentityStore {
+    async fn get_soul(&self, agent: AgentId) -> std::result::Result<SoulProfile, KernelError> {
+        self.load_soul(&agent).await.map_err(to_kernel_error)
+    }
+
+    async fn update_soul(
+        &self,
+        agent: AgentId,
+        update: SoulUpdate,
+    ) -> std::result::Result<SoulProfile, KernelError> {
+        self.apply_soul_update(&agent, update)
+            .await
+            .map_err(to_kernel_error)
+    }
+
+    async fn get_beliefs(
+        &self,
+        agent: AgentId,
+        filter: BeliefFilter,
+    ) -> std::result::Result<Vec<Belief>, KernelError> {
+        self.beliefs(&agent, &filter).await.map_err(to_kernel_error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -406,7 +858,9 @@ mod tests {
     use anyhow::Result;
     use tokio::fs;
 
-    use crate::{EventStore, FileEventStore};
+    use aios_protocol::ports::BlobStorePort;
+
+    use crate::{EventStore, FileBlobStore, FileEventStore, FileIdentityStore};
 
     fn unique_test_root(name: &str) -> PathBuf {
         let nanos = SystemTime::now()
@@ -565,4 +1019,169 @@ mod tests {
         let _ = fs::remove_dir_all(root).await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn file_blob_store_is_content_addressed() -> Result<()> {
+        let root = unique_test_root("aios-blobs");
+        let store = FileBlobStore::new(&root);
+
+        let payload = bytes::Bytes::from_static(b"large tool output");
+        let hash = store
+            .put(payload.clone(), Some("text/plain".to_owned()))
+            .await?;
+        let again = store.put(payload.clone(), None).await?;
+        assert_eq!(hash, again);
+        assert_eq!(hash.as_str().len(), 64);
+
+        assert_eq!(store.get(hash.clone()).await?, payload);
+        let metadata = store.head(hash).await?;
+        assert_eq!(metadata.size_bytes, payload.len() as u64);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+
+        let missing = aios_protocol::BlobHash::from_hex("0".repeat(64));
+        assert!(store.get(missing).await.is_err());
+        let traversal = aios_protocol::BlobHash::from_hex("../../etc/passwd");
+        assert!(store.get(traversal).await.is_err());
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn concurrent_puts_of_the_same_blob_all_succeed() -> Result<()> {
+        let root = unique_test_root("aios-blobs-race");
+        let store = FileBlobStore::new(&root);
+        let payload = bytes::Bytes::from(vec![7_u8; 64 * 1024]);
+
+        let puts: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                let payload = payload.clone();
+                tokio::spawn(async move { store.put(payload, None).await })
+            })
+            .collect();
+        let mut hashes = Vec::new();
+        for put in puts {
+            hashes.push(put.await.expect("put task")?);
+        }
+        assert!(hashes.windows(2).all(|pair| pair[0] == pair[1]));
+
+        assert_eq!(store.get(hashes[0].clone()).await?, payload);
+        assert_eq!(store.head(hashes[0].clone()).await?.size_bytes, 64 * 1024);
+        let mut shard = fs::read_dir(root.join(&hashes[0].as_str()[..2])).await?;
+        while let Some(entry) = shard.next_entry().await? {
+            let name = entry.file_name();
+            assert!(
+                !name.to_string_lossy().contains(".tmp-"),
+                "leftover temp file {name:?}"
+            );
+        }
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn identity_store_records_soul_history() -> Result<()> {
+        use aios_protocol::ports::IdentityPort;
+        use aios_protocol::{AgentId, SoulUpdate};
+
+        let root = unique_test_root("aios-identity");
+        let store = FileIdentityStore::new(&root);
+        let agent = AgentId::from_string("agent/one");
+
+        let soul = store.get_soul(agent.clone()).await?;
+        assert!(soul.traits.is_empty());
+
+        let update = SoulUpdate::new()
+            .with_display_name("Ada")
+            .add_trait("curious")
+            .add_trait("terse");
+        store.update_soul(agent.clone(), update.clone()).await?;
+        // Re-applying the same update changes nothing and is not recorded.
+        store.update_soul(agent.clone(), update).await?;
+        let soul = store
+            .update_soul(agent.clone(), SoulUpdate::new().remove_trait("terse"))
+            .await?;
+        assert_eq!(soul.name, "Ada");
+        assert_eq!(soul.traits, ["curious"]);
+
+        let reopened = FileIdentityStore::new(&root);
+        assert_eq!(reopened.get_soul(agent.clone()).await?.traits, ["curious"]);
+        let history = reopened.soul_history(&agent).await?;
+        assert_eq!(
+            history.iter().map(|rev| rev.revision).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(history[0].profile.traits, ["curious", "terse"]);
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn identity_store_links_and_flags_contradicting_beliefs() -> Result<()> {
+        use aios_protocol::ports::IdentityPort;
+        use aios_protocol::{AgentId, Belief, BeliefFilter};
+
+        let root = unique_test_root("aios-identity");
+        let store = FileIdentityStore::new(&root);
+        let agent = AgentId::from_string("agent-two");
+
+        let green = store
+            .add_belief(&agent, Belief::new("build", "The build is green", 0.9))
+            .await?;
+        assert!(green.contradictions.is_empty());
+        let fast = store
+            .add_belief(
+                &agent,
+                Belief::new("build", "tests are fast", 0.4).with_supports([&green.belief.id]),
+            )
+            .await?;
+
+        let red = store
+            .add_belief(&agent, Belief::new("Build", "the build isn't green", 0.6))
+            .await?;
+        assert_eq!(red.belief.contradicts, [green.belief.id.as_str()]);
+        assert_eq!(red.contradictions.len(), 1);
+        assert_eq!(red.contradictions[0].id, green.belief.id);
+
+        // Opposing a low-confidence belief links it without flagging it.
+        let slow = store
+            .add_belief(&agent, Belief::new("build", "tests are not fast", 0.8))
+            .await?;
+        assert_eq!(slow.belief.contradicts, [fast.belief.id.as_str()]);
+        assert!(slow.contradictions.is_empty());
+
+        assert!(
+            store
+                .add_belief(
+                    &agent,
+                    Belief::new("build", "x", 0.5).with_supports(["nope"])
+                )
+                .await
+                .is_err()
+        );
+
+        let stored = store
+            .get_beliefs(agent.clone(), BeliefFilter::new().with_subject("build"))
+            .await?;
+        let green = stored
+            .iter()
+            .find(|belief| belief.id == green.belief.id)
+            .expect("green belief");
+        assert_eq!(green.contradicts, [red.belief.id.as_str()]);
+
+        let confident = store
+            .get_beliefs(
+                agent,
+                BeliefFilter::new().with_min_confidence(0.7).with_limit(1),
+            )
+            .await?;
+        assert_eq!(confident.len(), 1);
+        assert_eq!(confident[0].id, slow.belief.id);
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
 }
@@ -0,0 +1,340 @@
+//! Event signatures: attributing journaled records to the agent (or
+//! kernel) that produced them.
+//!
+//! [`SigningEventStore`] wraps an event store and, before each append,
+//! sets the record's `digest` to the SHA-256 of its
+//! [`EventRecord::signing_bytes`] and signs those bytes with the key of the
+//! record's `agent_id`, falling back to the kernel's system key.
+//! [`verify_signatures`] replays a journal and reports every record that is
+//! unsigned or whose signature, digest or signer does not check out: a
+//! record must be signed by the key [`TrustedSigners`] expects for its
+//! `agent_id`, so a valid signature by any other key is rejected.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aios_protocol::{
+    AgentId, BranchId, EventRecord, EventRecordStream, EventSignature, EventStorePort,
+    KernelResult, SessionId,
+};
+use anyhow::{Result, anyhow, ensure};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::{Signature, Verifier};
+use sha2::{Digest, Sha256};
+
+use crate::keys::{AgentKey, verifying_key_from_did};
+
+/// Chooses the key that signs each record.
+#[derive(Debug, Clone)]
+pub struct EventSigner {
+    system_key: AgentKey,
+    agent_keys: HashMap<AgentId, AgentKey>,
+}
+
+impl EventSigner {
+    /// Sign with `system_key` unless an agent key matches the record.
+    pub fn new(system_key: AgentKey) -> Self {
+        Self {
+            system_key,
+            agent_keys: HashMap::new(),
+        }
+    }
+
+    /// Sign records whose `agent_id` is `agent_id` with `key`.
+    pub fn with_agent_key(mut self, agent_id: AgentId, key: AgentKey) -> Self {
+        self.agent_keys.insert(agent_id, key);
+        self
+    }
+
+    /// The DIDs this signer signs each agent's records with, for
+    /// [`verify_signatures`].
+    pub fn trusted_signers(&self) -> TrustedSigners {
+        self.agent_keys.iter().fold(
+            TrustedSigners::new(self.system_key.did()),
+            |trusted, (agent_id, key)| trusted.with_agent(agent_id.clone(), key.did()),
+        )
+    }
+
+    /// Set `record.digest` and `record.signature`.
+    pub fn sign(&self, record: &mut EventRecord) {
+        let key = self
+            .agent_keys
+            .get(&record.agent_id)
+            .unwrap_or(&self.system_key);
+        let bytes = record.signing_bytes();
+        record.digest = Some(hex::encode(Sha256::digest(&bytes)));
+        record.signature = Some(EventSignature {
+            alg: "ed25519".to_owned(),
+            signer: key.did().to_owned(),
+            value: URL_SAFE_NO_PAD.encode(key.sign(&bytes).to_bytes()),
+        });
+    }
+}
+
+/// [`EventStorePort`] that signs every record with an [`EventSigner`]
+/// before handing it to `inner`.
+///
+/// Wrap it innermost — inside any store that still rewrites records, such
+/// as a redacting one — so the signature covers the bytes that are
+/// actually persisted.
+pub struct SigningEventStore {
+    inner: Arc<dyn EventStorePort>,
+    signer: EventSigner,
+}
+
+impl SigningEventStore {
+    pub fn new(inner: Arc<dyn EventStorePort>, signer: EventSigner) -> Self {
+        Self { inner, signer }
+    }
+}
+
+#[async_trait]
+impl EventStorePort for SigningEventStore {
+    async fn append(&self, mut event: EventRecord) -> KernelResult<EventRecord> {
+        self.signer.sign(&mut event);
+        self.inner.append(event).await
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        self.inner
+            .read(session_id, branch_id, from_sequence, limit)
+            .await
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        self.inner.head(session_id, branch_id).await
+    }
+
+    async fn subscribe(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        self.inner
+            .subscribe(session_id, branch_id, after_sequence)
+            .await
+    }
+}
+
+/// Check `record`'s signature (and digest, when present) and return the
+/// signer's DID.
+pub fn verify_record(record: &EventRecord) -> Result<String> {
+    let signature = record
+        .signature
+        .as_ref()
+        .ok_or_else(|| anyhow!("record is unsigned"))?;
+    ensure!(
+        signature.alg == "ed25519",
+        "unsupported signature algorithm {}",
+        signature.alg
+    );
+    let bytes = record.signing_bytes();
+    if let Some(digest) = &record.digest {
+        ensure!(
+            *digest == hex::encode(Sha256::digest(&bytes)),
+            "digest does not match record contents"
+        );
+    }
+    let key = verifying_key_from_did(&signature.signer)?;
+    let value = Signature::from_slice(&URL_SAFE_NO_PAD.decode(&signature.value)?)
+        .map_err(|_| anyhow!("malformed signature"))?;
+    key.verify(&bytes, &value)
+        .map_err(|_| anyhow!("signature by {} does not match record", signature.signer))?;
+    Ok(signature.signer.clone())
+}
+
+/// The DID expected to sign each agent's records: the agent's own key when
+/// one is registered, otherwise the system key. The default trusts nobody.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedSigners {
+    system: Option<String>,
+    agents: HashMap<AgentId, String>,
+}
+
+impl TrustedSigners {
+    /// Expect `system_did` to sign records of agents without a key of
+    /// their own.
+    pub fn new(system_did: impl Into<String>) -> Self {
+        Self {
+            system: Some(system_did.into()),
+            agents: HashMap::new(),
+        }
+    }
+
+    /// Expect `did` to sign the records of `agent_id`.
+    pub fn with_agent(mut self, agent_id: AgentId, did: impl Into<String>) -> Self {
+        self.agents.insert(agent_id, did.into());
+        self
+    }
+
+    /// The DID that should have signed a record of `agent_id`.
+    pub fn expected(&self, agent_id: &AgentId) -> Option<&str> {
+        self.agents
+            .get(agent_id)
+            .or(self.system.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Outcome of [`verify_signatures`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignatureReport {
+    /// `(sequence, signer DID)` of every record that verified.
+    pub verified: Vec<(u64, String)>,
+    /// Sequences of records without a signature.
+    pub unsigned: Vec<u64>,
+    /// `(sequence, reason)` of records whose signature failed, or whose
+    /// signer is not the one trusted for the record's agent.
+    pub invalid: Vec<(u64, String)>,
+}
+
+impl SignatureReport {
+    /// Whether every record carried a valid, trusted signature.
+    pub fn is_clean(&self) -> bool {
+        self.unsigned.is_empty() && self.invalid.is_empty()
+    }
+}
+
+/// Verify every record in `records`. A valid signature by a DID other than
+/// the one `trusted` expects for the record's agent is reported as invalid.
+pub fn verify_signatures<'a>(
+    records: impl IntoIterator<Item = &'a EventRecord>,
+    trusted: &TrustedSigners,
+) -> SignatureReport {
+    let mut report = SignatureReport::default();
+    for record in records {
+        if record.signature.is_none() {
+            report.unsigned.push(record.sequence);
+            continue;
+        }
+        match verify_record(record) {
+            Ok(signer) => match trusted.expected(&record.agent_id) {
+                Some(expected) if expected == signer => {
+                    report.verified.push((record.sequence, signer));
+                }
+                Some(expected) => report.invalid.push((
+                    record.sequence,
+                    format!(
+                        "untrusted signer {signer} for agent {}: expected {expected}",
+                        record.agent_id
+                    ),
+                )),
+                None => report.invalid.push((
+                    record.sequence,
+                    format!(
+                        "untrusted signer {signer}: no key is trusted for agent {}",
+                        record.agent_id
+                    ),
+                )),
+            },
+            Err(error) => report.invalid.push((record.sequence, error.to_string())),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use aios_protocol::EventKind;
+
+    use super::*;
+
+    fn record(agent_id: &AgentId, sequence: u64) -> EventRecord {
+        let mut record = EventRecord::new(
+            SessionId::default(),
+            BranchId::main(),
+            sequence,
+            EventKind::UserMessage {
+                content: format!("message {sequence}"),
+            },
+        );
+        record.agent_id = agent_id.clone();
+        record
+    }
+
+    #[test]
+    fn records_are_signed_by_their_agent_or_the_system_key() {
+        let system = AgentKey::generate();
+        let agent_key = AgentKey::generate();
+        let agent = AgentId::from_string("agent-1");
+        let signer =
+            EventSigner::new(system.clone()).with_agent_key(agent.clone(), agent_key.clone());
+
+        let mut by_agent = record(&agent, 1);
+        let mut by_kernel = record(&AgentId::from_string("other"), 2);
+        signer.sign(&mut by_agent);
+        signer.sign(&mut by_kernel);
+        let unsigned = record(&agent, 3);
+
+        let report = verify_signatures(
+            [&by_agent, &by_kernel, &unsigned],
+            &signer.trusted_signers(),
+        );
+        assert_eq!(
+            report.verified,
+            [
+                (1, agent_key.did().to_owned()),
+                (2, system.did().to_owned())
+            ]
+        );
+        assert_eq!(report.unsigned, [3]);
+        assert!(!report.is_clean());
+
+        let trusted = TrustedSigners::new(system.did());
+        let report = verify_signatures([&by_agent], &trusted);
+        assert_eq!(report.invalid.len(), 1);
+        assert!(report.invalid[0].1.contains("untrusted"));
+    }
+
+    #[test]
+    fn a_valid_signature_by_the_wrong_key_is_invalid() {
+        let system = AgentKey::generate();
+        let agent = AgentId::from_string("agent-1");
+        let signer =
+            EventSigner::new(system.clone()).with_agent_key(agent.clone(), AgentKey::generate());
+
+        // Self-signed by a fresh key claiming to be `agent`.
+        let mut forged = record(&agent, 1);
+        EventSigner::new(AgentKey::generate()).sign(&mut forged);
+        assert!(verify_record(&forged).is_ok());
+
+        let report = verify_signatures([&forged], &signer.trusted_signers());
+        assert!(report.verified.is_empty());
+        assert_eq!(report.invalid.len(), 1);
+        assert!(report.invalid[0].1.contains("agent-1"), "{report:?}");
+
+        let report = verify_signatures([&forged], &TrustedSigners::default());
+        assert_eq!(report.invalid.len(), 1);
+    }
+
+    #[test]
+    fn tampered_or_reattributed_records_fail_verification() {
+        let signer = EventSigner::new(AgentKey::generate());
+        let mut signed = record(&AgentId::default(), 1);
+        signer.sign(&mut signed);
+        assert!(verify_record(&signed).is_ok());
+
+        let mut tampered = signed.clone();
+        tampered.kind = EventKind::UserMessage {
+            content: "forged".to_owned(),
+        };
+        assert!(verify_record(&tampered).is_err());
+        tampered.digest = None;
+        assert!(verify_record(&tampered).is_err());
+
+        let mut reattributed = signed.clone();
+        if let Some(signature) = &mut reattributed.signature {
+            signature.signer = AgentKey::generate().did().to_owned();
+        }
+        assert!(verify_record(&reattributed).is_err());
+    }
+}
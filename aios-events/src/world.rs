@@ -0,0 +1,607 @@
+//! File-persisted, versioned world store.
+//!
+//! [`FileWorldStore`] keeps each world as an append-only mutation log at
+//! `<root>/<world>/mutations.jsonl`, one [`WorldCommit`] per line. A
+//! mutation is applied atomically (all of its ops or none) and bumps the
+//! world's version by one; the current state is the log replayed in
+//! order, so a snapshot at any earlier version is a shorter replay. Every
+//! committed op is broadcast as a [`WorldEvent`], letting several sessions
+//! share one consistent environment model.
+//!
+//! A commit is acknowledged only once its line is written and synced to
+//! disk. A crash mid-append can leave an unterminated last line; it was
+//! never acknowledged, so loading truncates it away. A failed append is
+//! cut back off the file before the error is returned.
+//!
+//! Subscribers get every op of every commit in order. One that falls too
+//! far behind the broadcast channel gets an error and the stream ends,
+//! rather than silently missing versions; it can resubscribe from the
+//! version the error names.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use aios_protocol::KernelError;
+use aios_protocol::ports::WorldPort;
+use aios_protocol::world::{WorldEvent, WorldId, WorldMutation, WorldSnapshot, WorldVersion};
+use anyhow::{Context, Result, bail, ensure};
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+use crate::to_kernel_error;
+
+/// One line of a world's mutation log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldCommit {
+    pub version: u64,
+    pub committed_at: chrono::DateTime<chrono::Utc>,
+    pub mutation: WorldMutation,
+}
+
+impl WorldCommit {
+    fn events(&self, world: &WorldId) -> impl Iterator<Item = WorldEvent> + '_ {
+        let world = world.clone();
+        self.mutation.ops.iter().map(move |op| {
+            WorldEvent::new(world.clone(), self.version, op.clone(), self.committed_at)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct LoadedWorld {
+    state: Value,
+    log: Vec<WorldCommit>,
+}
+
+impl LoadedWorld {
+    fn version(&self) -> u64 {
+        self.log.last().map_or(0, |commit| commit.version)
+    }
+}
+
+/// [`WorldPort`] over per-world mutation logs. See the module docs.
+#[derive(Debug)]
+pub struct FileWorldStore {
+    root: PathBuf,
+    worlds: Mutex<HashMap<WorldId, Arc<tokio::sync::Mutex<Option<LoadedWorld>>>>>,
+    sender: broadcast::Sender<WorldEvent>,
+}
+
+impl FileWorldStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self {
+            root: root.into(),
+            worlds: Mutex::new(HashMap::new()),
+            sender,
+        }
+    }
+
+    fn log_path(&self, world: &WorldId) -> Result<PathBuf> {
+        let id = world.as_str();
+        ensure!(
+            !id.is_empty()
+                && id != "."
+                && id != ".."
+                && id
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')),
+            "invalid world id: {id:?}"
+        );
+        Ok(self.root.join(id).join("mutations.jsonl"))
+    }
+
+    fn slot(&self, world: &WorldId) -> Arc<tokio::sync::Mutex<Option<LoadedWorld>>> {
+        self.worlds.lock().entry(world.clone()).or_default().clone()
+    }
+
+    async fn load(path: &Path) -> Result<LoadedWorld> {
+        let raw = match fs::read_to_string(path).await {
+            Ok(raw) => raw,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error).with_context(|| format!("failed reading {path:?}")),
+        };
+        let complete = raw.rfind('\n').map_or(0, |end| end + 1);
+        if complete < raw.len() {
+            warn!(
+                path = ?path,
+                dropped_bytes = raw.len() - complete,
+                "truncating unterminated world commit left by an interrupted append"
+            );
+            let file = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .await
+                .with_context(|| format!("failed opening {path:?}"))?;
+            file.set_len(complete as u64).await?;
+            file.sync_data().await?;
+        }
+        let mut world = LoadedWorld {
+            state: empty_state(),
+            log: Vec::new(),
+        };
+        for line in raw[..complete]
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+        {
+            let commit: WorldCommit = serde_json::from_str(line)
+                .with_context(|| format!("failed parsing world commit in {path:?}"))?;
+            ensure!(
+                commit.version == world.version() + 1,
+                "world log {path:?} skips from version {} to {}",
+                world.version(),
+                commit.version
+            );
+            commit.mutation.apply_to(&mut world.state)?;
+            world.log.push(commit);
+        }
+        Ok(world)
+    }
+
+    /// Lock `world`, loading its log on first use.
+    async fn lock(
+        &self,
+        world: &WorldId,
+    ) -> Result<(PathBuf, tokio::sync::OwnedMutexGuard<Option<LoadedWorld>>)> {
+        let path = self.log_path(world)?;
+        let mut guard = self.slot(world).lock_owned().await;
+        if guard.is_none() {
+            *guard = Some(Self::load(&path).await?);
+        }
+        Ok((path, guard))
+    }
+
+    /// Run `f` with `world` loaded, holding its lock.
+    async fn with_world<T>(
+        &self,
+        world: &WorldId,
+        f: impl FnOnce(&mut LoadedWorld) -> Result<T>,
+    ) -> Result<T> {
+        let (_, mut guard) = self.lock(world).await?;
+        f(guard.as_mut().expect("world loaded by lock"))
+    }
+
+    /// Apply `mutation` to `world`, persist it and broadcast its ops.
+    pub async fn apply(&self, world: &WorldId, mutation: WorldMutation) -> Result<WorldCommit> {
+        ensure!(!mutation.ops.is_empty(), "world mutation has no ops");
+        let (path, mut guard) = self.lock(world).await?;
+        let loaded = guard.as_mut().expect("world loaded by lock");
+
+        let mut state = loaded.state.clone();
+        mutation.apply_to(&mut state)?;
+        let commit = WorldCommit {
+            version: loaded.version() + 1,
+            committed_at: chrono::Utc::now(),
+            mutation,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create world dir {parent:?}"))?;
+        }
+        let mut line = serde_json::to_vec(&commit)?;
+        line.push(b'\n');
+        let appended = append_or_roll_back(&path, |mut file| async move {
+            file.write_all(&line).await?;
+            file.flush().await?;
+            file.sync_data().await?;
+            Ok(())
+        })
+        .await;
+        if let Err(error) = appended {
+            // Reload from disk on next use, in case the roll back failed too.
+            *guard = None;
+            return Err(error).with_context(|| format!("failed appending to {path:?}"));
+        }
+
+        loaded.state = state;
+        loaded.log.push(commit.clone());
+        // Broadcast under the world lock so subscribers see versions in order.
+        for event in commit.events(world) {
+            let _ = self.sender.send(event);
+        }
+        Ok(commit)
+    }
+
+    /// `world` as it was at `version` (0 is the empty world).
+    pub async fn snapshot_at(&self, world: &WorldId, version: u64) -> Result<WorldSnapshot> {
+        self.with_world(world, |loaded| {
+            if version > loaded.version() {
+                bail!(
+                    "world {world} is at version {}, not {version}",
+                    loaded.version()
+                );
+            }
+            if version == loaded.version() {
+                return Ok(WorldSnapshot::new(
+                    world.clone(),
+                    version,
+                    loaded.state.clone(),
+                ));
+            }
+            let mut state = empty_state();
+            for commit in loaded
+                .log
+                .iter()
+                .take_while(|commit| commit.version <= version)
+            {
+                commit.mutation.apply_to(&mut state)?;
+            }
+            Ok(WorldSnapshot::new(world.clone(), version, state))
+        })
+        .await
+    }
+
+    /// Commits of `world` after `after_version`, oldest first.
+    pub async fn history(&self, world: &WorldId, after_version: u64) -> Result<Vec<WorldCommit>> {
+        self.with_world(world, |loaded| {
+            Ok(loaded
+                .log
+                .iter()
+                .filter(|commit| commit.version > after_version)
+                .cloned()
+                .collect())
+        })
+        .await
+    }
+}
+
+/// Open the log at `path` for appending and run `write` on it. If `write`
+/// fails the file is cut back to its previous length, so an unacknowledged
+/// commit leaves no bytes behind for the next append to follow.
+async fn append_or_roll_back<F, Fut>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(fs::File) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| format!("failed opening {path:?}"))?;
+    let length = file.metadata().await?.len();
+    let Err(error) = write(file).await else {
+        return Ok(());
+    };
+    let rolled_back = async {
+        let file = OpenOptions::new().write(true).open(path).await?;
+        file.set_len(length).await?;
+        file.sync_data().await
+    }
+    .await;
+    if let Err(rollback) = rolled_back {
+        warn!(path = ?path, error = %rollback, "failed rolling back a partial world commit");
+    }
+    Err(error)
+}
+
+fn empty_state() -> Value {
+    Value::Object(serde_json::Map::new())
+}
+
+#[async_trait]
+impl WorldPort for FileWorldStore {
+    async fn snapshot(&self, world: WorldId) -> std::result::Result<WorldSnapshot, KernelError> {
+        self.with_world(&world, |loaded| {
+            Ok(WorldSnapshot::new(
+                world.clone(),
+                loaded.version(),
+                loaded.state.clone(),
+            ))
+        })
+        .await
+        .map_err(to_kernel_error)
+    }
+
+    async fn mutate(
+        &self,
+        world: WorldId,
+        mutation: WorldMutation,
+    ) -> std::result::Result<WorldVersion, KernelError> {
+        let commit = self
+            .apply(&world, mutation)
+            .await
+            .map_err(to_kernel_error)?;
+        Ok(WorldVersion {
+            world,
+            version: commit.version,
+            committed_at: commit.committed_at,
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        world: WorldId,
+        after_version: u64,
+    ) -> std::result::Result<
+        BoxStream<'static, std::result::Result<WorldEvent, KernelError>>,
+        KernelError,
+    > {
+        // Subscribe under the world lock: commits after the backlog can only
+        // arrive through the receiver, and none arrive twice.
+        let (backlog, mut receiver) = self
+            .with_world(&world, |loaded| {
+                let backlog: Vec<WorldEvent> = loaded
+                    .log
+                    .iter()
+                    .filter(|commit| commit.version > after_version)
+                    .flat_map(|commit| commit.events(&world).collect::<Vec<_>>())
+                    .collect();
+                Ok((backlog, self.sender.subscribe()))
+            })
+            .await
+            .map_err(to_kernel_error)?;
+
+        let stream = async_stream::try_stream! {
+            // Every commit up to here was delivered in full; the one being
+            // delivered may not have been.
+            let mut resume_after = after_version;
+            for event in backlog {
+                resume_after = resume_after.max(event.version - 1);
+                yield event;
+            }
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if event.world == world && event.version > after_version {
+                            resume_after = resume_after.max(event.version - 1);
+                            yield event;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        // Going on would skip versions; end the stream instead.
+                        warn!(skipped, world = %world, "world subscription lagged");
+                        Err(KernelError::Runtime(format!(
+                            "world {world} subscription fell {skipped} events behind; \
+                             resubscribe after version {resume_after}"
+                        )))?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        };
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aios_protocol::AgentId;
+    use aios_protocol::world::WorldMutationOp;
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    use super::*;
+
+    fn test_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aios-events-{name}-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn mutation(ops: Vec<WorldMutationOp>) -> WorldMutation {
+        WorldMutation::new(AgentId::from_string("agent"), ops)
+    }
+
+    #[tokio::test]
+    async fn world_store_versions_persists_and_serves_history() -> Result<()> {
+        let root = test_root("world");
+        let world = WorldId::new("lab");
+        let store = FileWorldStore::new(&root);
+
+        store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("closed"))])
+                    .with_causation_id("evt-1"),
+            )
+            .await?;
+        let version = store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("open"))]),
+            )
+            .await?;
+        assert_eq!(version.version, 2);
+
+        // A failing mutation commits nothing.
+        let rejected = store
+            .mutate(
+                world.clone(),
+                mutation(vec![
+                    WorldMutationOp::set("/lamp", json!("on")),
+                    WorldMutationOp::patch(
+                        "",
+                        json!([{ "op": "replace", "path": "/window", "value": 1 }]),
+                    ),
+                ]),
+            )
+            .await;
+        assert!(rejected.is_err());
+
+        let reopened = FileWorldStore::new(&root);
+        let snapshot = reopened.snapshot(world.clone()).await?;
+        assert_eq!(
+            (snapshot.version, snapshot.state),
+            (2, json!({ "door": "open" }))
+        );
+        let first = reopened.snapshot_at(&world, 1).await?;
+        assert_eq!(first.state, json!({ "door": "closed" }));
+        assert!(reopened.snapshot_at(&world, 3).await.is_err());
+        let history = reopened.history(&world, 0).await?;
+        assert_eq!(history[0].mutation.causation_id.as_deref(), Some("evt-1"));
+
+        assert!(store.snapshot(WorldId::new("../escape")).await.is_err());
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn an_interrupted_append_is_dropped_on_load() -> Result<()> {
+        let root = test_root("world-torn");
+        let world = WorldId::new("lab");
+        FileWorldStore::new(&root)
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("closed"))]),
+            )
+            .await?;
+
+        // A crash mid-append leaves half a commit without its newline.
+        let log = root.join("lab").join("mutations.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&log).await?;
+        file.write_all(br#"{"version":2,"committed_at":"#).await?;
+        file.flush().await?;
+
+        let reopened = FileWorldStore::new(&root);
+        assert_eq!(reopened.snapshot(world.clone()).await?.version, 1);
+        let commit = reopened
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("open"))]),
+            )
+            .await?;
+        assert_eq!(commit.version, 2);
+
+        let snapshot = FileWorldStore::new(&root).snapshot(world).await?;
+        assert_eq!(
+            (snapshot.version, snapshot.state),
+            (2, json!({ "door": "open" }))
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_failed_append_is_rolled_back() -> Result<()> {
+        let root = test_root("world-failed-append");
+        let world = WorldId::new("lab");
+        let store = FileWorldStore::new(&root);
+        store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("closed"))]),
+            )
+            .await?;
+        let log = root.join("lab").join("mutations.jsonl");
+        let length = fs::metadata(&log).await?.len();
+
+        // The disk fills up halfway through the next commit.
+        let failed = append_or_roll_back(&log, |mut file| async move {
+            file.write_all(br#"{"version":2,"committed_at":"#).await?;
+            file.flush().await?;
+            bail!("no space left on device")
+        })
+        .await;
+        assert!(failed.is_err());
+        assert_eq!(fs::metadata(&log).await?.len(), length);
+
+        let commit = store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/door", json!("open"))]),
+            )
+            .await?;
+        assert_eq!(commit.version, 2);
+        let snapshot = FileWorldStore::new(&root).snapshot(world).await?;
+        assert_eq!(
+            (snapshot.version, snapshot.state),
+            (2, json!({ "door": "open" }))
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn world_subscribers_replay_then_follow_commits() -> Result<()> {
+        let root = test_root("world-subscribe");
+        let world = WorldId::new("lab");
+        let store = FileWorldStore::new(&root);
+        store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/a", json!(1))]),
+            )
+            .await?;
+        store
+            .mutate(
+                world.clone(),
+                mutation(vec![
+                    WorldMutationOp::set("/b", json!(2)),
+                    WorldMutationOp::remove("/a"),
+                ]),
+            )
+            .await?;
+
+        let mut events = store.subscribe(world.clone(), 1).await?;
+        store
+            .mutate(
+                WorldId::new("elsewhere"),
+                mutation(vec![WorldMutationOp::set("/x", json!(0))]),
+            )
+            .await?;
+        store
+            .mutate(
+                world.clone(),
+                mutation(vec![WorldMutationOp::set("/c", json!(3))]),
+            )
+            .await?;
+
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            let event = events.next().await.expect("stream open")?;
+            seen.push((event.version, event.op.path));
+        }
+        assert_eq!(
+            seen,
+            [
+                (2, "/b".to_owned()),
+                (2, "/a".to_owned()),
+                (3, "/c".to_owned())
+            ]
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_lagging_subscriber_gets_an_error_instead_of_a_gap() -> Result<()> {
+        let root = test_root("world-lagged");
+        let world = WorldId::new("lab");
+        let store = FileWorldStore::new(&root);
+        let mut events = store.subscribe(world.clone(), 0).await?;
+
+        // One commit with more ops than the broadcast channel holds.
+        let ops = (0..1100)
+            .map(|index| WorldMutationOp::set(format!("/k{index}"), json!(index)))
+            .collect();
+        store.mutate(world.clone(), mutation(ops)).await?;
+
+        let error = events
+            .next()
+            .await
+            .expect("stream reports the lag")
+            .expect_err("lagged");
+        assert!(
+            error.to_string().contains("resubscribe after version 0"),
+            "{error}"
+        );
+        assert!(events.next().await.is_none());
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+}
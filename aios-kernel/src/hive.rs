@@ -0,0 +1,510 @@
+//! Hive: population search over one objective.
+//!
+//! [`AiosKernel::run_hive`] opens a coordinator session and `agent_count`
+//! child sessions on the same objective. Every generation, the agents
+//! concurrently produce a candidate artifact each (their tick's final
+//! answer), the [`HiveScorer`] scores it, and the best `survivors` candidates are shared
+//! with every agent as the starting point of the next generation. The
+//! search stops once a candidate reaches the target score or after
+//! `max_generations`, and returns the best candidate seen.
+//!
+//! The coordinator session journals the search: `HiveTaskCreated`, then per
+//! generation one `HiveArtifactShared` per survivor, `HiveSelectionMade` for
+//! the generation's winner and `HiveGenerationCompleted` with every agent's
+//! score, and finally `HiveTaskCompleted`.
+
+use aios_protocol::ports::SessionPort;
+use aios_protocol::session::TickInput as SessionTickInput;
+use aios_protocol::{EventKind, HiveTaskId, PolicySet, SessionId};
+use anyhow::{Context, Result, ensure};
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde_json::json;
+use tracing::instrument;
+
+use crate::AiosKernel;
+
+/// Scores a candidate artifact; higher is better. A non-finite score
+/// fails the hive.
+#[async_trait]
+pub trait HiveScorer: Send + Sync {
+    async fn score(&self, objective: &str, artifact: &str) -> Result<f32>;
+}
+
+/// What a hive searches for and how.
+#[derive(Debug, Clone)]
+pub struct HiveTask {
+    pub objective: String,
+    pub agent_count: u32,
+    pub max_generations: u32,
+    /// Candidates carried into the next generation.
+    pub survivors: u32,
+    /// Stop as soon as a candidate scores at least this much.
+    pub target_score: Option<f32>,
+    /// Kernel ticks each agent may run per candidate.
+    pub max_iterations: u32,
+    pub policy: PolicySet,
+}
+
+impl HiveTask {
+    /// `agent_count` agents, 3 generations, half the agents surviving each.
+    pub fn new(objective: impl Into<String>, agent_count: u32) -> Self {
+        Self {
+            objective: objective.into(),
+            agent_count,
+            max_generations: 3,
+            survivors: (agent_count / 2).max(1),
+            target_score: None,
+            max_iterations: 1,
+            policy: PolicySet::default(),
+        }
+    }
+
+    pub fn with_max_generations(mut self, max_generations: u32) -> Self {
+        self.max_generations = max_generations;
+        self
+    }
+
+    pub fn with_survivors(mut self, survivors: u32) -> Self {
+        self.survivors = survivors;
+        self
+    }
+
+    pub fn with_target_score(mut self, target_score: f32) -> Self {
+        self.target_score = Some(target_score);
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: PolicySet) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// One agent's scored artifact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HiveCandidate {
+    pub session_id: SessionId,
+    pub generation: u32,
+    pub artifact: String,
+    pub score: f32,
+}
+
+/// Result of [`AiosKernel::run_hive`].
+#[derive(Debug, Clone)]
+pub struct HiveOutcome {
+    pub hive_task_id: HiveTaskId,
+    /// Session holding the hive's events; the agents are its children.
+    pub session_id: SessionId,
+    pub agent_sessions: Vec<SessionId>,
+    pub best: HiveCandidate,
+    pub generations: u32,
+    pub trials: u32,
+}
+
+impl AiosKernel {
+    /// Run `task` to completion. See the module docs.
+    #[instrument(skip(self, task, scorer), fields(agents = task.agent_count))]
+    pub async fn run_hive(&self, task: HiveTask, scorer: &dyn HiveScorer) -> Result<HiveOutcome> {
+        ensure!(task.agent_count > 0, "a hive needs at least one agent");
+        ensure!(
+            task.max_generations > 0,
+            "a hive needs at least one generation"
+        );
+        let survivors = task.survivors.clamp(1, task.agent_count) as usize;
+
+        let hive_task_id = HiveTaskId::default();
+        let coordinator = self
+            .create_session("hive", task.policy.clone(), None)
+            .await?
+            .session_id;
+        let mut agent_sessions = Vec::with_capacity(task.agent_count as usize);
+        for _ in 0..task.agent_count {
+            let agent = self
+                .create_child_session(&coordinator, task.policy.clone())
+                .await?;
+            agent_sessions.push(agent.session_id);
+        }
+        self.record_external_event(
+            &coordinator,
+            EventKind::HiveTaskCreated {
+                hive_task_id: hive_task_id.clone(),
+                objective: task.objective.clone(),
+                agent_count: task.agent_count,
+            },
+        )
+        .await?;
+
+        let mut shared: Vec<HiveCandidate> = Vec::new();
+        let mut best: Option<HiveCandidate> = None;
+        let mut trials = 0;
+        let mut generation = 0;
+        while generation < task.max_generations {
+            generation += 1;
+            let prompt = generation_prompt(&task.objective, &shared);
+
+            // Agents work in sessions of their own, so a generation's
+            // ticks run concurrently.
+            trials += task.agent_count;
+            let attempts = agent_sessions.iter().map(|session_id| {
+                let (task, prompt) = (&task, &prompt);
+                async move {
+                    let output = SessionPort::tick(
+                        self,
+                        session_id.clone(),
+                        SessionTickInput::new(prompt.clone())
+                            .with_max_iterations(task.max_iterations),
+                    )
+                    .await
+                    .with_context(|| format!("hive agent {session_id} failed"))?;
+                    let Some(artifact) = output.final_answer else {
+                        return Ok(None);
+                    };
+                    let score = scorer.score(&task.objective, &artifact).await?;
+                    ensure!(
+                        score.is_finite(),
+                        "hive scorer gave agent {session_id} the non-finite score {score}"
+                    );
+                    Ok::<_, anyhow::Error>(Some(HiveCandidate {
+                        session_id: session_id.clone(),
+                        generation,
+                        artifact,
+                        score,
+                    }))
+                }
+            });
+            let mut candidates = Vec::with_capacity(agent_sessions.len());
+            for attempt in join_all(attempts).await {
+                candidates.extend(attempt?);
+            }
+
+            let agent_results = json!(
+                agent_sessions
+                    .iter()
+                    .map(|session_id| {
+                        let score = candidates
+                            .iter()
+                            .find(|candidate| &candidate.session_id == session_id)
+                            .map(|candidate| candidate.score);
+                        json!({ "session_id": session_id, "score": score })
+                    })
+                    .collect::<Vec<_>>()
+            );
+            candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+            candidates.truncate(survivors);
+
+            let mutation_summary = if shared.is_empty() {
+                "initial candidate".to_owned()
+            } else {
+                format!(
+                    "refined from {} survivor(s) of generation {}",
+                    shared.len(),
+                    generation - 1
+                )
+            };
+            for candidate in &candidates {
+                self.record_external_event(
+                    &coordinator,
+                    EventKind::HiveArtifactShared {
+                        hive_task_id: hive_task_id.clone(),
+                        source_session_id: candidate.session_id.clone(),
+                        score: candidate.score,
+                        mutation_summary: mutation_summary.clone(),
+                    },
+                )
+                .await?;
+            }
+            let generation_best = candidates.first().map_or(f32::NEG_INFINITY, |c| c.score);
+            if let Some(winner) = candidates.first() {
+                self.record_external_event(
+                    &coordinator,
+                    EventKind::HiveSelectionMade {
+                        hive_task_id: hive_task_id.clone(),
+                        winning_session_id: winner.session_id.clone(),
+                        winning_score: winner.score,
+                        generation,
+                    },
+                )
+                .await?;
+                if best.as_ref().is_none_or(|best| winner.score > best.score) {
+                    best = Some(winner.clone());
+                }
+            }
+            self.record_external_event(
+                &coordinator,
+                EventKind::HiveGenerationCompleted {
+                    hive_task_id: hive_task_id.clone(),
+                    generation,
+                    best_score: generation_best,
+                    agent_results,
+                },
+            )
+            .await?;
+
+            // A generation without answers keeps the previous survivors.
+            if !candidates.is_empty() {
+                shared = candidates;
+            }
+            if task
+                .target_score
+                .is_some_and(|target| generation_best >= target)
+            {
+                break;
+            }
+        }
+
+        let best = best.context("no hive agent produced an artifact")?;
+        self.record_external_event(
+            &coordinator,
+            EventKind::HiveTaskCompleted {
+                hive_task_id: hive_task_id.clone(),
+                total_generations: generation,
+                total_trials: trials,
+                final_score: best.score,
+            },
+        )
+        .await?;
+
+        Ok(HiveOutcome {
+            hive_task_id,
+            session_id: coordinator,
+            agent_sessions,
+            best,
+            generations: generation,
+            trials,
+        })
+    }
+}
+
+/// The objective, followed from the second generation on by the
+/// survivors of the previous one.
+fn generation_prompt(objective: &str, shared: &[HiveCandidate]) -> String {
+    if shared.is_empty() {
+        return objective.to_owned();
+    }
+    let mut prompt = format!(
+        "{objective}\n\nBest candidates so far, highest score first. Produce a better one.\n"
+    );
+    for (rank, candidate) in shared.iter().enumerate() {
+        prompt.push_str(&format!(
+            "\n{}. [score {:.3}]\n{}\n",
+            rank + 1,
+            candidate.score,
+            candidate.artifact
+        ));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use aios_protocol::{BranchId, EventKind};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use tokio::sync::Barrier;
+    use tokio::time::timeout;
+
+    use super::{HiveScorer, HiveTask};
+    use crate::KernelBuilder;
+
+    /// Scores an artifact by how many earlier answers it builds on; the
+    /// baseline provider echoes its objective, which carries the survivors.
+    struct LineageScorer;
+
+    #[async_trait]
+    impl HiveScorer for LineageScorer {
+        async fn score(&self, _objective: &str, artifact: &str) -> Result<f32> {
+            Ok(artifact.matches("objective received").count() as f32)
+        }
+    }
+
+    fn test_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aios-kernel-{name}-{}", uuid::Uuid::new_v4()))
+    }
+
+    async fn hive_events(
+        kernel: &crate::AiosKernel,
+        session: &aios_protocol::SessionId,
+    ) -> Vec<EventKind> {
+        kernel
+            .read_events_on_branch(session, &BranchId::main(), 0, usize::MAX)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.kind)
+            .filter(|kind| {
+                matches!(
+                    kind,
+                    EventKind::HiveTaskCreated { .. }
+                        | EventKind::HiveArtifactShared { .. }
+                        | EventKind::HiveSelectionMade { .. }
+                        | EventKind::HiveGenerationCompleted { .. }
+                        | EventKind::HiveTaskCompleted { .. }
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn hive_stops_once_a_candidate_reaches_the_target() -> Result<()> {
+        let root = test_root("hive-target");
+        let kernel = KernelBuilder::new(&root).build();
+
+        let outcome = kernel
+            .run_hive(
+                HiveTask::new("name the project", 3)
+                    .with_survivors(2)
+                    .with_max_generations(5)
+                    .with_target_score(2.0),
+                &LineageScorer,
+            )
+            .await?;
+
+        assert_eq!(outcome.generations, 2);
+        assert_eq!(outcome.trials, 6);
+        assert_eq!(outcome.best.generation, 2);
+        assert!(outcome.best.score >= 2.0);
+        assert!(outcome.agent_sessions.contains(&outcome.best.session_id));
+        for agent in &outcome.agent_sessions {
+            let manifest = kernel.runtime.session_manifest(agent).unwrap();
+            assert_eq!(manifest.parent_session.as_ref(), Some(&outcome.session_id));
+        }
+
+        let names: Vec<&str> = hive_events(&kernel, &outcome.session_id)
+            .await
+            .iter()
+            .map(|kind| match kind {
+                EventKind::HiveTaskCreated { .. } => "created",
+                EventKind::HiveArtifactShared { .. } => "shared",
+                EventKind::HiveSelectionMade { .. } => "selected",
+                EventKind::HiveGenerationCompleted { .. } => "generation",
+                _ => "completed",
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "created",
+                "shared",
+                "shared",
+                "selected",
+                "generation",
+                "shared",
+                "shared",
+                "selected",
+                "generation",
+                "completed"
+            ]
+        );
+
+        let _ = tokio::fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    /// Scores only once every agent of the generation is being scored.
+    struct RendezvousScorer(Barrier);
+
+    #[async_trait]
+    impl HiveScorer for RendezvousScorer {
+        async fn score(&self, _objective: &str, _artifact: &str) -> Result<f32> {
+            self.0.wait().await;
+            Ok(1.0)
+        }
+    }
+
+    struct NanScorer;
+
+    #[async_trait]
+    impl HiveScorer for NanScorer {
+        async fn score(&self, _objective: &str, _artifact: &str) -> Result<f32> {
+            Ok(f32::NAN)
+        }
+    }
+
+    #[tokio::test]
+    async fn hive_agents_of_a_generation_run_concurrently() -> Result<()> {
+        let root = test_root("hive-concurrent");
+        let kernel = KernelBuilder::new(&root).build();
+
+        let outcome = timeout(
+            Duration::from_secs(10),
+            kernel.run_hive(
+                HiveTask::new("name the project", 3).with_max_generations(1),
+                &RendezvousScorer(Barrier::new(3)),
+            ),
+        )
+        .await
+        .expect("agents ran one after another")?;
+        assert_eq!(outcome.trials, 3);
+
+        let _ = tokio::fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hive_rejects_non_finite_scores() {
+        let root = test_root("hive-nan");
+        let kernel = KernelBuilder::new(&root).build();
+
+        let error = kernel
+            .run_hive(HiveTask::new("name the project", 2), &NanScorer)
+            .await
+            .expect_err("a NaN score must not win the selection");
+        assert!(error.to_string().contains("non-finite"), "{error:#}");
+
+        let _ = tokio::fs::remove_dir_all(root).await;
+    }
+
+    #[tokio::test]
+    async fn hive_stops_at_the_generation_cap() -> Result<()> {
+        let root = test_root("hive-cap");
+        let kernel = KernelBuilder::new(&root).build();
+
+        let outcome = kernel
+            .run_hive(
+                HiveTask::new("name the project", 2)
+                    .with_max_generations(3)
+                    .with_target_score(100.0),
+                &LineageScorer,
+            )
+            .await?;
+
+        assert_eq!(outcome.generations, 3);
+        assert_eq!(outcome.trials, 6);
+        let events = hive_events(&kernel, &outcome.session_id).await;
+        match events.last() {
+            Some(EventKind::HiveTaskCompleted {
+                total_generations,
+                total_trials,
+                final_score,
+                ..
+            }) => {
+                assert_eq!((*total_generations, *total_trials), (3, 6));
+                assert_eq!(*final_score, outcome.best.score);
+            }
+            other => panic!("hive did not complete: {other:?}"),
+        }
+        match &events[events.len() - 2] {
+            EventKind::HiveGenerationCompleted {
+                generation,
+                agent_results,
+                ..
+            } => {
+                assert_eq!(*generation, 3);
+                assert_eq!(agent_results.as_array().map(Vec::len), Some(2));
+            }
+            other => panic!("expected the last generation, got {other:?}"),
+        }
+
+        let _ = tokio::fs::remove_dir_all(root).await;
+        Ok(())
+    }
+}
@@ -1,23 +1,51 @@
+mod hive;
+
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use aios_events::{EventJournal, EventStreamHub, FileEventStore};
+use aios_events::{
+    EventJournal, EventSigner, EventStreamHub, FileBlobStore, FileEventStore, FileIdentityStore,
+    FileWorldStore, SignatureReport, SigningEventStore, TrustedSigners,
+};
 use aios_policy::{ApprovalQueue, SessionPolicyEngine};
+use aios_protocol::blob::BlobHash;
+use aios_protocol::ports::{BlobStorePort, SessionPort, WorldPort};
+use aios_protocol::session::{
+    CreateSessionRequest, SessionFilter, TickInput as SessionTickInput,
+    TickOutput as SessionTickOutput,
+};
+use aios_protocol::world::{WorldId, WorldSnapshot};
 use aios_protocol::{
-    BranchId, BranchInfo, BranchMergeResult, EventKind, EventRecord, EventStorePort, KernelResult,
-    ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelRouting,
-    ModelStopReason, PolicyGatePort, PolicySet, SessionId, SessionManifest, TokenUsage, ToolCall,
-    ToolHarnessPort,
+    AgentId, BranchId, BranchInfo, BranchMergeResult, EventKind, EventRecord, EventRecordStream,
+    EventStorePort, KernelError, KernelResult, ModelCompletion, ModelCompletionRequest,
+    ModelCompletionStream, ModelDirective, ModelProviderPort, ModelRouting, ModelStopReason,
+    ModelStreamChunk, OperatingMode, PolicyGatePort, PolicySet, SessionId, SessionManifest,
+    StreamingModelProviderPort, TokenUsage, ToolCall, ToolHarnessPort, VersionedCanonicalState,
+};
+use aios_runtime::{
+    KernelRuntime, RedactingEventStore, RuntimeConfig, TickInput, TickKind, TickOutput,
+    TurnMiddleware,
 };
-use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind, TickOutput, TurnMiddleware};
 use aios_sandbox::LocalSandboxRunner;
 use aios_tools::{ToolDispatcher, ToolRegistry};
+
+pub use hive::{HiveCandidate, HiveOutcome, HiveScorer, HiveTask};
+
+pub use aios_runtime::{
+    Intent, IntentEvaluator, IntentGate, IntentVerdict, MEMORY_PROPOSE_TOOL, PLAN_UPDATE_TOOL,
+    PromptInjectionGuard, RedactionConfig, Redactor, SPAWN_SUBAGENT_TOOL, STATE_PATCH_TOOL,
+    StabilityConfig, TickConcurrency, TickInProgress,
+};
 use anyhow::Result;
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use tracing::instrument;
 
-#[derive(Debug, Default)]
-struct BaselineModelProvider;
+/// Deterministic provider every kernel runs on: proposes the caller's tool
+/// or echoes the objective. Its streaming side sends each assistant message
+/// word by word, so hosts can exercise live text deltas end to end.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BaselineModelProvider;
 
 #[async_trait]
 impl ModelProviderPort for BaselineModelProvider {
@@ -57,12 +85,45 @@ impl ModelProviderPort for BaselineModelProvider {
     }
 }
 
+#[async_trait]
+impl StreamingModelProviderPort for BaselineModelProvider {
+    async fn stream(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletionStream> {
+        let mut completion = self.complete(request).await?;
+        let mut chunks = Vec::new();
+        for directive in std::mem::take(&mut completion.directives) {
+            match directive {
+                ModelDirective::Message { content, .. } => {
+                    chunks.extend(content.split_inclusive(' ').map(|word| {
+                        Ok(ModelStreamChunk::Directive {
+                            directive: ModelDirective::TextDelta {
+                                delta: word.to_owned(),
+                                index: Some(0),
+                            },
+                        })
+                    }));
+                }
+                directive => chunks.push(Ok(ModelStreamChunk::Directive { directive })),
+            }
+        }
+        chunks.push(Ok(ModelStreamChunk::Finished { completion }));
+        Ok(futures_util::stream::iter(chunks).boxed())
+    }
+}
+
 #[derive(Clone)]
 pub struct KernelBuilder {
     root: PathBuf,
     allowed_commands: Vec<String>,
     default_policy: PolicySet,
     turn_middlewares: Vec<Arc<dyn TurnMiddleware>>,
+    tick_concurrency: TickConcurrency,
+    redactor: Option<Redactor>,
+    prompt_injection_guard: Option<PromptInjectionGuard>,
+    agent_id: Option<AgentId>,
+    event_signer: Option<EventSigner>,
+    intent_gate: Option<IntentGate>,
+    stability: Option<StabilityConfig>,
+    streaming_provider: Option<Arc<dyn StreamingModelProviderPort>>,
 }
 
 impl KernelBuilder {
@@ -72,6 +133,14 @@ impl KernelBuilder {
             allowed_commands: vec!["echo".to_owned(), "git".to_owned(), "cargo".to_owned()],
             default_policy: PolicySet::default(),
             turn_middlewares: Vec::new(),
+            tick_concurrency: TickConcurrency::default(),
+            redactor: None,
+            prompt_injection_guard: None,
+            agent_id: None,
+            event_signer: None,
+            intent_gate: None,
+            stability: None,
+            streaming_provider: None,
         }
     }
 
@@ -90,42 +159,166 @@ impl KernelBuilder {
         self
     }
 
+    pub fn tick_concurrency(mut self, tick_concurrency: TickConcurrency) -> Self {
+        self.tick_concurrency = tick_concurrency;
+        self
+    }
+
+    /// Redactor applied to every journaled and published event and to
+    /// spilled tool outputs. `None` (the default) journals events as they
+    /// are; build one with [`Redactor::new`] (e.g. from
+    /// [`RedactionConfig::default`]).
+    pub fn redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Guard screening tool results for prompt injection. `None` (the
+    /// default) leaves tool results unscreened.
+    pub fn prompt_injection_guard(mut self, guard: Option<PromptInjectionGuard>) -> Self {
+        self.prompt_injection_guard = guard;
+        self
+    }
+
+    /// Agent whose persona, kept in the kernel's identity store, opens
+    /// every tick's system prompt. `None` (the default) sends no persona.
+    pub fn agent_id(mut self, agent_id: Option<AgentId>) -> Self {
+        self.agent_id = agent_id;
+        self
+    }
+
+    /// Sign every journaled record with `signer`; `None` (the default)
+    /// journals records unsigned.
+    pub fn event_signer(mut self, signer: Option<EventSigner>) -> Self {
+        self.event_signer = signer;
+        self
+    }
+
+    /// Gate side-effecting tool batches behind an intent that is evaluated,
+    /// and held for approval at the gate's risk threshold, before any call
+    /// runs. `None` (the default) executes tool calls directly.
+    pub fn intent_gate(mut self, gate: Option<IntentGate>) -> Self {
+        self.intent_gate = gate;
+        self
+    }
+
+    /// Estimate each session's stability margin, journal it every tick and
+    /// force the safe mode while it is negative. `None` (the default) turns
+    /// monitoring off.
+    pub fn stability_monitor(mut self, config: Option<StabilityConfig>) -> Self {
+        self.stability = config;
+        self
+    }
+
+    /// Stream tick completions through `provider`, publishing text deltas to
+    /// [`AiosKernel::subscribe_events`] as they arrive. `None` (the default)
+    /// completes each turn in one call to the baseline provider.
+    pub fn streaming_provider(
+        mut self,
+        provider: Option<Arc<dyn StreamingModelProviderPort>>,
+    ) -> Self {
+        self.streaming_provider = provider;
+        self
+    }
+
     pub fn build(self) -> AiosKernel {
         let events_root = self.root.join("kernel");
 
         let event_store_backend = Arc::new(FileEventStore::new(events_root));
         let stream = EventStreamHub::new(1024);
         let journal = Arc::new(EventJournal::new(event_store_backend, stream));
-        let event_store: Arc<dyn EventStorePort> = journal;
+        let mut event_store: Arc<dyn EventStorePort> = journal.clone();
+        let trusted_signers = self
+            .event_signer
+            .as_ref()
+            .map(EventSigner::trusted_signers)
+            .unwrap_or_default();
+        if let Some(signer) = self.event_signer {
+            // Innermost, so signatures cover the redacted bytes on disk.
+            event_store = Arc::new(SigningEventStore::new(event_store, signer));
+        }
+        if let Some(redactor) = &self.redactor {
+            event_store = Arc::new(RedactingEventStore::new(event_store, redactor.clone()));
+        }
 
         let approvals_engine = Arc::new(ApprovalQueue::default());
         let approvals: Arc<dyn aios_protocol::ApprovalPort> = approvals_engine;
         let policy_engine = Arc::new(SessionPolicyEngine::new(self.default_policy));
         let policy_gate: Arc<dyn PolicyGatePort> = policy_engine.clone();
 
+        let blob_store: Arc<dyn BlobStorePort> =
+            Arc::new(FileBlobStore::new(self.root.join("kernel").join("blobs")));
+
         let registry = Arc::new(ToolRegistry::with_core_tools());
+        let tool_annotations: Vec<_> = registry.annotations().collect();
         let sandbox = Arc::new(LocalSandboxRunner::new(self.allowed_commands));
-        let dispatcher = Arc::new(ToolDispatcher::new(registry, policy_engine, sandbox));
+        let dispatcher = Arc::new(
+            ToolDispatcher::new(registry, policy_engine, sandbox)
+                .with_blob_store(blob_store.clone()),
+        );
         let tool_harness: Arc<dyn ToolHarnessPort> = dispatcher;
 
+        let identity = Arc::new(FileIdentityStore::new(self.root.join("identity")));
+        let world = Arc::new(FileWorldStore::new(self.root.join("world")));
+
         let provider: Arc<dyn ModelProviderPort> = Arc::new(BaselineModelProvider);
+        let mut config = RuntimeConfig::new(self.root);
+        config.tick_concurrency = self.tick_concurrency;
         let runtime = KernelRuntime::with_turn_middlewares(
-            RuntimeConfig::new(self.root),
+            config,
             event_store,
             provider,
             tool_harness,
             approvals,
             policy_gate,
             self.turn_middlewares,
-        );
+        )
+        .with_tool_annotations(tool_annotations)
+        .with_blob_store(blob_store.clone());
+        let runtime = match self.redactor {
+            Some(redactor) => runtime.with_redactor(redactor),
+            None => runtime,
+        };
+        let runtime = match self.prompt_injection_guard {
+            Some(guard) => runtime.with_prompt_injection_guard(guard),
+            None => runtime,
+        };
+        let runtime = match self.agent_id {
+            Some(agent_id) => runtime.with_identity(identity.clone(), agent_id),
+            None => runtime,
+        };
+        let runtime = match self.intent_gate {
+            Some(gate) => runtime.with_intent_gate(gate),
+            None => runtime,
+        };
+        let runtime = match self.stability {
+            Some(config) => runtime.with_stability_monitor(config),
+            None => runtime,
+        };
+        let runtime = match self.streaming_provider {
+            Some(provider) => runtime.with_streaming_provider(provider),
+            None => runtime,
+        };
 
-        AiosKernel { runtime }
+        AiosKernel {
+            runtime,
+            identity,
+            world,
+            blob_store,
+            journal,
+            trusted_signers,
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct AiosKernel {
     runtime: KernelRuntime,
+    identity: Arc<FileIdentityStore>,
+    world: Arc<FileWorldStore>,
+    blob_store: Arc<dyn BlobStorePort>,
+    journal: Arc<EventJournal>,
+    trusted_signers: TrustedSigners,
 }
 
 impl AiosKernel {
@@ -141,6 +334,17 @@ impl AiosKernel {
             .await
     }
 
+    #[instrument(skip(self, policy), fields(parent_session_id = %parent_session_id))]
+    pub async fn create_child_session(
+        &self,
+        parent_session_id: &SessionId,
+        policy: PolicySet,
+    ) -> Result<SessionManifest> {
+        self.runtime
+            .create_child_session(parent_session_id, policy)
+            .await
+    }
+
     #[instrument(skip(self, objective, proposed_tool), fields(session_id = %session_id))]
     pub async fn tick(
         &self,
@@ -223,6 +427,16 @@ impl AiosKernel {
             .await
     }
 
+    /// Journal `SessionClosed` and reject further ticks on the session.
+    #[instrument(skip(self, reason), fields(session_id = %session_id))]
+    pub async fn close_session(
+        &self,
+        session_id: &SessionId,
+        reason: impl Into<String>,
+    ) -> Result<()> {
+        self.runtime.close_session(session_id, reason).await
+    }
+
     pub async fn resolve_approval(
         &self,
         session_id: &SessionId,
@@ -235,6 +449,89 @@ impl AiosKernel {
             .await
     }
 
+    /// Report the session's records (on one branch, or all) that are
+    /// unsigned or not validly signed. A record must be signed by the event
+    /// signer's key for its agent (or its system key); without an event
+    /// signer no key is trusted.
+    pub async fn verify_event_signatures(
+        &self,
+        session_id: &SessionId,
+        branch_id: Option<BranchId>,
+    ) -> Result<SignatureReport> {
+        self.journal
+            .verify_signatures(session_id.clone(), branch_id, &self.trusted_signers)
+            .await
+    }
+
+    /// Souls and beliefs of the kernel's agents.
+    pub fn identity(&self) -> &FileIdentityStore {
+        &self.identity
+    }
+
+    /// Versioned world models shared by the kernel's sessions.
+    pub fn world(&self) -> &FileWorldStore {
+        &self.world
+    }
+
+    /// Store `world`'s current snapshot as a blob and journal it on the
+    /// session as `WorldModelObserved`.
+    pub async fn observe_world(
+        &self,
+        session_id: &SessionId,
+        world: &WorldId,
+    ) -> Result<WorldSnapshot> {
+        let snapshot = WorldPort::snapshot(self.world.as_ref(), world.clone()).await?;
+        let state_ref = self
+            .blob_store
+            .put(
+                serde_json::to_vec(&snapshot)?.into(),
+                Some("application/json".to_owned()),
+            )
+            .await?;
+        self.record_external_event(
+            session_id,
+            EventKind::WorldModelObserved {
+                state_ref,
+                meta: serde_json::json!({ "world": world, "version": snapshot.version }),
+            },
+        )
+        .await?;
+        Ok(snapshot)
+    }
+
+    /// Store the commits of `world` after `from_version` as a blob and
+    /// journal them on the session as a scored `WorldModelRollout`.
+    pub async fn record_world_rollout(
+        &self,
+        session_id: &SessionId,
+        world: &WorldId,
+        from_version: u64,
+        score: Option<f32>,
+    ) -> Result<BlobHash> {
+        let commits = self.world.history(world, from_version).await?;
+        let trajectory = serde_json::json!({
+            "world": world,
+            "from_version": from_version,
+            "commits": commits,
+        });
+        let trajectory_ref = self
+            .blob_store
+            .put(
+                serde_json::to_vec(&trajectory)?.into(),
+                Some("application/json".to_owned()),
+            )
+            .await?;
+        self.record_external_event(
+            session_id,
+            EventKind::WorldModelRollout {
+                trajectory_ref: trajectory_ref.clone(),
+                score,
+            },
+        )
+        .await?;
+        Ok(trajectory_ref)
+    }
+
     pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<EventRecord> {
         self.runtime.subscribe_events()
     }
@@ -280,6 +577,195 @@ impl AiosKernel {
             .read_events_on_branch(session_id, branch_id, from_sequence, limit)
             .await
     }
+
+    pub async fn canonical_state(
+        &self,
+        session_id: &SessionId,
+        branch_id: &BranchId,
+    ) -> Result<VersionedCanonicalState> {
+        self.runtime.canonical_state(session_id, branch_id).await
+    }
+}
+
+/// `SessionPort` over the kernel, so downstream crates can hold an
+/// `Arc<dyn SessionPort>` instead of `AiosKernel` itself.
+///
+/// `tick` runs up to `max_iterations` (default 1) kernel ticks, continuing
+/// while the previous one executed tools; `proposed_tool` only applies to
+/// the first. The result reports the last run's stop reason and final
+/// answer, and token usage summed across iterations.
+#[async_trait]
+impl SessionPort for AiosKernel {
+    async fn create(&self, req: CreateSessionRequest) -> KernelResult<SessionManifest> {
+        self.runtime
+            .create_session_from_request(req, ModelRouting::default())
+            .await
+            .map_err(runtime_error)
+    }
+
+    async fn get(&self, id: SessionId) -> KernelResult<SessionManifest> {
+        self.runtime
+            .session_manifest(&id)
+            .ok_or_else(|| session_not_found(&id))
+    }
+
+    async fn list(&self, filter: SessionFilter) -> KernelResult<Vec<SessionManifest>> {
+        let mut sessions: Vec<SessionManifest> = self
+            .runtime
+            .list_sessions()
+            .into_iter()
+            .filter(|manifest| filter.matches(manifest))
+            .collect();
+        sessions.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.session_id.as_str().cmp(b.session_id.as_str()))
+        });
+        if let Some(limit) = filter.limit {
+            sessions.truncate(limit as usize);
+        }
+        Ok(sessions)
+    }
+
+    async fn tick(
+        &self,
+        id: SessionId,
+        input: SessionTickInput,
+    ) -> KernelResult<SessionTickOutput> {
+        if !self.runtime.session_exists(&id) {
+            return Err(session_not_found(&id));
+        }
+        if self.runtime.is_session_closed(&id) {
+            return Err(KernelError::InvalidState(format!("session {id} is closed")));
+        }
+
+        let max_iterations = input.max_iterations.unwrap_or(1).max(1);
+        let main = BranchId::main();
+        let mut next_sequence = self
+            .runtime
+            .list_branches(&id)
+            .await
+            .map_err(runtime_error)?
+            .into_iter()
+            .find(|branch| branch.branch_id == main)
+            .map_or(0, |branch| branch.head_sequence)
+            + 1;
+        let mut proposed_tool = input.proposed_tool;
+        let mut stop_reason = ModelStopReason::Completed;
+        let mut final_answer = None;
+        let mut usage: Option<TokenUsage> = None;
+        let mut iteration = 0;
+        let mut wants_more = false;
+
+        while iteration < max_iterations {
+            iteration += 1;
+            let output = self
+                .tick(&id, input.objective.clone(), proposed_tool.take())
+                .await
+                .map_err(runtime_error)?;
+            let events = self
+                .runtime
+                .read_events_on_branch(&id, &main, next_sequence, usize::MAX)
+                .await
+                .map_err(runtime_error)?;
+            next_sequence = output.last_sequence + 1;
+
+            final_answer = None;
+            for event in events {
+                if let EventKind::RunFinished {
+                    reason,
+                    final_answer: answer,
+                    usage: run_usage,
+                    ..
+                } = event.kind
+                {
+                    stop_reason = parse_stop_reason(&reason);
+                    final_answer = answer;
+                    if let Some(run_usage) = run_usage {
+                        let total = usage.get_or_insert(TokenUsage {
+                            prompt_tokens: 0,
+                            completion_tokens: 0,
+                            total_tokens: 0,
+                        });
+                        total.prompt_tokens += run_usage.prompt_tokens;
+                        total.completion_tokens += run_usage.completion_tokens;
+                        total.total_tokens += run_usage.total_tokens;
+                    }
+                }
+            }
+
+            wants_more = output.tool_calls_executed > 0
+                && !matches!(
+                    output.mode,
+                    OperatingMode::AskHuman | OperatingMode::Recover
+                );
+            if !wants_more {
+                break;
+            }
+        }
+        if wants_more && max_iterations > 1 {
+            stop_reason = ModelStopReason::MaxIterations;
+        }
+
+        let mut output = SessionTickOutput::new(id, iteration, stop_reason);
+        if let Some(answer) = final_answer {
+            output = output.with_final_answer(answer);
+        }
+        if let Some(usage) = usage {
+            output = output.with_usage(usage);
+        }
+        Ok(output)
+    }
+
+    async fn stream_events(
+        &self,
+        id: SessionId,
+        branch: BranchId,
+        after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        if !self.runtime.session_exists(&id) {
+            return Err(session_not_found(&id));
+        }
+        self.runtime
+            .stream_events(&id, &branch, after_sequence)
+            .await
+            .map_err(runtime_error)
+    }
+
+    async fn close(&self, id: SessionId, reason: String) -> KernelResult<()> {
+        if !self.runtime.session_exists(&id) {
+            return Err(session_not_found(&id));
+        }
+        if self.runtime.is_session_closed(&id) {
+            return Err(KernelError::InvalidState(format!(
+                "session {id} is already closed"
+            )));
+        }
+        self.runtime
+            .close_session(&id, reason)
+            .await
+            .map_err(runtime_error)
+    }
+}
+
+fn runtime_error(error: anyhow::Error) -> KernelError {
+    KernelError::Runtime(format!("{error:#}"))
+}
+
+fn session_not_found(id: &SessionId) -> KernelError {
+    KernelError::InvalidState(format!("session not found: {id}"))
+}
+
+/// Inverse of the runtime's `RunFinished.reason` rendering.
+fn parse_stop_reason(reason: &str) -> ModelStopReason {
+    match reason {
+        "completed" => ModelStopReason::Completed,
+        "tool_call" => ModelStopReason::ToolCall,
+        "max_iterations" => ModelStopReason::MaxIterations,
+        "cancelled" => ModelStopReason::Cancelled,
+        "error" => ModelStopReason::Error,
+        other => ModelStopReason::Other(other.to_owned()),
+    }
 }
 
 #[cfg(test)]
@@ -288,7 +774,14 @@ mod tests {
     use std::sync::Arc;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use aios_protocol::{BranchId, Capability, EventKind, OperatingMode, PolicySet, ToolCall};
+    use aios_protocol::ports::SessionPort;
+    use aios_protocol::session::{
+        CreateSessionRequest, SessionFilter, TickInput as SessionTickInput,
+    };
+    use aios_protocol::{
+        BranchId, Capability, EventKind, KernelError, ModelStopReason, OperatingMode,
+        PolicyDecisionKind, PolicySet, SessionId, SessionManifest, ToolCall,
+    };
     use aios_runtime::{
         LoopDetectionMiddleware, TickOutput, TurnContext, TurnMiddleware, TurnNext,
     };
@@ -334,6 +827,7 @@ mod tests {
                 Capability::exec("*"),
             ],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 128,
         };
@@ -366,6 +860,113 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn tick_events_share_run_correlation_and_tool_results_name_their_request() -> Result<()> {
+        let root = unique_test_root("aios-kernel-causality");
+        let kernel = KernelBuilder::new(&root).build();
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_write("/session/**")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 128,
+        };
+        let session = kernel.create_session("tester", policy, None).await?;
+        let before = kernel
+            .read_events(&session.session_id, 1, 10_000)
+            .await?
+            .len();
+
+        let call = ToolCall::new(
+            "fs.write",
+            json!({ "path": "artifacts/causality.txt", "content": "ok" }),
+            vec![Capability::fs_write("/session/artifacts/**")],
+        );
+        kernel
+            .tick(&session.session_id, "write with causality", Some(call))
+            .await?;
+
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let tick_events = &events[before..];
+        let correlation = tick_events[0].correlation_id.clone();
+        assert!(correlation.is_some());
+        assert!(
+            tick_events
+                .iter()
+                .all(|event| event.correlation_id == correlation)
+        );
+
+        let requested = tick_events
+            .iter()
+            .find(|event| matches!(event.kind, EventKind::ToolCallRequested { .. }))
+            .expect("tool call requested");
+        let completed = tick_events
+            .iter()
+            .find(|event| matches!(event.kind, EventKind::ToolCallCompleted { .. }))
+            .expect("tool call completed");
+        assert_eq!(completed.causation_id.as_ref(), Some(&requested.event_id));
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deny_rules_are_journaled_with_the_matching_rule() -> Result<()> {
+        let root = unique_test_root("aios-kernel-deny-rules");
+        let kernel = KernelBuilder::new(&root).build();
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_write("/session/**")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![Capability::fs_write("/session/state/**")],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 128,
+        };
+        let session = kernel.create_session("tester", policy, None).await?;
+
+        let call = ToolCall::new(
+            "fs.write",
+            json!({ "path": "state/plan.json", "content": "{}" }),
+            vec![Capability::fs_write("/session/state/plan.json")],
+        );
+        kernel
+            .tick(&session.session_id, "overwrite state", Some(call))
+            .await?;
+
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let (decision, rule_id, explanation) = events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::PolicyEvaluated {
+                    decision,
+                    rule_id,
+                    explanation,
+                    ..
+                } => Some((*decision, rule_id.clone(), explanation.clone())),
+                _ => None,
+            })
+            .expect("policy evaluated");
+        assert_eq!(decision, PolicyDecisionKind::Deny);
+        assert_eq!(rule_id.as_deref(), Some("deny:fs:write:/session/state/**"));
+        assert!(
+            explanation
+                .unwrap_or_default()
+                .contains("overriding allow:fs:write:/session/**")
+        );
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event.kind, EventKind::ToolCallFailed { .. }))
+        );
+        assert!(
+            !PathBuf::from(&session.workspace_root)
+                .join("state/plan.json")
+                .exists()
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn turn_middleware_can_rewrite_turn_objective() -> Result<()> {
         let root = unique_test_root("aios-kernel-turn-middleware");
@@ -421,6 +1022,7 @@ mod tests {
         let policy = PolicySet {
             allow_capabilities: vec![Capability::fs_write("/session/artifacts/**")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 128,
         };
@@ -491,6 +1093,7 @@ mod tests {
         let policy = PolicySet {
             allow_capabilities: vec![Capability::fs_write("/session/artifacts/**")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 128,
         };
@@ -563,6 +1166,7 @@ mod tests {
         let restrictive_policy = PolicySet {
             allow_capabilities: vec![Capability::fs_read("/session/**")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 128,
         };
@@ -595,6 +1199,116 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn tool_capabilities_are_derived_from_call_input() -> Result<()> {
+        let root = unique_test_root("aios-kernel-derived-capabilities");
+        let kernel = KernelBuilder::new(&root).allowed_commands(vec![]).build();
+        let policy = PolicySet {
+            allow_capabilities: vec![
+                Capability::exec("git"),
+                Capability::fs_write("/session/artifacts/**"),
+            ],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 128,
+        };
+        let session = kernel.create_session("tester", policy, None).await?;
+
+        // Both calls claim capabilities the policy allows, but their inputs
+        // name something else.
+        let shell = ToolCall::new(
+            "shell.exec",
+            json!({ "command": "echo", "args": ["not git"] }),
+            vec![Capability::exec("git")],
+        );
+        kernel
+            .tick(&session.session_id, "run a command", Some(shell))
+            .await?;
+        let write = ToolCall::new(
+            "fs.write",
+            json!({ "path": "artifacts/../notes.md", "content": "escaped" }),
+            vec![Capability::fs_write("/session/artifacts/**")],
+        );
+        kernel
+            .tick(&session.session_id, "write notes", Some(write))
+            .await?;
+
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let failures: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::ToolCallFailed { error, .. } => Some(error.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failures.len(), 2);
+        assert!(
+            failures
+                .iter()
+                .all(|error| error.contains("capabilities denied"))
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event.kind, EventKind::ToolCallCompleted { .. }))
+        );
+        assert!(
+            !PathBuf::from(&session.workspace_root)
+                .join("notes.md")
+                .exists()
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_deny_on_a_command_is_not_bypassed_by_its_path() -> Result<()> {
+        let root = unique_test_root("aios-kernel-exec-deny");
+        // The sandbox would run either spelling; only the policy stops them.
+        let kernel = KernelBuilder::new(&root)
+            .allowed_commands(vec!["rm".to_owned(), "/bin/rm".to_owned()])
+            .build();
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::exec("*")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![Capability::exec("rm")],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 128,
+        };
+        let session = kernel.create_session("tester", policy, None).await?;
+        let keep = PathBuf::from(&session.workspace_root).join("keep.txt");
+        fs::write(&keep, "still here").await?;
+
+        for command in ["/bin/rm", "rm"] {
+            let call = ToolCall::new(
+                "shell.exec",
+                json!({ "command": command, "args": ["keep.txt"] }),
+                vec![Capability::exec("ls")],
+            );
+            kernel
+                .tick(&session.session_id, "clean up", Some(call))
+                .await?;
+        }
+
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let failures: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::ToolCallFailed { error, .. } => Some(error.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].contains("bare program name"));
+        assert!(failures[1].contains("capabilities denied: exec:cmd:rm"));
+        assert!(keep.exists());
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn branch_ticks_keep_independent_sequences() -> Result<()> {
         let root = unique_test_root("aios-kernel-branches");
@@ -805,4 +1519,409 @@ mod tests {
         let _ = fs::remove_dir_all(root).await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn large_tool_output_spills_to_blob_and_pages_back() -> Result<()> {
+        let root = unique_test_root("aios-kernel-blob-spill");
+        let kernel = KernelBuilder::new(&root).build();
+        let session = kernel
+            .create_session("tester", PolicySet::default(), None)
+            .await?;
+
+        let content = "0123456789abcdef\n".repeat(2048);
+        let write = ToolCall::new(
+            "fs.write",
+            json!({ "path": "artifacts/big.txt", "content": content }),
+            vec![],
+        );
+        kernel
+            .tick(&session.session_id, "write big file", Some(write))
+            .await?;
+        let read = ToolCall::new("fs.read", json!({ "path": "artifacts/big.txt" }), vec![]);
+        kernel
+            .tick(&session.session_id, "read big file", Some(read))
+            .await?;
+
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let spilled = events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::ToolCallCompleted {
+                    tool_name, result, ..
+                } if tool_name == "fs.read" => Some(result["output"].clone()),
+                _ => None,
+            })
+            .expect("fs.read completed");
+        assert_eq!(spilled["truncated"], json!(true));
+        assert!(spilled["preview"].as_str().unwrap().len() <= 2048);
+        let blob_id = spilled["blob"]["blob_id"].as_str().unwrap().to_owned();
+        let total_bytes = spilled["total_bytes"].as_u64().unwrap();
+
+        let page = ToolCall::new(
+            "blob.read",
+            json!({ "blob_id": blob_id, "offset": 0, "length": 100 }),
+            vec![],
+        );
+        kernel
+            .tick(&session.session_id, "page the output", Some(page))
+            .await?;
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        let output = events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::ToolCallCompleted {
+                    tool_name, result, ..
+                } if tool_name == "blob.read" => Some(result["output"].clone()),
+                _ => None,
+            })
+            .expect("blob.read completed");
+        assert_eq!(output["length"], json!(100));
+        assert_eq!(output["next_offset"], json!(100));
+        assert_eq!(output["total_bytes"], json!(total_bytes));
+        assert!(
+            output["content"]
+                .as_str()
+                .unwrap()
+                .contains("0123456789abcdef")
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blob_reads_are_scoped_to_the_spilling_session() -> Result<()> {
+        let root = unique_test_root("aios-kernel-blob-scope");
+        let kernel = KernelBuilder::new(&root).build();
+        let owner = kernel
+            .create_session("tester", PolicySet::default(), None)
+            .await?;
+        let content = "0123456789abcdef\n".repeat(2048);
+        let write = ToolCall::new(
+            "fs.write",
+            json!({ "path": "artifacts/big.txt", "content": content }),
+            vec![],
+        );
+        kernel.tick(&owner.session_id, "write", Some(write)).await?;
+        let read = ToolCall::new("fs.read", json!({ "path": "artifacts/big.txt" }), vec![]);
+        kernel.tick(&owner.session_id, "read", Some(read)).await?;
+        let blob_id = kernel
+            .read_events(&owner.session_id, 1, 10_000)
+            .await?
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::ToolCallCompleted { result, .. } => result["output"]["blob"]["blob_id"]
+                    .as_str()
+                    .map(ToOwned::to_owned),
+                _ => None,
+            })
+            .expect("fs.read output spilled");
+        let page = || {
+            ToolCall::new(
+                "blob.read",
+                json!({ "blob_id": blob_id, "offset": 0, "length": 16 }),
+                vec![],
+            )
+        };
+        let blob_read_failure = |events: &[aios_protocol::EventRecord]| {
+            events.iter().find_map(|event| match &event.kind {
+                EventKind::ToolCallFailed {
+                    tool_name, error, ..
+                } if tool_name == "blob.read" => Some(error.clone()),
+                _ => None,
+            })
+        };
+
+        let other = kernel
+            .create_session("intruder", PolicySet::default(), None)
+            .await?;
+        kernel.tick(&other.session_id, "peek", Some(page())).await?;
+        let events = kernel.read_events(&other.session_id, 1, 10_000).await?;
+        let error = blob_read_failure(&events).expect("foreign blob read fails");
+        assert!(error.contains("not found in this session"), "{error}");
+
+        kernel.tick(&owner.session_id, "page", Some(page())).await?;
+        let events = kernel.read_events(&owner.session_id, 1, 10_000).await?;
+        assert!(blob_read_failure(&events).is_none());
+        assert!(events.iter().any(|event| matches!(
+            &event.kind,
+            EventKind::ToolCallCompleted { tool_name, .. } if tool_name == "blob.read"
+        )));
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn session_port_lists_sessions_by_owner_labels_and_creation_time() -> Result<()> {
+        let root = unique_test_root("aios-kernel-session-port-list");
+        let kernel = KernelBuilder::new(&root).build();
+        let port: &dyn SessionPort = &kernel;
+
+        let nightly = port
+            .create(CreateSessionRequest::new("alice").with_labels(["nightly", "billing"]))
+            .await?;
+        let adhoc = port
+            .create(CreateSessionRequest::new("alice").with_labels(["adhoc"]))
+            .await?;
+        let other = port.create(CreateSessionRequest::new("bob")).await?;
+        assert_eq!(nightly.labels, vec!["nightly", "billing"]);
+        assert_eq!(port.get(adhoc.session_id.clone()).await?.owner, "alice");
+
+        let ids = |sessions: Vec<SessionManifest>| {
+            sessions
+                .into_iter()
+                .map(|manifest| manifest.session_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(port
+                .list(SessionFilter::default().with_owner("alice"))
+                .await?),
+            vec![nightly.session_id.clone(), adhoc.session_id.clone()]
+        );
+        assert_eq!(
+            ids(port
+                .list(SessionFilter::default().with_labels(["billing"]))
+                .await?),
+            vec![nightly.session_id.clone()]
+        );
+        assert_eq!(
+            ids(port
+                .list(SessionFilter::default().created_after(adhoc.created_at))
+                .await?),
+            vec![other.session_id.clone()]
+        );
+        assert_eq!(
+            port.list(SessionFilter::default().with_limit(2))
+                .await?
+                .len(),
+            2
+        );
+        assert!(port.get(SessionId::default()).await.is_err());
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn session_port_close_journals_session_closed_and_rejects_ticks() -> Result<()> {
+        let root = unique_test_root("aios-kernel-session-port-close");
+        let kernel = KernelBuilder::new(&root).build();
+        let port: &dyn SessionPort = &kernel;
+
+        let session = port.create(CreateSessionRequest::new("tester")).await?;
+        let output = port
+            .tick(
+                session.session_id.clone(),
+                SessionTickInput::new("summarize"),
+            )
+            .await?;
+        assert_eq!(output.iteration, 1);
+        assert!(matches!(output.stop_reason, ModelStopReason::Completed));
+        assert_eq!(
+            output.final_answer.as_deref(),
+            Some("objective received: summarize")
+        );
+        assert_eq!(output.usage.map(|usage| usage.total_tokens), Some(20));
+
+        port.close(session.session_id.clone(), "done".to_owned())
+            .await?;
+        let events = kernel.read_events(&session.session_id, 1, 10_000).await?;
+        assert!(matches!(
+            &events.last().expect("events").kind,
+            EventKind::SessionClosed { reason } if reason == "done"
+        ));
+
+        let rejected = port
+            .tick(session.session_id.clone(), SessionTickInput::new("again"))
+            .await;
+        assert!(matches!(rejected, Err(KernelError::InvalidState(_))));
+        assert!(
+            kernel
+                .tick(&session.session_id, "again", None)
+                .await
+                .is_err()
+        );
+        assert!(
+            port.close(session.session_id.clone(), "twice".to_owned())
+                .await
+                .is_err()
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_session_closed_after_a_long_journal_stays_closed_when_reattached() -> Result<()> {
+        let root = unique_test_root("aios-kernel-session-reattach-closed");
+        let kernel = KernelBuilder::new(&root).build();
+        let session = kernel
+            .create_session("tester", PolicySet::default(), None)
+            .await?;
+        // More than one journal scan page, so the close is not on the first.
+        for index in 0..1_100 {
+            kernel
+                .runtime
+                .record_external_event(
+                    &session.session_id,
+                    EventKind::Custom {
+                        event_type: "test.filler".to_owned(),
+                        data: serde_json::json!({ "index": index }),
+                    },
+                )
+                .await?;
+        }
+        kernel.close_session(&session.session_id, "done").await?;
+        drop(kernel);
+
+        let reopened = KernelBuilder::new(&root).build();
+        reopened
+            .runtime
+            .create_session_with_id(
+                session.session_id.clone(),
+                "tester",
+                PolicySet::default(),
+                aios_protocol::ModelRouting::default(),
+            )
+            .await?;
+        assert!(reopened.runtime.is_session_closed(&session.session_id));
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn journaled_events_are_signed_by_the_session_agent() -> Result<()> {
+        use aios_events::{AgentKey, EventSigner};
+        use aios_protocol::AgentId;
+
+        let root = unique_test_root("aios-kernel-signed");
+        let agent = AgentId::from_string("agent-signer");
+        let agent_key = AgentKey::generate();
+        let kernel = KernelBuilder::new(&root)
+            .agent_id(Some(agent.clone()))
+            .event_signer(Some(
+                EventSigner::new(AgentKey::generate()).with_agent_key(agent, agent_key.clone()),
+            ))
+            .build();
+
+        let session = kernel
+            .create_session("tester", PolicySet::default(), None)
+            .await?;
+        kernel.tick(&session.session_id, "say hello", None).await?;
+
+        let report = kernel
+            .verify_event_signatures(&session.session_id, None)
+            .await?;
+        assert!(report.is_clean(), "{report:?}");
+        assert!(
+            report
+                .verified
+                .iter()
+                .any(|(_, signer)| signer == agent_key.did())
+        );
+
+        // Editing a journaled record on disk breaks its signature.
+        let log = root
+            .join("kernel")
+            .join("events")
+            .join(format!("{}.jsonl", session.session_id.as_str()));
+        let journal = fs::read_to_string(&log).await?;
+        fs::write(&log, journal.replacen("say hello", "say goodbye", 1)).await?;
+        let report = kernel
+            .verify_event_signatures(&session.session_id, None)
+            .await?;
+        assert_eq!(report.invalid.len(), 1, "{report:?}");
+
+        // So does re-signing one with a key the kernel never used, however
+        // valid that signature is on its own.
+        let mut lines: Vec<String> = fs::read_to_string(&log)
+            .await?
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect();
+        let mut forged: aios_protocol::EventRecord = serde_json::from_str(&lines[1])?;
+        EventSigner::new(AgentKey::generate()).sign(&mut forged);
+        lines[1] = serde_json::to_string(&forged)?;
+        fs::write(&log, lines.join("\n") + "\n").await?;
+        let report = kernel
+            .verify_event_signatures(&session.session_id, None)
+            .await?;
+        assert_eq!(report.invalid.len(), 2, "{report:?}");
+        assert!(
+            report
+                .invalid
+                .iter()
+                .any(|(sequence, reason)| *sequence == forged.sequence
+                    && reason.contains("untrusted signer")),
+            "{report:?}"
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn world_observations_and_rollouts_are_journaled() -> Result<()> {
+        use aios_protocol::AgentId;
+        use aios_protocol::ports::WorldPort;
+        use aios_protocol::world::{WorldId, WorldMutation, WorldMutationOp};
+
+        let root = unique_test_root("aios-kernel-world");
+        let kernel = KernelBuilder::new(&root).build();
+        let session = kernel
+            .create_session("tester", PolicySet::default(), None)
+            .await?;
+        let world = WorldId::new("warehouse");
+        let mutation = |path: &str, value| {
+            WorldMutation::new(
+                AgentId::from_string("agent"),
+                vec![WorldMutationOp::set(path, value)],
+            )
+        };
+
+        kernel
+            .world()
+            .mutate(world.clone(), mutation("/shelves", json!(3)))
+            .await?;
+        let snapshot = kernel.observe_world(&session.session_id, &world).await?;
+        assert_eq!(snapshot.version, 1);
+        kernel
+            .world()
+            .mutate(world.clone(), mutation("/shelves", json!(4)))
+            .await?;
+        let trajectory = kernel
+            .record_world_rollout(&session.session_id, &world, 1, Some(0.5))
+            .await?;
+
+        let events = kernel
+            .read_events(&session.session_id, 0, usize::MAX)
+            .await?;
+        let observed = events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::WorldModelObserved { state_ref, meta } => {
+                    Some((state_ref.clone(), meta.clone()))
+                }
+                _ => None,
+            })
+            .expect("world observed");
+        assert_eq!(observed.1, json!({ "world": "warehouse", "version": 1 }));
+        let stored: serde_json::Value =
+            serde_json::from_slice(&kernel.blob_store.get(observed.0).await?)?;
+        assert_eq!(stored["state"], json!({ "shelves": 3 }));
+        assert!(events.iter().any(|event| matches!(
+            &event.kind,
+            EventKind::WorldModelRollout { trajectory_ref, score: Some(score) }
+                if *trajectory_ref == trajectory && *score == 0.5
+        )));
+        let rollout: serde_json::Value =
+            serde_json::from_slice(&kernel.blob_store.get(trajectory).await?)?;
+        assert_eq!(rollout["commits"].as_array().map(Vec::len), Some(1));
+
+        let _ = fs::remove_dir_all(root).await;
+        Ok(())
+    }
 }
@@ -3,8 +3,8 @@ use std::sync::Arc;
 
 use aios_protocol::{
     ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution as PortApprovalResolution,
-    ApprovalTicket as PortApprovalTicket, Capability, KernelError, PolicyGateDecision,
-    PolicyGatePort, PolicySet, SessionId,
+    ApprovalTicket as PortApprovalTicket, Capability, CapabilitySpec, KernelError,
+    PolicyDecisionKind, PolicyGateDecision, PolicyGatePort, PolicyRuleMatch, PolicySet, SessionId,
 };
 use async_trait::async_trait;
 use chrono::Utc;
@@ -18,6 +18,8 @@ pub struct PolicyEvaluation {
     pub allowed: Vec<Capability>,
     pub requires_approval: Vec<Capability>,
     pub denied: Vec<Capability>,
+    /// The deciding rule per requested capability, in request order.
+    pub matches: Vec<PolicyRuleMatch>,
 }
 
 impl PolicyEvaluation {
@@ -51,32 +53,149 @@ pub trait PolicyEngine: Send + Sync {
     ) -> PolicyEvaluation;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleKind {
+    Allow,
+    Gate,
+    Deny,
+}
+
+impl RuleKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Gate => "gate",
+            Self::Deny => "deny",
+        }
+    }
+
+    fn decision(self) -> PolicyDecisionKind {
+        match self {
+            Self::Allow => PolicyDecisionKind::Allow,
+            Self::Gate => PolicyDecisionKind::RequireApproval,
+            Self::Deny => PolicyDecisionKind::Deny,
+        }
+    }
+
+    /// Tie-break between equally specific patterns: deny > gate > allow.
+    fn precedence(self) -> u8 {
+        match self {
+            Self::Allow => 0,
+            Self::Gate => 1,
+            Self::Deny => 2,
+        }
+    }
+}
+
+/// Evaluates capabilities against a [`PolicySet`]: the most specific
+/// pattern covering the request decides, deny > gate > allow between equally
+/// specific ones, and anything unmatched is denied. Patterns are matched
+/// with the capability algebra of [`aios_protocol::capability`], so
+/// `fs:read:/session/**` does not cover `fs:read:/session/../etc/passwd`.
 #[derive(Debug, Clone)]
 pub struct StaticPolicyEngine {
-    allow: Vec<Capability>,
-    gate: Vec<Capability>,
+    policy: PolicySet,
 }
 
 impl StaticPolicyEngine {
     pub fn from_policy_set(policy: &PolicySet) -> Self {
         Self {
-            allow: policy.allow_capabilities.clone(),
-            gate: policy.gate_capabilities.clone(),
+            policy: policy.clone(),
         }
     }
 
-    fn matches(pattern: &str, actual: &str) -> bool {
-        if pattern.ends_with('*') {
-            let prefix = pattern.trim_end_matches('*');
-            prefix.is_empty() || actual.starts_with(prefix)
-        } else {
-            pattern == actual
-        }
+    /// The policy set this engine evaluates against.
+    pub fn policy(&self) -> &PolicySet {
+        &self.policy
     }
 
-    fn set_contains(set: &[Capability], requested: &Capability) -> bool {
-        set.iter()
-            .any(|candidate| Self::matches(candidate.as_str(), requested.as_str()))
+    /// Exact patterns rank above every wildcard; wildcards rank by the
+    /// length of their literal prefix. A brace pattern ranks as the most
+    /// specific of its alternatives that covers `requested`.
+    fn specificity(pattern: &Capability, requested: &Capability) -> (bool, usize) {
+        let Ok(spec) = pattern.parse() else {
+            return (true, pattern.as_str().len());
+        };
+        let Ok(requested) = requested.parse() else {
+            return spec.specificity();
+        };
+        spec.alternatives()
+            .iter()
+            .filter(|alternative| alternative.covers(&requested))
+            .map(CapabilitySpec::specificity)
+            .max()
+            .unwrap_or_else(|| spec.specificity())
+    }
+
+    fn rules(&self) -> impl Iterator<Item = (RuleKind, &Capability)> {
+        [
+            (RuleKind::Allow, &self.policy.allow_capabilities),
+            (RuleKind::Gate, &self.policy.gate_capabilities),
+            (RuleKind::Deny, &self.policy.deny_capabilities),
+        ]
+        .into_iter()
+        .flat_map(|(kind, set)| set.iter().map(move |pattern| (kind, pattern)))
+    }
+
+    fn decide(&self, requested: &Capability) -> PolicyRuleMatch {
+        if let Err(error) = requested.parse() {
+            return PolicyRuleMatch {
+                capability: requested.clone(),
+                decision: PolicyDecisionKind::Deny,
+                rule_id: None,
+                explanation: format!("invalid capability {}: {error}", requested.as_str()),
+            };
+        }
+
+        let mut matched: Vec<(RuleKind, &Capability)> = self
+            .rules()
+            .filter(|(_, pattern)| pattern.covers(requested))
+            .collect();
+        matched.sort_by_key(|(kind, pattern)| {
+            std::cmp::Reverse((Self::specificity(pattern, requested), kind.precedence()))
+        });
+
+        let Some(&(mut kind, mut pattern)) = matched.first() else {
+            return PolicyRuleMatch {
+                capability: requested.clone(),
+                decision: PolicyDecisionKind::Deny,
+                rule_id: None,
+                explanation: format!("no rule matches {}; denied by default", requested.as_str()),
+            };
+        };
+        let mut overridden: Vec<String> = matched[1..]
+            .iter()
+            .filter(|(other, _)| *other != kind)
+            .map(|(other, pattern)| format!("{}:{}", other.label(), pattern.as_str()))
+            .collect();
+
+        // A stricter rule covering only part of a requested pattern still
+        // applies to that part, so it decides the whole request.
+        let carved_out = self
+            .rules()
+            .filter(|(other, candidate)| {
+                other.precedence() > kind.precedence()
+                    && !candidate.covers(requested)
+                    && candidate.overlaps(requested)
+            })
+            .max_by_key(|(other, _)| other.precedence());
+        if let Some((stricter, candidate)) = carved_out {
+            overridden.insert(0, format!("{}:{}", kind.label(), pattern.as_str()));
+            kind = stricter;
+            pattern = candidate;
+        }
+
+        let rule_id = format!("{}:{}", kind.label(), pattern.as_str());
+        let mut explanation = format!("{} matched rule {rule_id}", requested.as_str());
+        if !overridden.is_empty() {
+            explanation.push_str(&format!(", overriding {}", overridden.join(", ")));
+        }
+        PolicyRuleMatch {
+            capability: requested.clone(),
+            decision: kind.decision(),
+            rule_id: Some(rule_id),
+            explanation,
+        }
     }
 }
 
@@ -90,25 +209,23 @@ impl PolicyEngine for StaticPolicyEngine {
         let mut allowed = IndexSet::new();
         let mut requires_approval = IndexSet::new();
         let mut denied = IndexSet::new();
+        let mut matches = Vec::with_capacity(requested.len());
 
         for capability in requested {
-            if Self::set_contains(&self.gate, capability) {
-                requires_approval.insert(capability.clone());
-                continue;
-            }
-
-            if Self::set_contains(&self.allow, capability) {
-                allowed.insert(capability.clone());
-                continue;
-            }
-
-            denied.insert(capability.clone());
+            let matched = self.decide(capability);
+            match matched.decision {
+                PolicyDecisionKind::Allow => allowed.insert(capability.clone()),
+                PolicyDecisionKind::RequireApproval => requires_approval.insert(capability.clone()),
+                PolicyDecisionKind::Deny => denied.insert(capability.clone()),
+            };
+            matches.push(matched);
         }
 
         PolicyEvaluation {
             allowed: allowed.into_iter().collect(),
             requires_approval: requires_approval.into_iter().collect(),
             denied: denied.into_iter().collect(),
+            matches,
         }
     }
 }
@@ -133,6 +250,18 @@ impl SessionPolicyEngine {
             StaticPolicyEngine::from_policy_set(policy),
         );
     }
+
+    /// The policy currently enforced for `session_id`: its override, or
+    /// the default.
+    pub async fn policy_for(&self, session_id: &SessionId) -> PolicySet {
+        self.overrides
+            .read()
+            .await
+            .get(session_id.as_str())
+            .unwrap_or(&self.default)
+            .policy()
+            .clone()
+    }
 }
 
 #[async_trait]
@@ -244,11 +373,12 @@ impl PolicyGatePort for SessionPolicyEngine {
     ) -> std::result::Result<PolicyGateDecision, KernelError> {
         let evaluation =
             <Self as PolicyEngine>::evaluate_capabilities(self, session_id, &requested).await;
-        Ok(PolicyGateDecision {
-            allowed: evaluation.allowed,
-            requires_approval: evaluation.requires_approval,
-            denied: evaluation.denied,
-        })
+        Ok(PolicyGateDecision::new(
+            evaluation.allowed,
+            evaluation.requires_approval,
+            evaluation.denied,
+        )
+        .with_matches(evaluation.matches))
     }
 
     async fn set_policy(
@@ -259,6 +389,13 @@ impl PolicyGatePort for SessionPolicyEngine {
         SessionPolicyEngine::set_policy(self, &session_id, &policy).await;
         Ok(())
     }
+
+    async fn policy(
+        &self,
+        session_id: SessionId,
+    ) -> std::result::Result<Option<PolicySet>, KernelError> {
+        Ok(Some(self.policy_for(&session_id).await))
+    }
 }
 
 #[async_trait]
@@ -336,6 +473,7 @@ mod tests {
         let policy = PolicySet {
             allow_capabilities: vec![Capability::exec("*")],
             gate_capabilities: vec![Capability::new("payments:initiate")],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 10,
         };
@@ -370,6 +508,7 @@ mod tests {
         let default_policy = PolicySet {
             allow_capabilities: vec![Capability::fs_read("/session/**")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 10,
         };
@@ -381,6 +520,7 @@ mod tests {
         let override_policy = PolicySet {
             allow_capabilities: vec![Capability::exec("*")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 10,
             max_events_per_turn: 10,
         };
@@ -397,4 +537,178 @@ mod tests {
         assert_eq!(override_eval.allowed.len(), 1);
         assert!(override_eval.denied.is_empty());
     }
+
+    #[tokio::test]
+    async fn deny_rules_carve_exceptions_out_of_allows() {
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_write("/session/**"), Capability::exec("*")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![
+                Capability::fs_write("/session/state/**"),
+                Capability::exec("rm"),
+            ],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 10,
+        };
+        let engine = StaticPolicyEngine::from_policy_set(&policy);
+
+        let evaluation = engine
+            .evaluate_capabilities(
+                SessionId::default(),
+                &[
+                    Capability::fs_write("/session/notes.md"),
+                    Capability::fs_write("/session/state/plan.json"),
+                    Capability::exec("rm"),
+                    Capability::exec("ls"),
+                    Capability::net_egress("example.com"),
+                ],
+            )
+            .await;
+        assert_eq!(
+            evaluation.allowed,
+            vec![
+                Capability::fs_write("/session/notes.md"),
+                Capability::exec("ls")
+            ]
+        );
+        assert_eq!(evaluation.denied.len(), 3);
+
+        let rule_ids: Vec<Option<&str>> = evaluation
+            .matches
+            .iter()
+            .map(|matched| matched.rule_id.as_deref())
+            .collect();
+        assert_eq!(
+            rule_ids,
+            vec![
+                Some("allow:fs:write:/session/**"),
+                Some("deny:fs:write:/session/state/**"),
+                Some("deny:exec:cmd:rm"),
+                Some("allow:exec:cmd:*"),
+                None,
+            ]
+        );
+        assert!(
+            evaluation.matches[2]
+                .explanation
+                .contains("overriding allow:exec:cmd:*")
+        );
+        assert!(
+            evaluation.matches[4]
+                .explanation
+                .contains("denied by default")
+        );
+    }
+
+    #[tokio::test]
+    async fn more_specific_rule_wins_and_ties_prefer_deny() {
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::exec("git"), Capability::new("payments:*")],
+            gate_capabilities: vec![Capability::new("payments:*")],
+            deny_capabilities: vec![Capability::exec("*"), Capability::new("payments:*")],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 10,
+        };
+        let engine = StaticPolicyEngine::from_policy_set(&policy);
+
+        let evaluation = engine
+            .evaluate_capabilities(
+                SessionId::default(),
+                &[
+                    Capability::exec("git"),
+                    Capability::exec("curl"),
+                    Capability::new("payments:initiate"),
+                ],
+            )
+            .await;
+        let decisions: Vec<PolicyDecisionKind> = evaluation
+            .matches
+            .iter()
+            .map(|matched| matched.decision)
+            .collect();
+        assert_eq!(
+            decisions,
+            vec![
+                PolicyDecisionKind::Allow,
+                PolicyDecisionKind::Deny,
+                PolicyDecisionKind::Deny,
+            ]
+        );
+        assert_eq!(
+            evaluation.matches[2].rule_id.as_deref(),
+            Some("deny:payments:*")
+        );
+    }
+
+    #[tokio::test]
+    async fn a_narrow_allow_under_a_broad_gate_runs_without_approval() {
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_read("/session/docs/**")],
+            gate_capabilities: vec![Capability::fs_read("/session/**")],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 10,
+        };
+        let engine = StaticPolicyEngine::from_policy_set(&policy);
+
+        let evaluation = engine
+            .evaluate_capabilities(
+                SessionId::default(),
+                &[
+                    Capability::fs_read("/session/docs/guide.md"),
+                    Capability::fs_read("/session/state/plan.json"),
+                ],
+            )
+            .await;
+        assert_eq!(
+            evaluation.allowed,
+            vec![Capability::fs_read("/session/docs/guide.md")]
+        );
+        assert_eq!(
+            evaluation.requires_approval,
+            vec![Capability::fs_read("/session/state/plan.json")]
+        );
+        assert_eq!(
+            evaluation.matches[0].rule_id.as_deref(),
+            Some("allow:fs:read:/session/docs/**")
+        );
+    }
+
+    #[tokio::test]
+    async fn paths_are_normalized_and_partial_overlaps_apply_stricter_rules() {
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_write("/session/**")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![Capability::fs_write("/session/state/**")],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 10,
+        };
+        let engine = StaticPolicyEngine::from_policy_set(&policy);
+
+        let evaluation = engine
+            .evaluate_capabilities(
+                SessionId::default(),
+                &[
+                    Capability::fs_write("/session/../etc/passwd"),
+                    Capability::fs_write("/session/../../etc/passwd"),
+                    Capability::fs_write("/session/**"),
+                    Capability::fs_write("/session/./notes/../notes.md"),
+                ],
+            )
+            .await;
+        assert_eq!(
+            evaluation.allowed,
+            vec![Capability::fs_write("/session/./notes/../notes.md")]
+        );
+        assert_eq!(evaluation.matches[0].rule_id, None);
+        assert!(
+            evaluation.matches[1]
+                .explanation
+                .contains("escapes the root")
+        );
+        assert_eq!(
+            evaluation.matches[2].rule_id.as_deref(),
+            Some("deny:fs:write:/session/state/**")
+        );
+    }
 }
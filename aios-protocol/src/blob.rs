@@ -10,6 +10,10 @@ use serde::{Deserialize, Serialize};
 
 pub use crate::ids::BlobHash;
 
+/// Tool that pages through a tool output spilled to blob storage. The
+/// runtime points the model at it; `aios-tools` serves it.
+pub const BLOB_READ_TOOL: &str = "blob.read";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct BlobMetadata {
@@ -19,3 +23,19 @@ pub struct BlobMetadata {
     pub content_type: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
+
+impl BlobMetadata {
+    pub fn new(
+        hash: BlobHash,
+        size_bytes: u64,
+        content_type: Option<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            hash,
+            size_bytes,
+            content_type,
+            created_at,
+        }
+    }
+}
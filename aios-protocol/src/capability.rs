@@ -0,0 +1,813 @@
+//! Structured capabilities: parsing, matching, subsumption and intersection.
+//!
+//! A [`Capability`] string `domain:action:resource` parses into a
+//! [`CapabilitySpec`]. Every segment is a glob: `*` and `?` match any run of
+//! characters and any single character, `{a,b}` matches either alternative.
+//! A trailing `*` segment stands for everything after it, so `payments:*`
+//! reads as `payments:*:**` and `*` as `*:*:**`; `payments:initiate` has an
+//! empty resource.
+//!
+//! How the resource is matched depends on the domain:
+//!
+//! - `fs` resources are absolute paths, normalized lexically (`.` and `..`
+//!   resolved, a `..` above `/` rejected) before matching. `*` and `?` stop
+//!   at `/`, a `**` segment spans any number of segments (including none),
+//!   and a bare `*`/`**` resource means `/**`.
+//! - `net` resources are hosts, compared case-insensitively: `*` matches any
+//!   host and `*.example.com` every subdomain of `example.com` at any depth.
+//! - Anything else is an opaque glob.
+//!
+//! [`CapabilitySpec::covers`] is conservative: it only answers `true` when
+//! every capability the other pattern matches is provably matched as well,
+//! which is what narrowing a child's policy against its parent needs.
+//! [`CapabilitySpec::overlaps`] is exact for the supported syntax.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::policy::Capability;
+
+/// Alternatives a single brace expansion may produce.
+const MAX_ALTERNATIVES: usize = 64;
+
+/// Why a capability string is not a valid pattern.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CapabilityError {
+    #[error("empty capability")]
+    Empty,
+    #[error("invalid segment `{0}`")]
+    InvalidSegment(String),
+    #[error("unbalanced braces in `{0}`")]
+    UnbalancedBraces(String),
+    #[error("`{0}` expands to more than {MAX_ALTERNATIVES} alternatives")]
+    TooManyAlternatives(String),
+    #[error("path `{0}` is not absolute")]
+    RelativePath(String),
+    #[error("path `{0}` escapes the root")]
+    PathEscapesRoot(String),
+    #[error("invalid host `{0}`")]
+    InvalidHost(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResourceKind {
+    Path,
+    Host,
+    Opaque,
+}
+
+impl ResourceKind {
+    fn of(domain: &str) -> Self {
+        match domain {
+            "fs" => Self::Path,
+            "net" => Self::Host,
+            _ => Self::Opaque,
+        }
+    }
+}
+
+/// A parsed, validated capability. See the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilitySpec {
+    domain: String,
+    action: String,
+    resource: String,
+}
+
+impl CapabilitySpec {
+    pub fn parse(value: &str) -> Result<Self, CapabilityError> {
+        if value.is_empty() {
+            return Err(CapabilityError::Empty);
+        }
+        let mut parts = value.splitn(3, ':');
+        let domain = parts.next().unwrap_or_default();
+        let action = parts.next();
+        let resource = parts.next();
+        let (action, resource) = match (action, resource) {
+            (None, _) if is_wildcard(domain) => ("*", "**"),
+            (None, _) => return Err(CapabilityError::InvalidSegment(value.to_owned())),
+            (Some(action), None) if is_wildcard(action) => (action, "**"),
+            (Some(action), None) => (action, ""),
+            (Some(action), Some(resource)) => (action, resource),
+        };
+        validate_segment(domain)?;
+        validate_segment(action)?;
+
+        let resource = match ResourceKind::of(domain) {
+            ResourceKind::Path => normalize_path_pattern(resource)?,
+            ResourceKind::Host => {
+                let host = resource.to_ascii_lowercase();
+                host_patterns(&host)?;
+                host
+            }
+            ResourceKind::Opaque => {
+                if resource.chars().any(char::is_control) {
+                    return Err(CapabilityError::InvalidSegment(resource.to_owned()));
+                }
+                expand_braces(resource)?;
+                resource.to_owned()
+            }
+        };
+
+        Ok(Self {
+            domain: domain.to_owned(),
+            action: action.to_owned(),
+            resource,
+        })
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// The resource, normalized for its domain.
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    /// Whether the spec names a single capability rather than a pattern.
+    pub fn is_exact(&self) -> bool {
+        !self.to_string().contains(['*', '?', '{'])
+    }
+
+    /// Ranks patterns for most-specific-match-wins: exact specs above any
+    /// pattern, patterns by the length of their literal prefix. A brace
+    /// pattern ranks as its least specific normalized alternative, so text
+    /// an alternative resolves away (`{a,/../b}`) cannot raise its rank.
+    pub fn specificity(&self) -> (bool, usize) {
+        let rendered = self.to_string();
+        if rendered.contains('{') {
+            return self
+                .alternatives()
+                .iter()
+                .map(Self::specificity)
+                .min()
+                .unwrap_or((false, 0));
+        }
+        match rendered.find(['*', '?']) {
+            Some(literal) => (false, literal),
+            None => (true, rendered.len()),
+        }
+    }
+
+    /// The spec with its braces expanded: one brace-free spec per
+    /// alternative, each normalized for its domain.
+    pub fn alternatives(&self) -> Vec<CapabilitySpec> {
+        let rendered = self.to_string();
+        if !rendered.contains('{') {
+            return vec![self.clone()];
+        }
+        expand_braces(&rendered)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|alternative| Self::parse(alternative).ok())
+            .collect()
+    }
+
+    /// Whether every capability `other` matches is also matched by `self`.
+    pub fn covers(&self, other: &CapabilitySpec) -> bool {
+        if !segment_covers(&self.domain, &other.domain)
+            || !segment_covers(&self.action, &other.action)
+        {
+            return false;
+        }
+        // A literal domain only covers the same literal domain, so both
+        // resources share its semantics; a glob domain matches opaquely.
+        let kind = if is_literal(&self.domain) {
+            ResourceKind::of(&self.domain)
+        } else {
+            ResourceKind::Opaque
+        };
+        resource_covers(kind, &self.resource, &other.resource)
+    }
+
+    /// Whether some capability is matched by both `self` and `other`.
+    pub fn overlaps(&self, other: &CapabilitySpec) -> bool {
+        if !segment_overlaps(&self.domain, &other.domain)
+            || !segment_overlaps(&self.action, &other.action)
+        {
+            return false;
+        }
+        let kind = if is_literal(&self.domain) && self.domain == other.domain {
+            ResourceKind::of(&self.domain)
+        } else {
+            ResourceKind::Opaque
+        };
+        resource_overlaps(kind, &self.resource, &other.resource)
+    }
+
+    /// The widest spec both `self` and `other` cover, when it can be
+    /// expressed by picking the narrower of each segment; `None` when the
+    /// two are disjoint or their common part needs more than one pattern.
+    pub fn intersection(&self, other: &CapabilitySpec) -> Option<CapabilitySpec> {
+        if self.covers(other) {
+            return Some(other.clone());
+        }
+        if other.covers(self) {
+            return Some(self.clone());
+        }
+        if !self.overlaps(other) {
+            return None;
+        }
+        let narrower = |ours: &str, theirs: &str| {
+            if segment_covers(ours, theirs) {
+                Some(theirs.to_owned())
+            } else if segment_covers(theirs, ours) {
+                Some(ours.to_owned())
+            } else {
+                None
+            }
+        };
+        let domain = narrower(&self.domain, &other.domain)?;
+        let action = narrower(&self.action, &other.action)?;
+        let candidates = [&self.resource, &other.resource];
+        candidates.into_iter().find_map(|resource| {
+            let candidate = CapabilitySpec::parse(&format!("{domain}:{action}:{resource}")).ok()?;
+            (self.covers(&candidate) && other.covers(&candidate)).then_some(candidate)
+        })
+    }
+
+    pub fn to_capability(&self) -> Capability {
+        Capability::new(self.to_string())
+    }
+}
+
+impl fmt::Display for CapabilitySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.resource.is_empty() {
+            write!(f, "{}:{}", self.domain, self.action)
+        } else {
+            write!(f, "{}:{}:{}", self.domain, self.action, self.resource)
+        }
+    }
+}
+
+fn is_wildcard(segment: &str) -> bool {
+    segment == "*" || segment == "**"
+}
+
+fn is_literal(segment: &str) -> bool {
+    !segment.contains(['*', '?', '{'])
+}
+
+fn validate_segment(segment: &str) -> Result<(), CapabilityError> {
+    let valid = !segment.is_empty()
+        && segment
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "_-.*?{},".contains(character));
+    if !valid {
+        return Err(CapabilityError::InvalidSegment(segment.to_owned()));
+    }
+    expand_braces(segment).map(|_| ())
+}
+
+// ── Brace expansion ───────────────────────────────────────────────────
+
+fn expand_braces(pattern: &str) -> Result<Vec<String>, CapabilityError> {
+    let Some(open) = pattern.find('{') else {
+        if pattern.contains('}') {
+            return Err(CapabilityError::UnbalancedBraces(pattern.to_owned()));
+        }
+        return Ok(vec![pattern.to_owned()]);
+    };
+    let mut depth = 0usize;
+    let mut close = None;
+    let mut splits = Vec::new();
+    for (index, character) in pattern[open..].char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + index);
+                    break;
+                }
+            }
+            ',' if depth == 1 => splits.push(open + index),
+            _ => {}
+        }
+    }
+    let close = close.ok_or_else(|| CapabilityError::UnbalancedBraces(pattern.to_owned()))?;
+
+    let mut bounds = vec![open];
+    bounds.extend(splits);
+    bounds.push(close);
+    let prefix = &pattern[..open];
+    let suffix = expand_braces(&pattern[close + 1..])?;
+    let mut expanded = Vec::new();
+    for window in bounds.windows(2) {
+        for alternative in expand_braces(&pattern[window[0] + 1..window[1]])? {
+            for rest in &suffix {
+                expanded.push(format!("{prefix}{alternative}{rest}"));
+                if expanded.len() > MAX_ALTERNATIVES {
+                    return Err(CapabilityError::TooManyAlternatives(pattern.to_owned()));
+                }
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+// ── Globs ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    /// `?`: one character other than the separator.
+    Any,
+    /// `*` in a path: any run of characters without the separator.
+    Star,
+    /// `**`, or `*` where there is no separator: any run of characters.
+    GlobStar,
+}
+
+impl Token {
+    fn is_star(self) -> bool {
+        matches!(self, Self::Star | Self::GlobStar)
+    }
+
+    fn accepts(self, character: char, separator: Option<char>) -> bool {
+        match self {
+            Self::Literal(literal) => literal == character,
+            Self::Any | Self::Star => Some(character) != separator,
+            Self::GlobStar => true,
+        }
+    }
+
+    /// Whether everything this token matches is a run of characters
+    /// without the separator.
+    fn within_segment(self, separator: Option<char>) -> bool {
+        match self {
+            Self::Literal(literal) => Some(literal) != separator,
+            Self::Any | Self::Star => true,
+            Self::GlobStar => separator.is_none(),
+        }
+    }
+}
+
+fn tokenize(pattern: &str, separator: Option<char>) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut characters = pattern.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '*' => {
+                let double = characters.peek() == Some(&'*');
+                if double {
+                    while characters.peek() == Some(&'*') {
+                        characters.next();
+                    }
+                }
+                let token = if double || separator.is_none() {
+                    Token::GlobStar
+                } else {
+                    Token::Star
+                };
+                // Adjacent stars match the same as the wider of the two.
+                match tokens.last_mut() {
+                    Some(last) if last.is_star() => {
+                        if token == Token::GlobStar {
+                            *last = Token::GlobStar;
+                        }
+                    }
+                    _ => tokens.push(token),
+                }
+            }
+            '?' => tokens.push(Token::Any),
+            other => tokens.push(Token::Literal(other)),
+        }
+    }
+    tokens
+}
+
+/// `L(narrow) ⊆ L(wide)`, decided conservatively.
+fn tokens_cover(wide: &[Token], narrow: &[Token], separator: Option<char>) -> bool {
+    fn go(
+        wide: &[Token],
+        narrow: &[Token],
+        separator: Option<char>,
+        memo: &mut HashMap<(usize, usize), bool>,
+        (w, n): (usize, usize),
+    ) -> bool {
+        if let Some(&known) = memo.get(&(w, n)) {
+            return known;
+        }
+        let result = if n == narrow.len() {
+            wide[w..].iter().all(|token| token.is_star())
+        } else if w == wide.len() {
+            false
+        } else {
+            let next = narrow[n];
+            match wide[w] {
+                Token::GlobStar => {
+                    go(wide, narrow, separator, memo, (w + 1, n))
+                        || go(wide, narrow, separator, memo, (w, n + 1))
+                }
+                Token::Star => {
+                    go(wide, narrow, separator, memo, (w + 1, n))
+                        || (next.within_segment(separator)
+                            && go(wide, narrow, separator, memo, (w, n + 1)))
+                }
+                Token::Any => {
+                    matches!(next, Token::Any | Token::Literal(_))
+                        && next.within_segment(separator)
+                        && go(wide, narrow, separator, memo, (w + 1, n + 1))
+                }
+                Token::Literal(literal) => {
+                    next == Token::Literal(literal)
+                        && go(wide, narrow, separator, memo, (w + 1, n + 1))
+                }
+            }
+        };
+        memo.insert((w, n), result);
+        result
+    }
+    go(wide, narrow, separator, &mut HashMap::new(), (0, 0))
+}
+
+/// `L(left) ∩ L(right) ≠ ∅`.
+fn tokens_overlap(left: &[Token], right: &[Token], separator: Option<char>) -> bool {
+    fn shared_character(left: Token, right: Token, separator: Option<char>) -> bool {
+        match (left, right) {
+            (Token::Literal(character), other) | (other, Token::Literal(character)) => {
+                other.accepts(character, separator)
+            }
+            _ => true,
+        }
+    }
+
+    fn go(
+        left: &[Token],
+        right: &[Token],
+        separator: Option<char>,
+        memo: &mut HashMap<(usize, usize), bool>,
+        (l, r): (usize, usize),
+    ) -> bool {
+        if l == left.len() && r == right.len() {
+            return true;
+        }
+        if let Some(&known) = memo.get(&(l, r)) {
+            return known;
+        }
+        let left_token = left.get(l).copied();
+        let right_token = right.get(r).copied();
+        let mut result = false;
+        if let Some(token) = left_token
+            && token.is_star()
+        {
+            result = go(left, right, separator, memo, (l + 1, r));
+        }
+        if !result
+            && let Some(token) = right_token
+            && token.is_star()
+        {
+            result = go(left, right, separator, memo, (l, r + 1));
+        }
+        if !result
+            && let (Some(ours), Some(theirs)) = (left_token, right_token)
+            && !(ours.is_star() && theirs.is_star())
+            && shared_character(ours, theirs, separator)
+        {
+            let next_l = if ours.is_star() { l } else { l + 1 };
+            let next_r = if theirs.is_star() { r } else { r + 1 };
+            result = go(left, right, separator, memo, (next_l, next_r));
+        }
+        memo.insert((l, r), result);
+        result
+    }
+    go(left, right, separator, &mut HashMap::new(), (0, 0))
+}
+
+fn glob_alternatives(pattern: &str, kind: ResourceKind) -> Vec<Vec<Token>> {
+    let separator = (kind == ResourceKind::Path).then_some('/');
+    let mut alternatives = Vec::new();
+    for expanded in expand_braces(pattern).unwrap_or_default() {
+        let variants = if kind == ResourceKind::Path {
+            without_globstar_segments(&normalize_path(&expanded).unwrap_or(expanded))
+        } else {
+            vec![expanded]
+        };
+        alternatives.extend(variants.iter().map(|variant| tokenize(variant, separator)));
+    }
+    alternatives
+}
+
+fn segment_covers(wide: &str, narrow: &str) -> bool {
+    glob_covers(wide, narrow, ResourceKind::Opaque)
+}
+
+fn segment_overlaps(left: &str, right: &str) -> bool {
+    glob_overlaps(left, right, ResourceKind::Opaque)
+}
+
+fn glob_covers(wide: &str, narrow: &str, kind: ResourceKind) -> bool {
+    let separator = (kind == ResourceKind::Path).then_some('/');
+    let wide = glob_alternatives(wide, kind);
+    let narrow = glob_alternatives(narrow, kind);
+    !narrow.is_empty()
+        && narrow.iter().all(|narrow| {
+            wide.iter()
+                .any(|wide| tokens_cover(wide, narrow, separator))
+        })
+}
+
+fn glob_overlaps(left: &str, right: &str, kind: ResourceKind) -> bool {
+    let separator = (kind == ResourceKind::Path).then_some('/');
+    let left = glob_alternatives(left, kind);
+    let right = glob_alternatives(right, kind);
+    left.iter().any(|left| {
+        right
+            .iter()
+            .any(|right| tokens_overlap(left, right, separator))
+    })
+}
+
+fn resource_covers(kind: ResourceKind, wide: &str, narrow: &str) -> bool {
+    match kind {
+        ResourceKind::Host => match (host_patterns(wide), host_patterns(narrow)) {
+            (Ok(wide), Ok(narrow)) => narrow
+                .iter()
+                .all(|narrow| wide.iter().any(|wide| wide.covers(narrow))),
+            _ => false,
+        },
+        ResourceKind::Path | ResourceKind::Opaque => glob_covers(wide, narrow, kind),
+    }
+}
+
+fn resource_overlaps(kind: ResourceKind, left: &str, right: &str) -> bool {
+    match kind {
+        ResourceKind::Host => match (host_patterns(left), host_patterns(right)) {
+            (Ok(left), Ok(right)) => left.iter().any(|left| {
+                right
+                    .iter()
+                    .any(|right| left.covers(right) || right.covers(left))
+            }),
+            _ => true,
+        },
+        ResourceKind::Path | ResourceKind::Opaque => glob_overlaps(left, right, kind),
+    }
+}
+
+// ── Paths ─────────────────────────────────────────────────────────────
+
+/// Resolve `.` and `..` in every alternative of a path pattern; patterns
+/// without braces are returned in normalized form.
+fn normalize_path_pattern(pattern: &str) -> Result<String, CapabilityError> {
+    if is_wildcard(pattern) {
+        return Ok("/**".to_owned());
+    }
+    if pattern.chars().any(char::is_control) {
+        return Err(CapabilityError::InvalidSegment(pattern.to_owned()));
+    }
+    let alternatives = expand_braces(pattern)?;
+    let mut normalized = Vec::with_capacity(alternatives.len());
+    for alternative in &alternatives {
+        normalized.push(normalize_path(alternative)?);
+    }
+    if alternatives.len() == 1 && !pattern.contains('{') {
+        Ok(normalized.remove(0))
+    } else {
+        Ok(pattern.to_owned())
+    }
+}
+
+fn normalize_path(path: &str) -> Result<String, CapabilityError> {
+    if !path.starts_with('/') {
+        return Err(CapabilityError::RelativePath(path.to_owned()));
+    }
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                let popped = segments
+                    .pop()
+                    .ok_or_else(|| CapabilityError::PathEscapesRoot(path.to_owned()))?;
+                if !is_literal(popped) {
+                    return Err(CapabilityError::InvalidSegment(path.to_owned()));
+                }
+            }
+            other => segments.push(other),
+        }
+    }
+    Ok(format!("/{}", segments.join("/")))
+}
+
+/// A `**` segment may also match no segment at all, so `/a/**` includes
+/// `/a` itself: expand each such segment into "present" and "absent".
+fn without_globstar_segments(path: &str) -> Vec<String> {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut variants = vec![Vec::new()];
+    for (index, segment) in segments.iter().enumerate() {
+        let optional = index > 0 && *segment == "**";
+        let mut next = Vec::with_capacity(variants.len() * 2);
+        for variant in variants {
+            if optional {
+                next.push(variant.clone());
+            }
+            let mut with = variant;
+            with.push(*segment);
+            next.push(with);
+        }
+        variants = next;
+        if variants.len() > MAX_ALTERNATIVES {
+            break;
+        }
+    }
+    variants
+        .into_iter()
+        .map(|segments| match segments.join("/") {
+            joined if joined.is_empty() => "/".to_owned(),
+            joined => joined,
+        })
+        .collect()
+}
+
+// ── Hosts ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    Any,
+    /// Strict subdomains of the stored host.
+    Subdomains(String),
+    Exact(String),
+}
+
+impl HostPattern {
+    fn covers(&self, other: &HostPattern) -> bool {
+        let under = |host: &str, parent: &str| {
+            host.strip_suffix(parent)
+                .is_some_and(|label| label.ends_with('.'))
+        };
+        match (self, other) {
+            (Self::Any, _) => true,
+            (Self::Subdomains(parent), Self::Subdomains(host)) => {
+                host == parent || under(host, parent)
+            }
+            (Self::Subdomains(parent), Self::Exact(host)) => under(host, parent),
+            (Self::Exact(host), Self::Exact(other)) => host == other,
+            _ => false,
+        }
+    }
+}
+
+fn host_patterns(pattern: &str) -> Result<Vec<HostPattern>, CapabilityError> {
+    expand_braces(pattern)?
+        .into_iter()
+        .map(|alternative| {
+            let host = alternative.trim_end_matches('.');
+            if is_wildcard(host) {
+                return Ok(HostPattern::Any);
+            }
+            let (subdomains, name) = match host.strip_prefix("*.") {
+                Some(name) => (true, name),
+                None => (false, host),
+            };
+            let valid = !name.is_empty()
+                && name.split('.').all(|label| {
+                    !label.is_empty()
+                        && label.chars().all(|character| {
+                            character.is_ascii_alphanumeric()
+                                || character == '-'
+                                || character == '_'
+                        })
+                });
+            if !valid {
+                return Err(CapabilityError::InvalidHost(alternative.clone()));
+            }
+            Ok(if subdomains {
+                HostPattern::Subdomains(name.to_owned())
+            } else {
+                HostPattern::Exact(name.to_owned())
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(value: &str) -> CapabilitySpec {
+        CapabilitySpec::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parses_and_normalizes_segments() {
+        let read = spec("fs:read:/session/./notes/../notes.md");
+        assert_eq!(read.domain(), "fs");
+        assert_eq!(read.action(), "read");
+        assert_eq!(read.resource(), "/session/notes.md");
+        assert_eq!(spec("fs:write:**").resource(), "/**");
+        assert_eq!(spec("payments:*").to_string(), "payments:*:**");
+        assert_eq!(spec("payments:initiate").to_string(), "payments:initiate");
+        assert_eq!(
+            spec("net:egress:API.Example.com").resource(),
+            "api.example.com"
+        );
+
+        assert_eq!(
+            CapabilitySpec::parse("fs:read:/session/../../etc/passwd"),
+            Err(CapabilityError::PathEscapesRoot(
+                "/session/../../etc/passwd".to_owned()
+            ))
+        );
+        assert!(matches!(
+            CapabilitySpec::parse("fs:read:notes.md"),
+            Err(CapabilityError::RelativePath(_))
+        ));
+        assert!(matches!(
+            CapabilitySpec::parse("net:egress:exa*mple.com"),
+            Err(CapabilityError::InvalidHost(_))
+        ));
+        assert!(matches!(
+            CapabilitySpec::parse("exec:cmd:{git"),
+            Err(CapabilityError::UnbalancedBraces(_))
+        ));
+        assert!(CapabilitySpec::parse("fs").is_err());
+        assert!(CapabilitySpec::parse("").is_err());
+    }
+
+    #[test]
+    fn paths_match_with_segment_aware_globs() {
+        let session = spec("fs:read:/session/**");
+        assert!(session.covers(&spec("fs:read:/session")));
+        assert!(session.covers(&spec("fs:read:/session/a/b.md")));
+        assert!(session.covers(&spec("fs:read:/session/artifacts/**")));
+        assert!(!session.covers(&spec("fs:read:/session/../etc/passwd")));
+        assert!(!session.covers(&spec("fs:read:/sessions/a")));
+
+        let reports = spec("fs:write:/session/*/report-?.{md,txt}");
+        assert!(reports.covers(&spec("fs:write:/session/run/report-1.md")));
+        assert!(reports.covers(&spec("fs:write:/session/run/report-2.txt")));
+        assert!(!reports.covers(&spec("fs:write:/session/a/b/report-1.md")));
+        assert!(!reports.covers(&spec("fs:write:/session/run/report-10.md")));
+        assert!(spec("fs:write:/session/*/report-*").covers(&reports));
+        assert!(!reports.covers(&spec("fs:write:/session/*/report-*")));
+    }
+
+    #[test]
+    fn hosts_match_by_domain_suffix() {
+        let subdomains = spec("net:egress:*.example.com");
+        assert!(subdomains.covers(&spec("net:egress:api.example.com")));
+        assert!(subdomains.covers(&spec("net:egress:a.b.example.com")));
+        assert!(subdomains.covers(&spec("net:egress:*.eu.example.com")));
+        assert!(!subdomains.covers(&spec("net:egress:example.com")));
+        assert!(!subdomains.covers(&spec("net:egress:badexample.com")));
+        assert!(spec("net:egress:*").covers(&subdomains));
+        assert!(!subdomains.covers(&spec("net:egress:*")));
+    }
+
+    #[test]
+    fn overlaps_and_intersections() {
+        let session_write = spec("fs:write:/session/**");
+        let state = spec("fs:write:/session/state/**");
+        let any_action = spec("fs:*:/session/state/**");
+        assert!(session_write.overlaps(&state));
+        assert!(!state.overlaps(&spec("fs:write:/session/artifacts/**")));
+        assert!(spec("exec:cmd:g*").overlaps(&spec("exec:cmd:*t")));
+        assert!(!spec("exec:cmd:git").overlaps(&spec("exec:cmd:rm")));
+
+        assert_eq!(session_write.intersection(&state), Some(state.clone()));
+        assert_eq!(
+            spec("fs:*:/session/**").intersection(&spec("fs:read:/**")),
+            Some(spec("fs:read:/session/**"))
+        );
+        assert_eq!(any_action.intersection(&spec("exec:cmd:*")), None);
+        assert_eq!(spec("exec:cmd:g*").intersection(&spec("exec:cmd:*t")), None);
+    }
+
+    #[test]
+    fn brace_patterns_rank_by_their_normalized_alternatives() {
+        let escaping = spec("fs:write:/session/state/plan{.json,/../../../etc}");
+        assert_eq!(
+            escaping.alternatives(),
+            vec![
+                spec("fs:write:/session/state/plan.json"),
+                spec("fs:write:/etc")
+            ]
+        );
+        assert_eq!(escaping.specificity(), spec("fs:write:/etc").specificity());
+
+        let reports = spec("fs:write:/session/{a,bb}/*.md");
+        assert_eq!(reports.specificity(), (false, "fs:write:/session/a/".len()));
+        assert_eq!(
+            spec("exec:cmd:git").alternatives(),
+            vec![spec("exec:cmd:git")]
+        );
+    }
+
+    #[test]
+    fn wildcards_cover_every_domain() {
+        let everything = spec("*");
+        assert!(everything.covers(&spec("exec:cmd:git")));
+        assert!(everything.covers(&spec("fs:write:/session/**")));
+        assert!(everything.covers(&spec("payments:initiate")));
+        assert!(spec("payments:*").covers(&spec("payments:initiate")));
+        assert!(!spec("payments:initiate").covers(&spec("payments:*")));
+        assert!(!spec("exec:cmd:git").covers(&everything));
+    }
+}
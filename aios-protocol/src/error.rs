@@ -19,11 +19,39 @@ pub enum KernelError {
     InvalidState(String),
     #[error("runtime error: {0}")]
     Runtime(String),
+    /// A failure the caller may retry as is: a rate limit, an overloaded
+    /// upstream, a dropped connection.
+    #[error("transient error: {0}")]
+    Transient(String),
     #[error("budget exceeded: {0}")]
     BudgetExceeded(String),
     #[error("sequence conflict: expected {expected}, got {actual}")]
     SequenceConflict { expected: u64, actual: u64 },
 }
 
+impl KernelError {
+    /// Whether retrying the same operation may succeed.
+    ///
+    /// I/O failures and errors a provider reports as [`Self::Transient`]
+    /// are; policy, validation, bookkeeping and other runtime errors are
+    /// not.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::Transient(_))
+    }
+}
+
 /// Convenience result type for kernel operations.
 pub type KernelResult<T> = Result<T, KernelError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_errors_are_permanent_unless_marked_transient() {
+        assert!(KernelError::Transient("429 rate limited".to_owned()).is_transient());
+        assert!(KernelError::Io("connection reset".to_owned()).is_transient());
+        assert!(!KernelError::Runtime("malformed tool schema".to_owned()).is_transient());
+        assert!(!KernelError::InvalidState("unknown model".to_owned()).is_transient());
+    }
+}
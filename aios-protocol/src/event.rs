@@ -58,6 +58,18 @@ impl Default for EventSchema {
     }
 }
 
+/// Signature over a record's [`EventRecord::signing_bytes`], attributing
+/// it to the holder of the key named by `signer`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventSignature {
+    /// Signature scheme; currently always `"ed25519"`.
+    pub alg: String,
+    /// `did:key` of the signing key.
+    pub signer: String,
+    /// Base64url (unpadded) signature bytes.
+    pub value: String,
+}
+
 fn default_agent_id() -> AgentId {
     AgentId::default()
 }
@@ -92,6 +104,8 @@ pub struct EventEnvelope {
     pub span_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub digest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<EventSignature>,
     pub kind: EventKind,
     #[serde(default)]
     pub metadata: HashMap<String, String>,
@@ -140,6 +154,8 @@ pub struct EventRecord {
     pub span_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub digest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<EventSignature>,
     pub kind: EventKind,
 }
 
@@ -165,10 +181,41 @@ impl EventRecord {
             trace_id: None,
             span_id: None,
             digest: None,
+            signature: None,
             kind,
         }
     }
 
+    /// Create an ephemeral record for live subscribers only.
+    ///
+    /// Ephemeral records (e.g. streamed text deltas) are broadcast but never
+    /// appended to the journal, so they carry the reserved sequence `0`
+    /// instead of claiming a slot in the branch's sequence space.
+    pub fn ephemeral(session_id: SessionId, branch_id: BranchId, kind: EventKind) -> Self {
+        Self::new(session_id, branch_id, 0, kind)
+    }
+
+    /// Whether this record was published with [`Self::ephemeral`] and has
+    /// no journal counterpart.
+    pub fn is_ephemeral(&self) -> bool {
+        self.sequence == 0
+    }
+
+    /// Canonical bytes covered by `digest` and `signature`: the record's
+    /// JSON without those two fields, with object keys sorted and no
+    /// insignificant whitespace, so any two serializations of the same
+    /// record hash and verify alike.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("digest");
+            object.remove("signature");
+        }
+        let mut out = String::new();
+        write_canonical_json(&value, &mut out);
+        out.into_bytes()
+    }
+
     /// Convert to the canonical `EventEnvelope` for storage/streaming.
     pub fn to_envelope(&self) -> EventEnvelope {
         EventEnvelope {
@@ -185,6 +232,7 @@ impl EventRecord {
             trace_id: self.trace_id.clone(),
             span_id: self.span_id.clone(),
             digest: self.digest.clone(),
+            signature: self.signature.clone(),
             kind: self.kind.clone(),
             metadata: HashMap::new(),
             schema_version: 1,
@@ -192,6 +240,36 @@ impl EventRecord {
     }
 }
 
+fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
 // ─── Canonical EventKind ───────────────────────────────────────────
 
 /// Discriminated union of ALL Agent OS event types.
@@ -572,6 +650,12 @@ pub enum EventKind {
         kind: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         risk: Option<RiskLevel>,
+        /// What the agent means to achieve.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        summary: String,
+        /// Tool calls the intent covers, in the order they would run.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        planned_calls: Vec<crate::tool::ToolCall>,
     },
     IntentEvaluated {
         intent_id: String,
@@ -1190,6 +1274,10 @@ enum EventKindKnown {
         kind: String,
         #[serde(default)]
         risk: Option<RiskLevel>,
+        #[serde(default)]
+        summary: String,
+        #[serde(default)]
+        planned_calls: Vec<crate::tool::ToolCall>,
     },
     IntentEvaluated {
         intent_id: String,
@@ -1736,10 +1824,14 @@ impl From<EventKindKnown> for EventKind {
                 intent_id,
                 kind,
                 risk,
+                summary,
+                planned_calls,
             } => Self::IntentProposed {
                 intent_id,
                 kind,
                 risk,
+                summary,
+                planned_calls,
             },
             EventKindKnown::IntentEvaluated {
                 intent_id,
@@ -2451,12 +2543,43 @@ mod tests {
             trace_id: None,
             span_id: None,
             digest: None,
+            signature: None,
             kind,
             metadata: HashMap::new(),
             schema_version: 1,
         }
     }
 
+    #[test]
+    fn signing_bytes_are_canonical_and_exclude_digest_and_signature() {
+        let mut record = EventRecord::new(
+            SessionId::from_string("SESS001"),
+            BranchId::main(),
+            3,
+            EventKind::Custom {
+                event_type: "note".into(),
+                data: serde_json::json!({ "b": 1, "a": [true, null] }),
+            },
+        );
+        let bytes = record.signing_bytes();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains(r#""data":{"a":[true,null],"b":1}"#), "{text}");
+        assert!(!text.contains(' '));
+
+        record.digest = Some("abc".into());
+        record.signature = Some(EventSignature {
+            alg: "ed25519".into(),
+            signer: "did:key:z6Mk".into(),
+            value: "sig".into(),
+        });
+        assert_eq!(record.signing_bytes(), bytes);
+
+        let reparsed: EventRecord =
+            serde_json::from_str(&serde_json::to_string_pretty(&record).unwrap()).unwrap();
+        assert_eq!(reparsed.signing_bytes(), bytes);
+        assert_eq!(reparsed.signature, record.signature);
+    }
+
     #[test]
     fn error_raised_roundtrip() {
         let kind = EventKind::ErrorRaised {
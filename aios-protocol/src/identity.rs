@@ -47,6 +47,9 @@ pub trait AgentIdentityProvider: Send + Sync + std::fmt::Debug {
         if let Some(did) = self.did() {
             block.push_str(&format!("\nIdentity: {did}"));
         }
+        if !soul.traits.is_empty() {
+            block.push_str(&format!("\nTraits: {}", soul.traits.join(", ")));
+        }
         let caps = self.capabilities();
         if !caps.is_empty() {
             block.push_str(&format!("\nCapabilities: {}", caps.join(", ")));
@@ -117,6 +120,41 @@ pub struct Belief {
     pub contradicts: Vec<String>,
 }
 
+impl Belief {
+    /// A belief observed now, with a fresh id and `confidence` clamped to
+    /// `[0.0, 1.0]`.
+    pub fn new(
+        subject: impl Into<String>,
+        proposition: impl Into<String>,
+        confidence: f32,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject: subject.into(),
+            proposition: proposition.into(),
+            confidence: confidence.clamp(0.0, 1.0),
+            observed_at: chrono::Utc::now(),
+            supports: Vec::new(),
+            contradicts: Vec::new(),
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub fn with_supports(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.supports.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_contradicts(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.contradicts.extend(ids.into_iter().map(Into::into));
+        self
+    }
+}
+
 /// Filter parameters for querying an agent's belief store.
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 #[non_exhaustive]
@@ -135,6 +173,43 @@ pub struct BeliefFilter {
     pub limit: Option<u32>,
 }
 
+impl BeliefFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    pub fn since(mut self, since: chrono::DateTime<chrono::Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `belief` passes every criterion except `limit`.
+    pub fn matches(&self, belief: &Belief) -> bool {
+        self.subject
+            .as_ref()
+            .is_none_or(|subject| &belief.subject == subject)
+            && self
+                .min_confidence
+                .is_none_or(|min_confidence| belief.confidence >= min_confidence)
+            && self.since.is_none_or(|since| belief.observed_at >= since)
+    }
+}
+
 /// A partial update to an agent's [`SoulProfile`].
 ///
 /// Only non-`None` / non-empty fields are applied; absent fields are left
@@ -154,6 +229,53 @@ pub struct SoulUpdate {
     pub remove_traits: Vec<String>,
 }
 
+impl SoulUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn add_trait(mut self, tag: impl Into<String>) -> Self {
+        self.add_traits.push(tag.into());
+        self
+    }
+
+    pub fn remove_trait(mut self, tag: impl Into<String>) -> Self {
+        self.remove_traits.push(tag.into());
+        self
+    }
+
+    /// Apply to `soul`: rename, drop `remove_traits`, then append the
+    /// `add_traits` it does not have yet. Returns whether anything changed;
+    /// `updated_at` is bumped only then.
+    pub fn apply_to(&self, soul: &mut SoulProfile) -> bool {
+        let mut changed = false;
+        if let Some(name) = &self.display_name
+            && &soul.name != name
+        {
+            soul.name = name.clone();
+            changed = true;
+        }
+        let before = soul.traits.len();
+        soul.traits.retain(|tag| !self.remove_traits.contains(tag));
+        changed |= soul.traits.len() != before;
+        for tag in &self.add_traits {
+            if !soul.traits.contains(tag) {
+                soul.traits.push(tag.clone());
+                changed = true;
+            }
+        }
+        if changed {
+            soul.updated_at = chrono::Utc::now();
+        }
+        changed
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,4 +396,45 @@ mod tests {
         assert!(block.contains("Capabilities: chat:send, fs:read"));
         assert!(block.contains("Economic mode: hustle"));
     }
+
+    #[test]
+    fn soul_update_renames_and_edits_traits_and_feeds_persona() {
+        let mut id = BasicIdentity::new("Arcan", "Agent runtime");
+        let update = SoulUpdate::new()
+            .with_display_name("Arcan Prime")
+            .add_trait("curious")
+            .add_trait("terse");
+        assert!(update.apply_to(&mut id.soul));
+        assert!(!update.apply_to(&mut id.soul), "reapplying changes nothing");
+        assert!(
+            SoulUpdate::new()
+                .remove_trait("terse")
+                .add_trait("patient")
+                .apply_to(&mut id.soul)
+        );
+        assert_eq!(id.soul.traits, vec!["curious", "patient"]);
+
+        let block = id.persona_block();
+        assert!(block.contains("You are Arcan Prime"));
+        assert!(block.contains("Traits: curious, patient"));
+    }
+
+    #[test]
+    fn belief_filter_matches_subject_confidence_and_time() {
+        let belief = Belief::new("user", "prefers Rust", 1.4);
+        assert_eq!(belief.confidence, 1.0);
+        assert!(BeliefFilter::new().matches(&belief));
+        assert!(
+            BeliefFilter::new()
+                .with_subject("user")
+                .with_min_confidence(0.9)
+                .matches(&belief)
+        );
+        assert!(!BeliefFilter::new().with_subject("market").matches(&belief));
+        assert!(
+            !BeliefFilter::new()
+                .since(belief.observed_at + chrono::Duration::seconds(1))
+                .matches(&belief)
+        );
+    }
 }
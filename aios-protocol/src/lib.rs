@@ -13,6 +13,8 @@
 //! - [`state`] — AgentStateVector, BudgetState (homeostasis vitals)
 //! - [`mode`] — OperatingMode, GatingProfile (operating constraints)
 //! - [`policy`] — Capability, PolicySet, PolicyEvaluation
+//! - [`capability`] — CapabilitySpec (domain/action/resource parsing, path and host
+//!   globs, subsumption and intersection)
 //! - [`tool`] — ToolCall, ToolOutcome, ToolDefinition, ToolResult, Tool trait, ToolRegistry
 //! - [`sandbox`] — SandboxTier, SandboxLimits, NetworkPolicy
 //! - [`memory`] — SoulProfile, Observation, Provenance, MemoryScope
@@ -40,6 +42,7 @@
 pub mod billing;
 pub mod blob;
 pub mod budget;
+pub mod capability;
 pub mod error;
 pub mod evaluation;
 pub mod event;
@@ -67,20 +70,22 @@ pub mod world;
 
 // Re-export the most commonly used types at the crate root.
 pub use budget::{BudgetDecision, BudgetGatePort, ResourceBudget, ResourceUsage, UsageConfidence};
+pub use capability::{CapabilityError, CapabilitySpec};
 pub use error::{KernelError, KernelResult};
 pub use event::{
     ActorType, ApprovalDecision, EventActor, EventEnvelope, EventKind, EventRecord, EventSchema,
-    KernelDispatchCompleted, KernelDispatchDenied, KernelDispatchStarted, KernelEgressRecorded,
-    KernelForkDenied, KernelPolicyViolated, KernelUsageRecorded, KernelVmCreated,
-    KernelVmDestroyed, KernelVmForked, KernelVmHibernated, KernelVmResumed, KernelVmSnapshotted,
-    LoopPhase, PolicyDecisionKind, RiskLevel, SnapshotType, SpanStatus, SteeringMode, TokenUsage,
+    EventSignature, KernelDispatchCompleted, KernelDispatchDenied, KernelDispatchStarted,
+    KernelEgressRecorded, KernelForkDenied, KernelPolicyViolated, KernelUsageRecorded,
+    KernelVmCreated, KernelVmDestroyed, KernelVmForked, KernelVmHibernated, KernelVmResumed,
+    KernelVmSnapshotted, LoopPhase, PolicyDecisionKind, RiskLevel, SnapshotType, SpanStatus,
+    SteeringMode, TokenUsage,
 };
 pub use hypervisor::{
     BackendCapabilitySet, BackendError, BackendId, BackendSelector, ExecRequest, ExecResult,
     FileWrite, ForkSpec, HypervisorBackend, HypervisorFilesystemExt, Mount, RuntimeHint, VmHandle,
     VmId, VmInfo, VmResources, VmSnapshotHandle, VmSnapshotId, VmSpec, VmSpecOverrides, VmStatus,
 };
-pub use identity::{AgentIdentityProvider, BasicIdentity};
+pub use identity::{AgentIdentityProvider, BasicIdentity, Belief, BeliefFilter, SoulUpdate};
 pub use ids::{
     AgentId, ApprovalId, BlobHash, BranchId, CheckpointId, EventId, HiveTaskId, MemoryId, RunId,
     SeqNo, SessionId, SnapshotId, ToolRunId,
@@ -97,12 +102,13 @@ pub use payment::{
     PaymentAuthorizationDecision, PaymentAuthorizationRequest, PaymentPort,
     PaymentSettlementReceipt, WalletBalanceInfo,
 };
-pub use policy::{Capability, PolicyEvaluation, PolicySet, SubscriptionTier};
+pub use policy::{Capability, PolicyEvaluation, PolicyRuleMatch, PolicySet, SubscriptionTier};
 pub use ports::{
     ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, ConversationTurn,
     EventRecordStream, EventStorePort, KernelPort, ModelCompletion, ModelCompletionRequest,
-    ModelDirective, ModelProviderPort, ModelStopReason, PolicyGateDecision, PolicyGatePort,
-    ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort,
+    ModelCompletionStream, ModelDirective, ModelProviderPort, ModelStopReason, ModelStreamChunk,
+    PolicyGateDecision, PolicyGatePort, StreamingModelProviderPort, ToolExecutionReport,
+    ToolExecutionRequest, ToolHarnessPort,
 };
 pub use rcs::{
     L0, L1, L2, L3, Level, LyapunovCandidate, RecursiveControlledSystem, StabilityBreakdown,
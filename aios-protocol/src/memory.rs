@@ -20,6 +20,9 @@ pub struct SoulProfile {
     pub name: String,
     pub mission: String,
     pub preferences: IndexMap<String, String>,
+    /// Personality trait tags, in the order they were added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub traits: Vec<String>,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -29,6 +32,7 @@ impl Default for SoulProfile {
             name: "Agent OS agent".to_owned(),
             mission: "Run tool-mediated work safely and reproducibly".to_owned(),
             preferences: IndexMap::new(),
+            traits: Vec::new(),
             updated_at: Utc::now(),
         }
     }
@@ -2,10 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::capability::{CapabilityError, CapabilitySpec};
+use crate::event::PolicyDecisionKind;
+
 /// A capability token representing a specific permission.
 ///
 /// Capabilities are pattern-based strings like `"fs:read:/session/**"`.
-/// They support glob matching for flexible policy evaluation.
+/// They parse into a [`CapabilitySpec`] for glob matching, subsumption and
+/// intersection.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Capability(pub String);
 
@@ -37,13 +41,53 @@ impl Capability {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Parse into domain, action and resource. See [`crate::capability`].
+    pub fn parse(&self) -> Result<CapabilitySpec, CapabilityError> {
+        CapabilitySpec::parse(&self.0)
+    }
+
+    /// Whether every capability matched by `other` is also matched by
+    /// `self`. Unparseable capabilities only cover themselves.
+    pub fn covers(&self, other: &Capability) -> bool {
+        match (self.parse(), other.parse()) {
+            (Ok(ours), Ok(theirs)) => ours.covers(&theirs),
+            _ => self == other,
+        }
+    }
+
+    /// Whether some capability is matched by both `self` and `other`.
+    /// Unparseable capabilities only overlap themselves.
+    pub fn overlaps(&self, other: &Capability) -> bool {
+        match (self.parse(), other.parse()) {
+            (Ok(ours), Ok(theirs)) => ours.overlaps(&theirs),
+            _ => self == other,
+        }
+    }
+
+    /// The widest single capability both `self` and `other` cover, if any.
+    pub fn intersection(&self, other: &Capability) -> Option<Capability> {
+        let ours = self.parse().ok()?;
+        let theirs = other.parse().ok()?;
+        ours.intersection(&theirs).map(|spec| spec.to_capability())
+    }
 }
 
 /// A set of policy rules governing agent capabilities.
+///
+/// A requested capability is decided by the most specific pattern that
+/// matches it across all three lists: an exact pattern beats any wildcard,
+/// and a longer wildcard prefix beats a shorter one. Between equally
+/// specific patterns deny wins over gate, and gate over allow. A capability
+/// no pattern matches is denied.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicySet {
     pub allow_capabilities: Vec<Capability>,
     pub gate_capabilities: Vec<Capability>,
+    /// Explicit denials, e.g. `fs:write:/session/state/**` under a
+    /// `fs:write:/session/**` allow.
+    #[serde(default)]
+    pub deny_capabilities: Vec<Capability>,
     pub max_tool_runtime_secs: u64,
     pub max_events_per_turn: u64,
 }
@@ -66,6 +110,7 @@ impl PolicySet {
                 Capability::new("net:egress:*"),
                 Capability::new("secrets:read:*"),
             ],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 30,
             max_events_per_turn: 5,
         }
@@ -100,6 +145,7 @@ impl PolicySet {
                 Capability::new("fs:write:**"),
                 Capability::new("secrets:read:*"),
             ],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 30,
             max_events_per_turn: 15,
         }
@@ -111,6 +157,7 @@ impl PolicySet {
         Self {
             allow_capabilities: vec![Capability::new("*")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 60,
             max_events_per_turn: 50,
         }
@@ -122,6 +169,7 @@ impl PolicySet {
         Self {
             allow_capabilities: vec![Capability::new("*")],
             gate_capabilities: vec![],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 120,
             max_events_per_turn: 200,
         }
@@ -137,6 +185,7 @@ impl Default for PolicySet {
                 Capability::exec("git"),
             ],
             gate_capabilities: vec![Capability::new("payments:initiate")],
+            deny_capabilities: vec![],
             max_tool_runtime_secs: 30,
             max_events_per_turn: 256,
         }
@@ -161,12 +210,107 @@ pub enum SubscriptionTier {
     Enterprise,
 }
 
+impl PolicySet {
+    /// Clamp `self` so it grants nothing `parent` does not.
+    ///
+    /// Used for child sessions: an allowed capability survives only if the
+    /// parent allows it outright, and moves to the gate if the parent only
+    /// gates it. Everything the parent gates stays gated, gated entries the
+    /// parent neither allows nor gates are dropped (so they are denied),
+    /// and the runtime/event limits take the smaller of the two. Both deny
+    /// lists are kept. A child entry that would outrank a parent denial
+    /// anywhere the two overlap is dropped, and an allow that would outrank
+    /// a parent gate is gated, so no more specific child pattern can
+    /// override the parent.
+    pub fn narrowed_to(&self, parent: &PolicySet) -> PolicySet {
+        let allowed_by = |set: &[Capability], capability: &Capability| {
+            set.iter().any(|candidate| candidate.covers(capability))
+        };
+        let outranks_any = |set: &[Capability], capability: &Capability| {
+            set.iter()
+                .any(|rule| outranks_where_overlapping(capability, rule))
+        };
+
+        let mut allow_capabilities = Vec::new();
+        let mut gate_capabilities = parent.gate_capabilities.clone();
+        let mut deny_capabilities = parent.deny_capabilities.clone();
+        for capability in &self.deny_capabilities {
+            if !deny_capabilities.contains(capability) {
+                deny_capabilities.push(capability.clone());
+            }
+        }
+        for capability in &self.allow_capabilities {
+            if allowed_by(&parent.gate_capabilities, capability)
+                || allowed_by(&parent.deny_capabilities, capability)
+                || outranks_any(&parent.deny_capabilities, capability)
+                || !allowed_by(&parent.allow_capabilities, capability)
+            {
+                continue;
+            }
+            if outranks_any(&parent.gate_capabilities, capability) {
+                if !gate_capabilities.contains(capability) {
+                    gate_capabilities.push(capability.clone());
+                }
+            } else {
+                allow_capabilities.push(capability.clone());
+            }
+        }
+        for capability in &self.gate_capabilities {
+            if !gate_capabilities.contains(capability)
+                && !allowed_by(&parent.deny_capabilities, capability)
+                && !outranks_any(&parent.deny_capabilities, capability)
+                && allowed_by(&parent.allow_capabilities, capability)
+            {
+                gate_capabilities.push(capability.clone());
+            }
+        }
+
+        PolicySet {
+            allow_capabilities,
+            gate_capabilities,
+            deny_capabilities,
+            max_tool_runtime_secs: self.max_tool_runtime_secs.min(parent.max_tool_runtime_secs),
+            max_events_per_turn: self.max_events_per_turn.min(parent.max_events_per_turn),
+        }
+    }
+}
+
+/// Whether `child` would win over `parent` for some capability both match:
+/// an alternative of `child` overlaps one of `parent` and is strictly more
+/// specific. Ties go to the parent, whose rule is always the stricter kind.
+fn outranks_where_overlapping(child: &Capability, parent: &Capability) -> bool {
+    let (Ok(child), Ok(parent)) = (child.parse(), parent.parse()) else {
+        return false;
+    };
+    let parent = parent.alternatives();
+    child.alternatives().iter().any(|ours| {
+        parent
+            .iter()
+            .any(|theirs| ours.overlaps(theirs) && ours.specificity() > theirs.specificity())
+    })
+}
+
+/// The rule of a [`PolicySet`] that decided one requested capability.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRuleMatch {
+    pub capability: Capability,
+    pub decision: PolicyDecisionKind,
+    /// `allow:<pattern>`, `gate:<pattern>` or `deny:<pattern>`; `None` when
+    /// no pattern matched and the capability was denied by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    pub explanation: String,
+}
+
 /// Result of evaluating capabilities against a policy set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyEvaluation {
     pub allowed: Vec<Capability>,
     pub requires_approval: Vec<Capability>,
     pub denied: Vec<Capability>,
+    /// One entry per requested capability, in request order.
+    #[serde(default)]
+    pub matches: Vec<PolicyRuleMatch>,
 }
 
 #[cfg(test)]
@@ -269,4 +413,115 @@ mod tests {
         // enterprise allows all via wildcard
         assert!(ps.allow_capabilities.contains(&Capability::new("*")));
     }
+
+    #[test]
+    fn capability_covers_follows_wildcard_matching() {
+        let session_read = Capability::fs_read("/session/**");
+        assert!(session_read.covers(&Capability::fs_read("/session/notes.md")));
+        assert!(session_read.covers(&Capability::fs_read("/session/artifacts/**")));
+        assert!(!session_read.covers(&Capability::fs_read("/etc/passwd")));
+        assert!(!session_read.covers(&Capability::new("*")));
+        assert!(Capability::new("*").covers(&Capability::exec("git")));
+        assert!(!Capability::exec("git").covers(&Capability::exec("*")));
+    }
+
+    #[test]
+    fn narrowed_policy_never_exceeds_parent() {
+        let parent = PolicySet::default();
+        let requested = PolicySet {
+            allow_capabilities: vec![
+                Capability::fs_read("/session/artifacts/**"),
+                Capability::exec("*"),
+                Capability::new("payments:initiate"),
+            ],
+            gate_capabilities: vec![Capability::net_egress("*")],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 600,
+            max_events_per_turn: 16,
+        };
+
+        let child = requested.narrowed_to(&parent);
+        assert_eq!(
+            child.allow_capabilities,
+            vec![Capability::fs_read("/session/artifacts/**")]
+        );
+        assert_eq!(
+            child.gate_capabilities,
+            vec![Capability::new("payments:initiate")]
+        );
+        assert_eq!(child.max_tool_runtime_secs, 30);
+        assert_eq!(child.max_events_per_turn, 16);
+
+        let unchanged = parent.narrowed_to(&parent);
+        assert_eq!(unchanged.allow_capabilities, parent.allow_capabilities);
+        assert_eq!(unchanged.gate_capabilities, parent.gate_capabilities);
+    }
+
+    #[test]
+    fn narrowed_policy_keeps_parent_denials() {
+        let parent = PolicySet {
+            allow_capabilities: vec![Capability::new("*")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![Capability::exec("rm")],
+            max_tool_runtime_secs: 30,
+            max_events_per_turn: 16,
+        };
+        let requested = PolicySet {
+            allow_capabilities: vec![Capability::exec("rm"), Capability::exec("*")],
+            gate_capabilities: vec![],
+            deny_capabilities: vec![Capability::fs_write("/session/state/**")],
+            max_tool_runtime_secs: 30,
+            max_events_per_turn: 16,
+        };
+
+        let child = requested.narrowed_to(&parent);
+        assert_eq!(child.allow_capabilities, vec![Capability::exec("*")]);
+        assert_eq!(
+            child.deny_capabilities,
+            vec![
+                Capability::exec("rm"),
+                Capability::fs_write("/session/state/**")
+            ]
+        );
+    }
+
+    #[test]
+    fn narrowed_policy_drops_child_rules_that_outrank_a_parent_denial() {
+        let parent = PolicySet {
+            allow_capabilities: vec![Capability::fs_write("/**")],
+            gate_capabilities: vec![Capability::fs_write("/session/artifacts/*")],
+            deny_capabilities: vec![Capability::fs_write("/session/state/*.json")],
+            max_tool_runtime_secs: 30,
+            max_events_per_turn: 16,
+        };
+        let requested = PolicySet {
+            allow_capabilities: vec![
+                Capability::fs_write("/session/state/plan{.json,x}"),
+                Capability::fs_write("/session/state/plan{.json,/../../../etc}"),
+                Capability::fs_write("/session/artifacts/{report.md,drafts/*}"),
+                Capability::fs_write("/session/notes/**"),
+            ],
+            gate_capabilities: vec![Capability::fs_write("/session/state/plan.json")],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 30,
+            max_events_per_turn: 16,
+        };
+
+        let child = requested.narrowed_to(&parent);
+        assert_eq!(
+            child.allow_capabilities,
+            vec![Capability::fs_write("/session/notes/**")]
+        );
+        assert_eq!(
+            child.gate_capabilities,
+            vec![
+                Capability::fs_write("/session/artifacts/*"),
+                Capability::fs_write("/session/artifacts/{report.md,drafts/*}"),
+            ]
+        );
+        assert_eq!(
+            child.deny_capabilities,
+            vec![Capability::fs_write("/session/state/*.json")]
+        );
+    }
 }
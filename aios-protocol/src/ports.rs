@@ -11,7 +11,7 @@
 use crate::error::KernelResult;
 use crate::event::{EventRecord, TokenUsage};
 use crate::ids::{ApprovalId, BranchId, RunId, SessionId, ToolRunId};
-use crate::policy::Capability;
+use crate::policy::{Capability, PolicyRuleMatch};
 use crate::tool::{ClientToolDefinition, ToolCall, ToolOutcome};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -47,6 +47,14 @@ pub struct ModelCompletionRequest {
     /// upstream, before this request is built).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub client_tools: Vec<ClientToolDefinition>,
+    /// Model selected by the runtime's routing layer for this attempt
+    /// (`ModelRouting.primary_model` or one of its fallbacks). `None`
+    /// lets the provider use its configured default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Sampling temperature from the session's `ModelRouting`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
 }
 
 /// A single turn in the conversation history (user message + assistant response).
@@ -103,6 +111,21 @@ pub struct ModelCompletion {
     pub final_answer: Option<String>,
 }
 
+/// One increment of a streamed model completion.
+///
+/// Providers yield `Directive` chunks as the model produces them (text
+/// deltas first and foremost) and end the stream with exactly one
+/// `Finished` chunk carrying the completion metadata. Directives on the
+/// terminal completion, if any, are appended after the streamed ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModelStreamChunk {
+    Directive { directive: ModelDirective },
+    Finished { completion: ModelCompletion },
+}
+
+pub type ModelCompletionStream = BoxStream<'static, KernelResult<ModelStreamChunk>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolExecutionRequest {
     pub session_id: SessionId,
@@ -120,7 +143,12 @@ pub struct ToolExecutionReport {
     pub outcome: ToolOutcome,
 }
 
+/// A gate's verdict on a set of requested capabilities.
+///
+/// Non-exhaustive so the verdict can grow without breaking gate
+/// implementations; build one with [`PolicyGateDecision::new`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct PolicyGateDecision {
     #[serde(default)]
     pub allowed: Vec<Capability>,
@@ -128,9 +156,31 @@ pub struct PolicyGateDecision {
     pub requires_approval: Vec<Capability>,
     #[serde(default)]
     pub denied: Vec<Capability>,
+    /// The deciding rule per requested capability, when the gate knows it.
+    #[serde(default)]
+    pub matches: Vec<PolicyRuleMatch>,
 }
 
 impl PolicyGateDecision {
+    pub fn new(
+        allowed: Vec<Capability>,
+        requires_approval: Vec<Capability>,
+        denied: Vec<Capability>,
+    ) -> Self {
+        Self {
+            allowed,
+            requires_approval,
+            denied,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Attach the deciding rule for each requested capability.
+    pub fn with_matches(mut self, matches: Vec<PolicyRuleMatch>) -> Self {
+        self.matches = matches;
+        self
+    }
+
     pub fn is_allowed_now(&self) -> bool {
         self.denied.is_empty() && self.requires_approval.is_empty()
     }
@@ -188,9 +238,27 @@ pub trait ModelProviderPort: Send + Sync {
     async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion>;
 }
 
+/// Streaming variant of [`ModelProviderPort`].
+///
+/// The runtime prefers [`Self::stream`] when a streaming provider is
+/// registered: text deltas are published to live subscribers as they
+/// arrive, and only the committed assistant `Message` is persisted.
+#[async_trait]
+pub trait StreamingModelProviderPort: ModelProviderPort {
+    async fn stream(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletionStream>;
+}
+
 #[async_trait]
 pub trait ToolHarnessPort: Send + Sync {
     async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport>;
+
+    /// The capabilities `call` exercises: what the harness derives from its
+    /// input plus whatever the caller claims. The runtime gates, requests
+    /// approval for and journals these. Defaults to the claim alone, for
+    /// harnesses that cannot look inside a call.
+    fn capabilities(&self, call: &ToolCall) -> KernelResult<Vec<Capability>> {
+        Ok(call.requested_capabilities.clone())
+    }
 }
 
 #[async_trait]
@@ -208,6 +276,15 @@ pub trait PolicyGatePort: Send + Sync {
     ) -> KernelResult<()> {
         Ok(())
     }
+
+    /// The policy the gate currently enforces for a session, when it keeps
+    /// one. Child sessions are narrowed against it.
+    async fn policy(
+        &self,
+        _session_id: SessionId,
+    ) -> KernelResult<Option<crate::policy::PolicySet>> {
+        Ok(None)
+    }
 }
 
 #[async_trait]
@@ -356,6 +433,31 @@ pub trait SessionPort: Send + Sync {
     async fn close(&self, id: SessionId, reason: String) -> KernelResult<()>;
 }
 
+#[cfg(test)]
+mod streaming_provider_tests {
+    use super::*;
+
+    #[test]
+    fn _assert_streaming_provider_dyn_safe() {
+        fn _dyn_safe(_p: &dyn StreamingModelProviderPort) {}
+    }
+
+    #[test]
+    fn stream_chunk_roundtrip() {
+        let chunk = ModelStreamChunk::Directive {
+            directive: ModelDirective::TextDelta {
+                delta: "hel".to_owned(),
+                index: Some(0),
+            },
+        };
+        let json = serde_json::to_value(&chunk).unwrap();
+        assert_eq!(json["type"], "directive");
+        assert_eq!(json["directive"]["type"], "text_delta");
+        let back: ModelStreamChunk = serde_json::from_value(json).unwrap();
+        assert!(matches!(back, ModelStreamChunk::Directive { .. }));
+    }
+}
+
 #[cfg(test)]
 mod session_port_tests {
     use super::*;
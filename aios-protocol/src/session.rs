@@ -13,6 +13,12 @@ pub struct SessionManifest {
     pub workspace_root: String,
     pub model_routing: ModelRouting,
     pub policy: serde_json::Value,
+    /// Session that spawned this one as a sub-agent, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_session: Option<SessionId>,
+    /// Free-form tags set at creation, matched by [`SessionFilter::labels`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
 }
 
 /// LLM model routing configuration.
@@ -77,6 +83,36 @@ pub struct CreateSessionRequest {
     pub labels: Vec<String>,
 }
 
+impl CreateSessionRequest {
+    pub fn new(owner: impl Into<String>) -> Self {
+        Self {
+            owner: owner.into(),
+            policy: crate::policy::PolicySet::default(),
+            parent_session: None,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: crate::policy::PolicySet) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_parent_session(mut self, parent_session: SessionId) -> Self {
+        self.parent_session = Some(parent_session);
+        self
+    }
+
+    pub fn with_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.labels.extend(labels.into_iter().map(Into::into));
+        self
+    }
+}
+
 /// Filter for listing sessions.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -91,6 +127,46 @@ pub struct SessionFilter {
     pub limit: Option<u32>,
 }
 
+impl SessionFilter {
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    pub fn with_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.labels.extend(labels.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn created_after(mut self, after: DateTime<Utc>) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `manifest` passes every criterion set on the filter: same
+    /// owner, carries all of `labels`, created strictly after `after`.
+    /// `limit` is applied by the caller.
+    pub fn matches(&self, manifest: &SessionManifest) -> bool {
+        self.owner
+            .as_ref()
+            .is_none_or(|owner| &manifest.owner == owner)
+            && self
+                .labels
+                .iter()
+                .all(|label| manifest.labels.contains(label))
+            && self.after.is_none_or(|after| manifest.created_at > after)
+    }
+}
+
 /// Input for a single agent tick.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -102,6 +178,26 @@ pub struct TickInput {
     pub max_iterations: Option<u32>,
 }
 
+impl TickInput {
+    pub fn new(objective: impl Into<String>) -> Self {
+        Self {
+            objective: objective.into(),
+            proposed_tool: None,
+            max_iterations: None,
+        }
+    }
+
+    pub fn with_proposed_tool(mut self, call: crate::tool::ToolCall) -> Self {
+        self.proposed_tool = Some(call);
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+}
+
 /// Output from a single agent tick.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -115,6 +211,32 @@ pub struct TickOutput {
     pub usage: Option<crate::event::TokenUsage>,
 }
 
+impl TickOutput {
+    pub fn new(
+        session_id: SessionId,
+        iteration: u32,
+        stop_reason: crate::ports::ModelStopReason,
+    ) -> Self {
+        Self {
+            session_id,
+            iteration,
+            stop_reason,
+            final_answer: None,
+            usage: None,
+        }
+    }
+
+    pub fn with_final_answer(mut self, final_answer: impl Into<String>) -> Self {
+        self.final_answer = Some(final_answer.into());
+        self
+    }
+
+    pub fn with_usage(mut self, usage: crate::event::TokenUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,9 +257,57 @@ mod tests {
             workspace_root: "/tmp/test".into(),
             model_routing: ModelRouting::default(),
             policy: serde_json::json!({}),
+            parent_session: None,
+            labels: Vec::new(),
         };
         let json = serde_json::to_string(&manifest).unwrap();
+        assert!(!json.contains("labels"));
         let back: SessionManifest = serde_json::from_str(&json).unwrap();
         assert_eq!(back.session_id.as_str(), "S1");
+        assert!(back.labels.is_empty());
+    }
+
+    #[test]
+    fn session_filter_matches_owner_labels_and_creation_time() {
+        let created_at = Utc::now();
+        let manifest = SessionManifest {
+            session_id: SessionId::from_string("S1"),
+            owner: "alice".into(),
+            created_at,
+            workspace_root: "/tmp/test".into(),
+            model_routing: ModelRouting::default(),
+            policy: serde_json::json!({}),
+            parent_session: None,
+            labels: vec!["nightly".into(), "billing".into()],
+        };
+
+        assert!(SessionFilter::default().matches(&manifest));
+        assert!(
+            SessionFilter::default()
+                .with_owner("alice")
+                .with_labels(["billing"])
+                .matches(&manifest)
+        );
+        assert!(
+            !SessionFilter::default()
+                .with_owner("bob")
+                .matches(&manifest)
+        );
+        assert!(
+            !SessionFilter::default()
+                .with_labels(["billing", "adhoc"])
+                .matches(&manifest)
+        );
+        let earlier = created_at - chrono::Duration::seconds(1);
+        assert!(
+            SessionFilter::default()
+                .created_after(earlier)
+                .matches(&manifest)
+        );
+        assert!(
+            !SessionFilter::default()
+                .created_after(created_at)
+                .matches(&manifest)
+        );
     }
 }
@@ -1,10 +1,39 @@
 //! World state DTOs (served by opsisd).
+//!
+//! A world is one JSON document changed only by [`WorldMutation`]s, each
+//! a list of [`WorldMutationOp`]s addressed by JSON pointer (`""` or `"/"`
+//! is the whole document). [`WorldMutation::apply_to`] gives every
+//! implementation the same semantics:
+//!
+//! - `set` writes `value` at `path`, creating missing parent objects;
+//! - `remove` deletes `path` (a missing path is a no-op);
+//! - `merge` applies `value` as an RFC 7386 merge patch to `path`;
+//! - `patch` applies `value`, an RFC 6902 patch (`add`, `remove`,
+//!   `replace`, `test`) with paths relative to `path`.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct WorldId(pub String);
 
+impl WorldId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for WorldId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct WorldSnapshot {
@@ -14,6 +43,17 @@ pub struct WorldSnapshot {
     pub taken_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl WorldSnapshot {
+    pub fn new(world: WorldId, version: u64, state: Value) -> Self {
+        Self {
+            world,
+            version,
+            state,
+            taken_at: chrono::Utc::now(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct WorldMutation {
@@ -23,6 +63,33 @@ pub struct WorldMutation {
     pub causation_id: Option<String>,
 }
 
+impl WorldMutation {
+    pub fn new(issued_by: crate::ids::AgentId, ops: Vec<WorldMutationOp>) -> Self {
+        Self {
+            ops,
+            issued_by,
+            causation_id: None,
+        }
+    }
+
+    /// Record what caused the mutation, e.g. an event or tool call id.
+    pub fn with_causation_id(mut self, causation_id: impl Into<String>) -> Self {
+        self.causation_id = Some(causation_id.into());
+        self
+    }
+
+    /// Apply every op to `state`, or none of them: on error `state` is
+    /// left untouched.
+    pub fn apply_to(&self, state: &mut Value) -> Result<(), WorldApplyError> {
+        let mut next = state.clone();
+        for op in &self.ops {
+            op.apply_to(&mut next)?;
+        }
+        *state = next;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct WorldMutationOp {
@@ -32,6 +99,66 @@ pub struct WorldMutationOp {
     pub value: Option<serde_json::Value>,
 }
 
+impl WorldMutationOp {
+    pub fn set(path: impl Into<String>, value: Value) -> Self {
+        Self::with_value(path, WorldOpKind::Set, value)
+    }
+
+    pub fn remove(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            op: WorldOpKind::Remove,
+            value: None,
+        }
+    }
+
+    pub fn merge(path: impl Into<String>, value: Value) -> Self {
+        Self::with_value(path, WorldOpKind::Merge, value)
+    }
+
+    pub fn patch(path: impl Into<String>, patch: Value) -> Self {
+        Self::with_value(path, WorldOpKind::Patch, patch)
+    }
+
+    fn with_value(path: impl Into<String>, op: WorldOpKind, value: Value) -> Self {
+        Self {
+            path: path.into(),
+            op,
+            value: Some(value),
+        }
+    }
+
+    fn apply_to(&self, state: &mut Value) -> Result<(), WorldApplyError> {
+        let tokens = pointer_tokens(&self.path)?;
+        let value = || {
+            self.value
+                .as_ref()
+                .ok_or_else(|| WorldApplyError::MissingValue(self.path.clone()))
+        };
+        match self.op {
+            WorldOpKind::Set => write_at(state, &tokens, value()?.clone(), &self.path, false),
+            WorldOpKind::Remove => remove_at(state, &tokens, &self.path, false),
+            WorldOpKind::Merge => {
+                let mut target = read_at(state, &tokens).cloned().unwrap_or(Value::Null);
+                merge_patch(&mut target, value()?);
+                write_at(state, &tokens, target, &self.path, false)
+            }
+            WorldOpKind::Patch => {
+                let mut target = read_at(state, &tokens)
+                    .cloned()
+                    .ok_or_else(|| WorldApplyError::InvalidPath(self.path.clone()))?;
+                let ops = value()?.as_array().ok_or_else(|| {
+                    WorldApplyError::InvalidPatch("patch value must be an array".to_owned())
+                })?;
+                for op in ops {
+                    apply_json_patch_op(&mut target, op)?;
+                }
+                write_at(state, &tokens, target, &self.path, false)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
@@ -57,3 +184,277 @@ pub struct WorldEvent {
     pub op: WorldMutationOp,
     pub occurred_at: chrono::DateTime<chrono::Utc>,
 }
+
+impl WorldEvent {
+    pub fn new(
+        world: WorldId,
+        version: u64,
+        op: WorldMutationOp,
+        occurred_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            world,
+            version,
+            op,
+            occurred_at,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum WorldApplyError {
+    #[error("invalid world path: {0}")]
+    InvalidPath(String),
+    #[error("op at {0} needs a value")]
+    MissingValue(String),
+    #[error("type conflict at path {path}: expected {expected}")]
+    TypeConflict {
+        path: String,
+        expected: &'static str,
+    },
+    #[error("invalid patch: {0}")]
+    InvalidPatch(String),
+    #[error("patch test failed at {0}")]
+    TestFailed(String),
+}
+
+fn pointer_tokens(path: &str) -> Result<Vec<String>, WorldApplyError> {
+    if path.is_empty() || path == "/" {
+        return Ok(Vec::new());
+    }
+    let rest = path
+        .strip_prefix('/')
+        .ok_or_else(|| WorldApplyError::InvalidPath(path.to_owned()))?;
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn read_at<'a>(root: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    tokens
+        .iter()
+        .try_fold(root, |current, token| match current {
+            Value::Object(map) => map.get(token),
+            Value::Array(items) => token.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+/// Write `value` at `tokens`, creating missing parent objects. With
+/// `insert`, array targets shift later items instead of being replaced.
+fn write_at(
+    root: &mut Value,
+    tokens: &[String],
+    value: Value,
+    path: &str,
+    insert: bool,
+) -> Result<(), WorldApplyError> {
+    let Some((leaf, parents)) = tokens.split_last() else {
+        *root = value;
+        return Ok(());
+    };
+    let mut current = root;
+    for token in parents {
+        current = match current {
+            Value::Object(map) => map
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(Map::new())),
+            Value::Array(items) => {
+                let index = array_index(token, items.len(), path)?;
+                items
+                    .get_mut(index)
+                    .ok_or_else(|| WorldApplyError::InvalidPath(path.to_owned()))?
+            }
+            _ => {
+                return Err(WorldApplyError::TypeConflict {
+                    path: path.to_owned(),
+                    expected: "object or array",
+                });
+            }
+        };
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(leaf.clone(), value);
+        }
+        Value::Array(items) => {
+            let index = if leaf == "-" {
+                items.len()
+            } else {
+                array_index(leaf, items.len() + 1, path)?
+            };
+            if index == items.len() {
+                items.push(value);
+            } else if insert {
+                items.insert(index, value);
+            } else {
+                items[index] = value;
+            }
+        }
+        _ => {
+            return Err(WorldApplyError::TypeConflict {
+                path: path.to_owned(),
+                expected: "object or array",
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Remove the value at `tokens`; a missing value is an error only when
+/// `must_exist` is set.
+fn remove_at(
+    root: &mut Value,
+    tokens: &[String],
+    path: &str,
+    must_exist: bool,
+) -> Result<(), WorldApplyError> {
+    let Some((leaf, parents)) = tokens.split_last() else {
+        *root = Value::Object(Map::new());
+        return Ok(());
+    };
+    let mut current = Some(root);
+    for token in parents {
+        current = current.and_then(|value| match value {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => token.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+            _ => None,
+        });
+    }
+    let removed = match current {
+        Some(Value::Object(map)) => map.remove(leaf).is_some(),
+        Some(Value::Array(items)) => match leaf.parse::<usize>() {
+            Ok(index) if index < items.len() => {
+                items.remove(index);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+    if must_exist && !removed {
+        return Err(WorldApplyError::InvalidPath(path.to_owned()));
+    }
+    Ok(())
+}
+
+fn array_index(token: &str, len: usize, path: &str) -> Result<usize, WorldApplyError> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < len)
+        .ok_or_else(|| WorldApplyError::InvalidPath(path.to_owned()))
+}
+
+/// RFC 7386: objects merge key by key, `null` deletes, anything else
+/// replaces.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+fn apply_json_patch_op(target: &mut Value, op: &Value) -> Result<(), WorldApplyError> {
+    let field = |name: &str| op.get(name);
+    let kind = field("op").and_then(Value::as_str).unwrap_or_default();
+    let path = field("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| WorldApplyError::InvalidPatch(format!("{op} has no path")))?;
+    let tokens = pointer_tokens(path)?;
+    let value = || {
+        field("value")
+            .cloned()
+            .ok_or_else(|| WorldApplyError::MissingValue(path.to_owned()))
+    };
+    match kind {
+        "add" => write_at(target, &tokens, value()?, path, true),
+        "remove" => remove_at(target, &tokens, path, true),
+        "replace" => {
+            if read_at(target, &tokens).is_none() {
+                return Err(WorldApplyError::InvalidPath(path.to_owned()));
+            }
+            write_at(target, &tokens, value()?, path, false)
+        }
+        "test" => {
+            if read_at(target, &tokens) == Some(&value()?) {
+                Ok(())
+            } else {
+                Err(WorldApplyError::TestFailed(path.to_owned()))
+            }
+        }
+        other => Err(WorldApplyError::InvalidPatch(format!(
+            "unsupported patch op {other:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::ids::AgentId;
+
+    fn mutation(ops: Vec<WorldMutationOp>) -> WorldMutation {
+        WorldMutation::new(AgentId::from_string("agent"), ops)
+    }
+
+    #[test]
+    fn ops_set_merge_patch_and_remove() {
+        let mut state = json!({});
+        mutation(vec![
+            WorldMutationOp::set("/rooms/kitchen", json!({ "lights": "off", "temp": 19 })),
+            WorldMutationOp::merge("/rooms/kitchen", json!({ "lights": "on", "temp": null })),
+            WorldMutationOp::set("/log", json!(["boot"])),
+            WorldMutationOp::patch(
+                "/log",
+                json!([
+                    { "op": "test", "path": "/0", "value": "boot" },
+                    { "op": "add", "path": "/-", "value": "lights on" },
+                    { "op": "add", "path": "/0", "value": "init" }
+                ]),
+            ),
+            WorldMutationOp::remove("/rooms/attic"),
+        ])
+        .apply_to(&mut state)
+        .unwrap();
+
+        assert_eq!(
+            state,
+            json!({
+                "rooms": { "kitchen": { "lights": "on" } },
+                "log": ["init", "boot", "lights on"]
+            })
+        );
+    }
+
+    #[test]
+    fn a_failing_op_leaves_the_state_untouched() {
+        let mut state = json!({ "count": 1 });
+        let result = mutation(vec![
+            WorldMutationOp::set("/count", json!(2)),
+            WorldMutationOp::patch("", json!([{ "op": "test", "path": "/count", "value": 1 }])),
+        ])
+        .apply_to(&mut state);
+
+        assert_eq!(
+            result,
+            Err(WorldApplyError::TestFailed("/count".to_owned()))
+        );
+        assert_eq!(state, json!({ "count": 1 }));
+    }
+}
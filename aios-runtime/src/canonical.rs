@@ -0,0 +1,56 @@
+//! Per-branch canonical state, folded from `StatePatchCommitted` events.
+//!
+//! Every session branch owns a [`VersionedCanonicalState`]: a structured
+//! scratchpad (`session`, `agent`, `os`, `memory` namespaces) that starts
+//! empty at version 0. The journal is the source of truth — the state is
+//! the fold of the branch's committed patches, and a forked branch starts
+//! from its parent's state at the fork point. The runtime caches the fold
+//! per branch and advances it as patches are appended.
+//!
+//! The model proposes patches through the runtime-executed `state.patch`
+//! tool. A patch names the `base_version` it was written against; it is
+//! rejected unless that is still the branch's current version, so a stale
+//! writer has to re-read and retry.
+
+use aios_protocol::{EventKind, EventRecord, StatePatch, VersionedCanonicalState};
+use anyhow::{Result, bail};
+
+/// Name of the runtime-executed tool that commits a patch to the branch's
+/// canonical state. Input: a `StatePatch` — `{base_version, ops:
+/// [{op, path, ...}], provenance?}`.
+pub const STATE_PATCH_TOOL: &str = "state.patch";
+
+/// Apply `patch` to `state` as the commit recorded with `new_version`.
+pub(crate) fn apply_committed(
+    state: &mut VersionedCanonicalState,
+    new_version: u64,
+    patch: &StatePatch,
+) -> Result<()> {
+    state.apply_patch(patch)?;
+    if state.version != new_version {
+        bail!(
+            "state patch committed as version {new_version} folds to version {}",
+            state.version
+        );
+    }
+    Ok(())
+}
+
+/// Apply the `StatePatchCommitted` events among `events`, in order.
+pub(crate) fn fold_patches<'a>(
+    state: &mut VersionedCanonicalState,
+    events: impl IntoIterator<Item = &'a EventRecord>,
+) -> Result<()> {
+    for record in events {
+        if let EventKind::StatePatchCommitted { new_version, patch } = &record.kind {
+            apply_committed(state, *new_version, patch).map_err(|error| {
+                error.context(format!(
+                    "failed folding state patch at {}#{}",
+                    record.branch_id.as_str(),
+                    record.sequence
+                ))
+            })?;
+        }
+    }
+    Ok(())
+}
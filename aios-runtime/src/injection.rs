@@ -0,0 +1,255 @@
+//! Prompt-injection screening for tool results.
+//!
+//! Tool output (file contents, command output, fetched pages) reaches the
+//! model through conversation history. [`PromptInjectionGuard`] scans it
+//! for instruction-like text aimed at the agent — "ignore previous
+//! instructions", fake chat-template turns, requests to exfiltrate
+//! credentials — and, once registered with
+//! [`KernelRuntime::with_prompt_injection_guard`](crate::KernelRuntime::with_prompt_injection_guard):
+//!
+//! - wraps the offending segments in `<untrusted_data>` markers when the
+//!   result is rendered into history, with a note that it is data, not
+//!   instructions;
+//! - journals a `prompt_injection.detected` custom event naming the
+//!   patterns that matched;
+//! - optionally raises the session's `risk_level` to `High` until a human
+//!   resolves an approval, so the next side-effecting (non read-only) tool
+//!   call is held for approval.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::Value;
+
+/// Custom event type journaled when a tool result matches.
+pub const PROMPT_INJECTION_EVENT: &str = "prompt_injection.detected";
+
+const UNTRUSTED_OPEN: &str = "<untrusted_data>";
+const UNTRUSTED_CLOSE: &str = "</untrusted_data>";
+
+/// A named instruction-like pattern (`regex` crate syntax).
+#[derive(Debug, Clone)]
+pub struct InjectionPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl InjectionPattern {
+    pub fn new(name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptInjectionConfig {
+    pub patterns: Vec<InjectionPattern>,
+    /// Raise the session's risk level on a match, so the next
+    /// side-effecting call needs approval.
+    pub raise_risk: bool,
+}
+
+impl Default for PromptInjectionConfig {
+    fn default() -> Self {
+        Self {
+            patterns: vec![
+                InjectionPattern::new(
+                    "ignore_instructions",
+                    r"(?i)\b(?:ignore|disregard|forget|override)\b[^.\n]{0,40}?\b(?:previous|prior|above|earlier|all|any|your|system)\b[^.\n]{0,20}?\b(?:instructions?|prompts?|rules|directions|guidelines)\b",
+                ),
+                InjectionPattern::new(
+                    "role_override",
+                    r"(?i)\byou are now\b|\bfrom now on,? you (?:will|must|are)\b|\bact as (?:an? )?(?:unrestricted|jailbroken|unfiltered)\b|\bdeveloper mode\b",
+                ),
+                InjectionPattern::new(
+                    "fake_turn",
+                    r"(?im)<\|im_start\|>|<\|(?:system|assistant|user)\|>|\[/?INST\]|</?system>|^\s*#{2,}\s*(?:system|new instructions)\b",
+                ),
+                InjectionPattern::new(
+                    "new_instructions",
+                    r"(?i)\b(?:new|updated|real|actual|important) instructions\s*[:\-]",
+                ),
+                InjectionPattern::new(
+                    "agent_directive",
+                    r"(?i)\b(?:ai|assistant|agent|llm|language model)s?\b[,:]?\s+(?:must|should|needs? to|is instructed to)\b[^.\n]{0,60}?\b(?:run|execute|call|send|delete|write|curl|upload|reveal|print)\b",
+                ),
+                InjectionPattern::new(
+                    "conceal_from_user",
+                    r"(?i)\bdo not (?:tell|inform|mention|reveal)\b[^.\n]{0,30}?\b(?:the )?user\b",
+                ),
+                InjectionPattern::new(
+                    "exfiltration",
+                    r"(?i)\b(?:send|post|upload|exfiltrate|forward|leak)\b[^.\n]{0,40}?(?:\b(?:api[_ ]?keys?|credentials|secrets?|passwords?|tokens?|environment variables|ssh keys?)\b|\.env\b)",
+                ),
+            ],
+            raise_risk: true,
+        }
+    }
+}
+
+impl PromptInjectionConfig {
+    pub fn with_pattern(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.patterns.push(InjectionPattern::new(name, pattern));
+        self
+    }
+
+    pub fn with_raise_risk(mut self, raise_risk: bool) -> Self {
+        self.raise_risk = raise_risk;
+        self
+    }
+}
+
+/// Compiled [`PromptInjectionConfig`]. Cheap to clone.
+#[derive(Clone)]
+pub struct PromptInjectionGuard {
+    inner: Arc<GuardInner>,
+}
+
+struct GuardInner {
+    patterns: Vec<(String, Regex)>,
+    raise_risk: bool,
+}
+
+impl std::fmt::Debug for PromptInjectionGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromptInjectionGuard")
+            .field(
+                "patterns",
+                &self
+                    .inner
+                    .patterns
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("raise_risk", &self.inner.raise_risk)
+            .finish()
+    }
+}
+
+impl Default for PromptInjectionGuard {
+    fn default() -> Self {
+        Self::new(PromptInjectionConfig::default()).expect("default injection patterns compile")
+    }
+}
+
+impl PromptInjectionGuard {
+    pub fn new(config: PromptInjectionConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .into_iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.pattern)
+                    .with_context(|| format!("invalid injection pattern `{}`", pattern.name))?;
+                Ok((pattern.name, regex))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            inner: Arc::new(GuardInner {
+                patterns,
+                raise_risk: config.raise_risk,
+            }),
+        })
+    }
+
+    pub fn raises_risk(&self) -> bool {
+        self.inner.raise_risk
+    }
+
+    /// Names of the patterns matching anywhere in `text`.
+    pub fn scan(&self, text: &str) -> BTreeSet<String> {
+        self.inner
+            .patterns
+            .iter()
+            .filter(|(_, regex)| regex.is_match(text))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Names of the patterns matching any string inside `value`.
+    pub fn scan_value(&self, value: &Value) -> BTreeSet<String> {
+        let mut matched = BTreeSet::new();
+        self.scan_value_into(value, &mut matched);
+        matched
+    }
+
+    fn scan_value_into(&self, value: &Value, matched: &mut BTreeSet<String>) {
+        match value {
+            Value::String(text) => matched.extend(self.scan(text)),
+            Value::Array(items) => {
+                for item in items {
+                    self.scan_value_into(item, matched);
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values() {
+                    self.scan_value_into(item, matched);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `text` with every line (or JSON string segment) that matches a
+    /// pattern wrapped in `<untrusted_data>` markers, or `None` if nothing
+    /// matched. Works on rendered JSON too: a literal `\n` or a `"` ends a
+    /// segment.
+    pub fn mark_untrusted(&self, text: &str) -> Option<String> {
+        let mut spans: Vec<(usize, usize)> = self
+            .inner
+            .patterns
+            .iter()
+            .flat_map(|(_, regex)| regex.find_iter(text))
+            .map(|found| segment_around(text, found.start(), found.end()))
+            .collect();
+        if spans.is_empty() {
+            return None;
+        }
+        spans.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        let mut marked = String::with_capacity(text.len() + merged.len() * 32);
+        let mut cursor = 0;
+        for (start, end) in merged {
+            marked.push_str(&text[cursor..start]);
+            marked.push_str(UNTRUSTED_OPEN);
+            marked.push_str(&text[start..end]);
+            marked.push_str(UNTRUSTED_CLOSE);
+            cursor = end;
+        }
+        marked.push_str(&text[cursor..]);
+        Some(marked)
+    }
+}
+
+/// Widen `start..end` to the enclosing segment: up to the nearest newline,
+/// escaped newline (`\n` in rendered JSON) or double quote on either side.
+fn segment_around(text: &str, start: usize, end: usize) -> (usize, usize) {
+    let before = &text[..start];
+    let segment_start = [
+        before.rfind('\n').map(|index| index + 1),
+        before.rfind("\\n").map(|index| index + 2),
+        before.rfind('"').map(|index| index + 1),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap_or(0);
+    let after = &text[end..];
+    let segment_end = [after.find('\n'), after.find("\\n"), after.find('"')]
+        .into_iter()
+        .flatten()
+        .min()
+        .map_or(text.len(), |index| end + index);
+    (segment_start, segment_end)
+}
@@ -0,0 +1,199 @@
+//! Intent gate between deliberation and execution.
+//!
+//! With an [`IntentGate`] configured, a completion that plans at least one
+//! side-effecting tool call is first recorded as an intent
+//! (`IntentProposed`: a summary, the planned calls and a risk estimate).
+//! The intent is evaluated against the session policy and the gate's
+//! optional [`IntentEvaluator`] (`IntentEvaluated`), then either approved
+//! and executed, held for human approval when its risk reaches the gate's
+//! threshold, or rejected (`IntentRejected`). Held and rejected intents
+//! run none of their calls; the outcome is rendered into the
+//! conversation history so the model can revise its plan, and an intent a
+//! human approved runs when the model proposes the same calls again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aios_protocol::{Capability, RiskLevel, SessionId, ToolAnnotations, ToolCall};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// `kind` of the intents the runtime proposes for a completion's tool calls.
+pub const TOOL_BATCH_INTENT: &str = "tool_batch";
+
+/// A planned batch of tool calls awaiting evaluation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Intent {
+    pub intent_id: String,
+    pub summary: String,
+    pub planned_calls: Vec<ToolCall>,
+    pub risk: RiskLevel,
+}
+
+/// An [`IntentEvaluator`]'s judgement of an intent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntentVerdict {
+    pub allowed: bool,
+    pub reasons: Vec<String>,
+    /// Replaces the runtime's risk estimate when set.
+    pub risk: Option<RiskLevel>,
+}
+
+impl IntentVerdict {
+    pub fn allow() -> Self {
+        Self {
+            allowed: true,
+            reasons: Vec::new(),
+            risk: None,
+        }
+    }
+
+    pub fn reject(reason: impl Into<String>) -> Self {
+        Self {
+            allowed: false,
+            reasons: vec![reason.into()],
+            risk: None,
+        }
+    }
+
+    pub fn with_risk(mut self, risk: RiskLevel) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+}
+
+/// Judges intents after the policy check, e.g. against task scope or a
+/// second model.
+#[async_trait]
+pub trait IntentEvaluator: Send + Sync {
+    async fn evaluate(&self, session_id: &SessionId, intent: &Intent) -> Result<IntentVerdict>;
+}
+
+/// Configuration of the intent gate. See the module docs.
+#[derive(Clone)]
+pub struct IntentGate {
+    pub(crate) approval_threshold: RiskLevel,
+    pub(crate) evaluator: Option<Arc<dyn IntentEvaluator>>,
+}
+
+impl Default for IntentGate {
+    fn default() -> Self {
+        Self {
+            approval_threshold: RiskLevel::High,
+            evaluator: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for IntentGate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntentGate")
+            .field("approval_threshold", &self.approval_threshold)
+            .field("evaluator", &self.evaluator.is_some())
+            .finish()
+    }
+}
+
+impl IntentGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold intents at or above `risk` for human approval (default `High`).
+    pub fn with_approval_threshold(mut self, risk: RiskLevel) -> Self {
+        self.approval_threshold = risk;
+        self
+    }
+
+    pub fn with_evaluator(mut self, evaluator: Arc<dyn IntentEvaluator>) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    pub(crate) fn requires_approval(&self, risk: RiskLevel) -> bool {
+        risk_rank(risk) >= risk_rank(self.approval_threshold)
+    }
+}
+
+/// Intent awaiting a human decision, keyed by approval id.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingIntent {
+    pub(crate) intent_id: String,
+    pub(crate) branch_id: aios_protocol::BranchId,
+    pub(crate) fingerprint: String,
+}
+
+pub(crate) fn risk_rank(risk: RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+        RiskLevel::Critical => 3,
+    }
+}
+
+fn risk_from_rank(rank: u8) -> RiskLevel {
+    match rank {
+        0 => RiskLevel::Low,
+        1 => RiskLevel::Medium,
+        2 => RiskLevel::High,
+        _ => RiskLevel::Critical,
+    }
+}
+
+/// Side-effecting calls in one intent above which its risk goes up a level.
+const BULK_SIDE_EFFECTS: usize = 3;
+
+/// Risk of running `calls`: the riskiest call — `Low` for read-only tools,
+/// `High` for destructive tools or command execution, `Medium` otherwise —
+/// raised a level when more than a few calls have side effects.
+pub(crate) fn estimate_risk(
+    calls: &[ToolCall],
+    annotations: &HashMap<String, ToolAnnotations>,
+    is_read_only: impl Fn(&ToolCall) -> bool,
+    capabilities: impl Fn(&ToolCall) -> Vec<Capability>,
+) -> RiskLevel {
+    let mut rank = 0;
+    let mut side_effects = 0;
+    for call in calls {
+        if is_read_only(call) {
+            continue;
+        }
+        side_effects += 1;
+        let destructive = annotations
+            .get(&call.tool_name)
+            .is_some_and(|annotations| annotations.destructive);
+        let executes = capabilities(call)
+            .iter()
+            .any(|capability| capability.as_str().starts_with("exec:"));
+        rank = rank.max(if destructive || executes { 2 } else { 1 });
+    }
+    if side_effects > BULK_SIDE_EFFECTS {
+        rank += 1;
+    }
+    risk_from_rank(rank)
+}
+
+/// Identifies a set of planned calls by what they do, ignoring call ids,
+/// so the re-proposal of an approved intent is recognised.
+pub(crate) fn fingerprint(calls: &[ToolCall]) -> String {
+    let mut hasher = Sha256::new();
+    for call in calls {
+        hasher.update(call.tool_name.as_bytes());
+        hasher.update([0]);
+        hasher.update(call.input.to_string().as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// What the runtime does with a gated completion's calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IntentDecision {
+    Proceed,
+    /// Waiting for a human; none of the calls run this tick.
+    Held,
+    Rejected,
+}
@@ -0,0 +1,477 @@
+//! Memory pipeline: observations → reflections → committed memories →
+//! recall.
+//!
+//! - Every recorded tool result appends an [`Observation`] to the session
+//!   scope, with the event range it came from as provenance
+//!   (`ObservationAppended`).
+//! - During a tick's Reflect phase, once a scope holds
+//!   `RuntimeConfig::memory_reflect_every` unreflected observations, they
+//!   are compacted into a [`Reflection`] summary (`ReflectionCompacted`).
+//! - The model proposes memories through the runtime-executed
+//!   `memory.propose` tool (`MemoryProposed`). Entries that pass the
+//!   [`MemoryEvaluator`] are committed (`MemoryCommitted`); proposals for
+//!   the `user`, `agent` and `org` scopes additionally need the session's
+//!   policy to allow `memory:write:<scope>`, and otherwise wait for
+//!   [`KernelRuntime::commit_memory_proposal`](crate::KernelRuntime::commit_memory_proposal).
+//! - Before each model call, the live memories and latest reflections
+//!   that share terms with the objective are recalled into the system
+//!   prompt (`memory.recalled`).
+//!
+//! Content lives in one directory per scope — `memory/` in the session
+//! workspace for the session scope, `<root>/memory/user/<owner>`,
+//! `<root>/memory/agent` and `<root>/memory/org` otherwise. Events carry
+//! the sha256 of each stored record as their blob reference.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use aios_protocol::{BlobHash, MemoryId, MemoryScope, Observation, Provenance};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// Name of the runtime-executed tool that proposes memories. Input:
+/// `{scope?, entries: [{text, tags?, supersedes?}]}`; `scope` defaults to
+/// `session`.
+pub const MEMORY_PROPOSE_TOOL: &str = "memory.propose";
+
+/// Custom event type journaled when memories are recalled into a prompt.
+pub const MEMORY_RECALLED_EVENT: &str = "memory.recalled";
+
+const OBSERVATIONS_FILE: &str = "observations.jsonl";
+const REFLECTIONS_FILE: &str = "reflections.jsonl";
+const MEMORIES_FILE: &str = "memories.json";
+const PROPOSALS_DIR: &str = "proposals";
+
+/// Observation text kept per entry in a reflection summary.
+const REFLECTION_LINE_CHARS: usize = 200;
+
+/// One memory the model wants to keep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Live memory this entry replaces; it stops being recalled once the
+    /// entry is committed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<MemoryId>,
+}
+
+/// Input of `memory.propose`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryProposal {
+    #[serde(default = "default_scope")]
+    pub scope: MemoryScope,
+    pub entries: Vec<MemoryEntry>,
+}
+
+fn default_scope() -> MemoryScope {
+    MemoryScope::Session
+}
+
+/// A proposal as stored under `proposals/`, with what became of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct StoredProposal {
+    pub proposal_id: MemoryId,
+    pub scope: MemoryScope,
+    pub entries: Vec<MemoryEntry>,
+    pub provenance: Provenance,
+    pub proposed_at: DateTime<Utc>,
+    /// `None` while the proposal waits for a policy decision.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A committed memory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub memory_id: MemoryId,
+    pub scope: MemoryScope,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub proposal_id: MemoryId,
+    pub provenance: Provenance,
+    pub committed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<MemoryId>,
+    /// Set when a later memory supersedes this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<MemoryId>,
+    /// Reason given when the memory was tombstoned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tombstoned: Option<String>,
+}
+
+impl MemoryRecord {
+    /// Neither superseded nor tombstoned.
+    pub fn is_live(&self) -> bool {
+        self.superseded_by.is_none() && self.tombstoned.is_none()
+    }
+}
+
+/// A compacted summary of consecutive observations in one scope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reflection {
+    pub scope: MemoryScope,
+    pub summary: String,
+    /// Observations in the scope covered by this and earlier reflections.
+    pub observations_covered: usize,
+    pub provenance: Provenance,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Decides whether a proposed entry is worth committing.
+pub trait MemoryEvaluator: Send + Sync {
+    /// `Err(reason)` rejects the entry. `live` holds the scope's live
+    /// memories.
+    fn evaluate(
+        &self,
+        scope: MemoryScope,
+        entry: &MemoryEntry,
+        live: &[MemoryRecord],
+    ) -> std::result::Result<(), String>;
+}
+
+/// Rejects empty entries, entries over `max_chars`, and entries that
+/// repeat a live memory (ignoring case and whitespace).
+#[derive(Debug, Clone)]
+pub struct DefaultMemoryEvaluator {
+    pub max_chars: usize,
+}
+
+impl Default for DefaultMemoryEvaluator {
+    fn default() -> Self {
+        Self { max_chars: 2_000 }
+    }
+}
+
+impl MemoryEvaluator for DefaultMemoryEvaluator {
+    fn evaluate(
+        &self,
+        _scope: MemoryScope,
+        entry: &MemoryEntry,
+        live: &[MemoryRecord],
+    ) -> std::result::Result<(), String> {
+        let normalized = normalize(&entry.text);
+        if normalized.is_empty() {
+            return Err("memory text is empty".to_owned());
+        }
+        if entry.text.chars().count() > self.max_chars {
+            return Err(format!("memory text exceeds {} characters", self.max_chars));
+        }
+        if let Some(existing) = live
+            .iter()
+            .find(|record| normalize(&record.text) == normalized)
+        {
+            return Err(format!("duplicates memory {}", existing.memory_id));
+        }
+        Ok(())
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `memory:write:<scope>` — what a proposal outside the session scope
+/// needs from the policy to commit without a human.
+pub(crate) fn write_capability(scope: MemoryScope) -> String {
+    format!("memory:write:{}", scope_name(scope))
+}
+
+pub(crate) fn scope_name(scope: MemoryScope) -> &'static str {
+    match scope {
+        MemoryScope::Session => "session",
+        MemoryScope::User => "user",
+        MemoryScope::Agent => "agent",
+        MemoryScope::Org => "org",
+    }
+}
+
+/// Directory holding a scope's observations, reflections, proposals and
+/// memories.
+pub(crate) fn scope_dir(
+    root: &Path,
+    session_root: &Path,
+    owner: &str,
+    scope: MemoryScope,
+) -> PathBuf {
+    match scope {
+        MemoryScope::Session => session_root.join("memory"),
+        MemoryScope::User => root.join("memory").join("user").join(path_segment(owner)),
+        MemoryScope::Agent | MemoryScope::Org => root.join("memory").join(scope_name(scope)),
+    }
+}
+
+fn path_segment(raw: &str) -> String {
+    let segment: String = raw
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match segment.trim_matches('.') {
+        "" => "_".to_owned(),
+        trimmed => trimmed.to_owned(),
+    }
+}
+
+/// Content reference of a stored record: the sha256 of its JSON.
+pub(crate) fn content_ref<T: Serialize>(value: &T) -> Result<BlobHash> {
+    let payload = serde_json::to_vec(value)?;
+    Ok(BlobHash::from_hex(hex::encode(Sha256::digest(&payload))))
+}
+
+async fn append_line<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| format!("failed opening {}", path.display()))?;
+    file.write_all(line.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+async fn read_lines<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>> {
+    let source = match fs::read_to_string(path).await {
+        Ok(source) => source,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed reading {}", path.display()));
+        }
+    };
+    source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("corrupt line in {}", path.display()))
+        })
+        .collect()
+}
+
+pub(crate) async fn append_observation(dir: &Path, observation: &Observation) -> Result<BlobHash> {
+    append_line(&dir.join(OBSERVATIONS_FILE), observation).await?;
+    content_ref(observation)
+}
+
+pub(crate) async fn observations(dir: &Path) -> Result<Vec<Observation>> {
+    read_lines(&dir.join(OBSERVATIONS_FILE)).await
+}
+
+pub(crate) async fn append_reflection(dir: &Path, reflection: &Reflection) -> Result<BlobHash> {
+    append_line(&dir.join(REFLECTIONS_FILE), reflection).await?;
+    content_ref(reflection)
+}
+
+pub(crate) async fn reflections(dir: &Path) -> Result<Vec<Reflection>> {
+    read_lines(&dir.join(REFLECTIONS_FILE)).await
+}
+
+pub(crate) async fn memories(dir: &Path) -> Result<Vec<MemoryRecord>> {
+    let path = dir.join(MEMORIES_FILE);
+    match fs::read_to_string(&path).await {
+        Ok(source) => serde_json::from_str(&source)
+            .with_context(|| format!("failed parsing {}", path.display())),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error).with_context(|| format!("failed reading {}", path.display())),
+    }
+}
+
+pub(crate) async fn save_memories(dir: &Path, records: &[MemoryRecord]) -> Result<()> {
+    fs::create_dir_all(dir).await?;
+    fs::write(
+        dir.join(MEMORIES_FILE),
+        serde_json::to_string_pretty(records)?,
+    )
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn save_proposal(dir: &Path, proposal: &StoredProposal) -> Result<BlobHash> {
+    let path = dir.join(PROPOSALS_DIR).join(format!(
+        "{}.json",
+        path_segment(proposal.proposal_id.as_str())
+    ));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(proposal)?).await?;
+    content_ref(&proposal.entries)
+}
+
+pub(crate) async fn load_proposal(dir: &Path, proposal_id: &MemoryId) -> Result<StoredProposal> {
+    let path = dir
+        .join(PROPOSALS_DIR)
+        .join(format!("{}.json", path_segment(proposal_id.as_str())));
+    let source = fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("memory proposal not found: {proposal_id}"))?;
+    serde_json::from_str(&source).with_context(|| format!("failed parsing {}", path.display()))
+}
+
+/// Compact `pending` (the scope's observations after `already_covered`)
+/// into a reflection.
+pub(crate) fn reflect(
+    scope: MemoryScope,
+    already_covered: usize,
+    pending: &[Observation],
+) -> Reflection {
+    let event_start = pending
+        .iter()
+        .map(|observation| observation.provenance.event_start)
+        .min()
+        .unwrap_or(0);
+    let event_end = pending
+        .iter()
+        .map(|observation| observation.provenance.event_end)
+        .max()
+        .unwrap_or(0);
+    let mut files = Vec::new();
+    for file in pending
+        .iter()
+        .flat_map(|observation| &observation.provenance.files)
+    {
+        if !files
+            .iter()
+            .any(|known: &aios_protocol::FileProvenance| known.path == file.path)
+        {
+            files.push(file.clone());
+        }
+    }
+
+    let mut summary = format!(
+        "{} observations (events {event_start}-{event_end}):",
+        pending.len()
+    );
+    for observation in pending {
+        summary.push_str("\n- ");
+        summary.extend(observation.text.chars().take(REFLECTION_LINE_CHARS));
+        if observation.text.chars().count() > REFLECTION_LINE_CHARS {
+            summary.push('…');
+        }
+    }
+
+    Reflection {
+        scope,
+        summary,
+        observations_covered: already_covered + pending.len(),
+        provenance: Provenance {
+            event_start,
+            event_end,
+            files,
+        },
+        created_at: Utc::now(),
+    }
+}
+
+/// Something recall can put in front of the model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecalledMemory {
+    pub scope: MemoryScope,
+    /// `None` for a reflection summary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<MemoryId>,
+    pub text: String,
+    #[serde(skip)]
+    score: usize,
+    #[serde(skip)]
+    recorded_at: DateTime<Utc>,
+}
+
+impl RecalledMemory {
+    pub(crate) fn memory(record: &MemoryRecord) -> Self {
+        Self {
+            scope: record.scope,
+            memory_id: Some(record.memory_id.clone()),
+            text: record.text.clone(),
+            score: 0,
+            recorded_at: record.committed_at,
+        }
+    }
+
+    pub(crate) fn reflection(reflection: &Reflection) -> Self {
+        Self {
+            scope: reflection.scope,
+            memory_id: None,
+            text: reflection.summary.clone(),
+            score: 0,
+            recorded_at: reflection.created_at,
+        }
+    }
+}
+
+/// Terms of `text` that recall matches on: lowercase alphanumeric words
+/// of three or more characters.
+fn terms(text: &str) -> BTreeSet<String> {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The `limit` candidates sharing the most terms with `query` (ties go to
+/// the most recent); candidates sharing none are dropped.
+pub(crate) fn rank(
+    query: &str,
+    candidates: Vec<RecalledMemory>,
+    limit: usize,
+) -> Vec<RecalledMemory> {
+    let query = terms(query);
+    let mut ranked: Vec<RecalledMemory> = candidates
+        .into_iter()
+        .filter_map(|mut candidate| {
+            candidate.score = terms(&candidate.text).intersection(&query).count();
+            (candidate.score > 0).then_some(candidate)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.recorded_at.cmp(&a.recorded_at))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// The system-prompt block listing `recalled`.
+pub(crate) fn render_recalled(recalled: &[RecalledMemory]) -> String {
+    let mut block = String::from(
+        "Relevant memories from earlier work (recalled automatically; verify before relying on them):",
+    );
+    for memory in recalled {
+        block.push_str("\n- [");
+        block.push_str(scope_name(memory.scope));
+        match &memory.memory_id {
+            Some(_) => block.push_str("] "),
+            None => block.push_str(" reflection] "),
+        }
+        block.push_str(&memory.text.replace('\n', "\n  "));
+    }
+    block
+}
+
+/// `recalled` as journaled in `memory.recalled`.
+pub(crate) fn recalled_summary(recalled: &[RecalledMemory]) -> Value {
+    serde_json::to_value(recalled).unwrap_or_default()
+}
@@ -0,0 +1,362 @@
+//! Session plan kept in `state/plan.yaml` and mirrored to
+//! `state/task_graph.json`.
+//!
+//! The model edits the plan through the runtime-executed `plan.update`
+//! tool; the runtime moves steps along as ticks and tool calls finish:
+//!
+//! - at tick start, the first pending step whose dependencies are done
+//!   becomes `in_progress` (unless one already is);
+//! - a finished call to the tool a step is bound to (`tool`) completes the
+//!   step, or fails it when the call failed;
+//! - a tick that ends with an answer and no tool calls completes the
+//!   in-progress steps that are not bound to a tool.
+//!
+//! New sessions start from a single `bootstrap` placeholder step. It is
+//! never completed automatically — the first `plan.update` that adds steps
+//! replaces it.
+//!
+//! `AgentStateVector::progress` is derived from the plan, see
+//! [`Plan::progress`].
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Name of the runtime-executed tool that creates, edits and removes plan
+/// steps. Input: `{steps: [{id, title?, status?, depends_on?, tool?}],
+/// remove?: [id], mode?}`.
+pub const PLAN_UPDATE_TOOL: &str = "plan.update";
+
+/// Id of the placeholder step seeded into every new session's plan. It is
+/// dropped by the first `plan.update` that adds real steps.
+const BOOTSTRAP_STEP: &str = "bootstrap";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub version: u32,
+    pub mode: String,
+    #[serde(default)]
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub status: PlanStepStatus,
+    /// Steps that must be completed or skipped before this one starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Tool whose successful call completes this step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStepStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+    Skipped,
+}
+
+impl PlanStepStatus {
+    pub fn is_done(self) -> bool {
+        matches!(self, Self::Completed | Self::Skipped)
+    }
+}
+
+/// `plan.update` input.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlanUpdate {
+    /// Steps to add, or to merge into the existing step with the same id
+    /// (fields left out keep their current value). New steps are appended.
+    #[serde(default)]
+    pub steps: Vec<PlanStepUpdate>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlanStepUpdate {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub status: Option<PlanStepStatus>,
+    #[serde(default)]
+    pub depends_on: Option<Vec<String>>,
+    #[serde(default)]
+    pub tool: Option<String>,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            mode: "explore".to_owned(),
+            steps: vec![PlanStep {
+                id: BOOTSTRAP_STEP.to_owned(),
+                title: None,
+                status: PlanStepStatus::Pending,
+                depends_on: Vec::new(),
+                tool: None,
+            }],
+        }
+    }
+}
+
+impl Plan {
+    /// Share of the plan that is done: completed and skipped steps count
+    /// fully, in-progress steps half. An empty plan has made no progress.
+    pub fn progress(&self) -> f32 {
+        if self.steps.is_empty() {
+            return 0.0;
+        }
+        let done: f32 = self
+            .steps
+            .iter()
+            .map(|step| match step.status {
+                PlanStepStatus::Completed | PlanStepStatus::Skipped => 1.0,
+                PlanStepStatus::InProgress => 0.5,
+                PlanStepStatus::Pending | PlanStepStatus::Failed => 0.0,
+            })
+            .sum();
+        done / self.steps.len() as f32
+    }
+
+    /// Apply a `plan.update`. Validated as a whole: on error the plan is
+    /// left untouched.
+    pub fn apply(&mut self, update: PlanUpdate) -> Result<()> {
+        let mut next = self.clone();
+        let adds_steps = update
+            .steps
+            .iter()
+            .any(|step| step.id != BOOTSTRAP_STEP && next.step(&step.id).is_none());
+        if adds_steps && next.is_placeholder() {
+            next.steps.clear();
+        }
+
+        next.steps.retain(|step| !update.remove.contains(&step.id));
+        for change in update.steps {
+            if change.id.trim().is_empty() {
+                bail!("plan step ids must not be empty");
+            }
+            let index = match next.steps.iter().position(|step| step.id == change.id) {
+                Some(index) => index,
+                None => {
+                    next.steps.push(PlanStep {
+                        id: change.id.clone(),
+                        title: None,
+                        status: PlanStepStatus::Pending,
+                        depends_on: Vec::new(),
+                        tool: None,
+                    });
+                    next.steps.len() - 1
+                }
+            };
+            let step = &mut next.steps[index];
+            if let Some(title) = change.title {
+                step.title = Some(title);
+            }
+            if let Some(status) = change.status {
+                step.status = status;
+            }
+            if let Some(depends_on) = change.depends_on {
+                step.depends_on = depends_on;
+            }
+            if let Some(tool) = change.tool {
+                step.tool = Some(tool).filter(|tool| !tool.is_empty());
+            }
+        }
+        if let Some(mode) = update.mode {
+            next.mode = mode;
+        }
+
+        next.validate()?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Start the first runnable pending step if nothing is in progress.
+    /// Returns whether the plan changed.
+    pub fn start_next(&mut self) -> bool {
+        if self
+            .steps
+            .iter()
+            .any(|step| step.status == PlanStepStatus::InProgress)
+        {
+            return false;
+        }
+        let Some(index) = self.steps.iter().position(|step| {
+            step.status == PlanStepStatus::Pending && self.dependencies_done(step)
+        }) else {
+            return false;
+        };
+        self.steps[index].status = PlanStepStatus::InProgress;
+        true
+    }
+
+    /// Complete (or, when `succeeded` is false, fail) the step bound to
+    /// `tool_name`, preferring the one in progress. Returns whether the
+    /// plan changed.
+    pub fn record_tool(&mut self, tool_name: &str, succeeded: bool) -> bool {
+        let bound = |step: &PlanStep, status: PlanStepStatus| {
+            step.status == status && step.tool.as_deref() == Some(tool_name)
+        };
+        let candidates: &[PlanStepStatus] = if succeeded {
+            &[
+                PlanStepStatus::InProgress,
+                PlanStepStatus::Failed,
+                PlanStepStatus::Pending,
+            ]
+        } else {
+            &[PlanStepStatus::InProgress]
+        };
+        let Some(index) = candidates.iter().find_map(|status| {
+            self.steps.iter().position(|step| {
+                bound(step, *status)
+                    && (*status != PlanStepStatus::Pending || self.dependencies_done(step))
+            })
+        }) else {
+            return false;
+        };
+        self.steps[index].status = if succeeded {
+            PlanStepStatus::Completed
+        } else {
+            PlanStepStatus::Failed
+        };
+        true
+    }
+
+    /// Complete the in-progress steps that are not bound to a tool, after
+    /// a tick answered without calling tools. Returns whether the plan
+    /// changed.
+    pub fn finish_turn(&mut self) -> bool {
+        if self.is_placeholder() {
+            return false;
+        }
+        let mut changed = false;
+        for step in &mut self.steps {
+            if step.status == PlanStepStatus::InProgress && step.tool.is_none() {
+                step.status = PlanStepStatus::Completed;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// `state/task_graph.json`: one node per step, one edge per
+    /// dependency.
+    pub fn task_graph(&self) -> Value {
+        let nodes: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let mut node = serde_json::json!({
+                    "id": step.id,
+                    "type": "task",
+                    "status": step.status,
+                });
+                if let Some(title) = &step.title {
+                    node["title"] = Value::String(title.clone());
+                }
+                if let Some(tool) = &step.tool {
+                    node["tool"] = Value::String(tool.clone());
+                }
+                node
+            })
+            .collect();
+        let edges: Vec<Value> = self
+            .steps
+            .iter()
+            .flat_map(|step| {
+                step.depends_on
+                    .iter()
+                    .map(|dependency| serde_json::json!({ "from": dependency, "to": step.id }))
+            })
+            .collect();
+        serde_json::json!({ "nodes": nodes, "edges": edges })
+    }
+
+    /// `{id, status}` per step, as journaled in `plan.updated` events.
+    pub fn summary(&self) -> Value {
+        Value::Array(
+            self.steps
+                .iter()
+                .map(|step| serde_json::json!({ "id": step.id, "status": step.status }))
+                .collect(),
+        )
+    }
+
+    fn step(&self, id: &str) -> Option<&PlanStep> {
+        self.steps.iter().find(|step| step.id == id)
+    }
+
+    fn dependencies_done(&self, step: &PlanStep) -> bool {
+        step.depends_on.iter().all(|dependency| {
+            self.step(dependency)
+                .is_some_and(|dependency| dependency.status.is_done())
+        })
+    }
+
+    /// Still just the seeded bootstrap step, untouched by the model.
+    fn is_placeholder(&self) -> bool {
+        matches!(
+            self.steps.as_slice(),
+            [step] if step.id == BOOTSTRAP_STEP && !step.status.is_done()
+        )
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            if !ids.insert(step.id.as_str()) {
+                bail!("duplicate plan step id `{}`", step.id);
+            }
+        }
+        for step in &self.steps {
+            if let Some(missing) = step
+                .depends_on
+                .iter()
+                .find(|dependency| !ids.contains(dependency.as_str()))
+            {
+                bail!(
+                    "plan step `{}` depends on unknown step `{missing}`",
+                    step.id
+                );
+            }
+        }
+
+        // Resolve steps whose dependencies are resolved until nothing
+        // changes; anything left over sits on a cycle.
+        let mut resolved: HashSet<&str> = HashSet::new();
+        loop {
+            let before = resolved.len();
+            for step in &self.steps {
+                if step
+                    .depends_on
+                    .iter()
+                    .all(|dependency| resolved.contains(dependency.as_str()))
+                {
+                    resolved.insert(step.id.as_str());
+                }
+            }
+            if resolved.len() == self.steps.len() {
+                return Ok(());
+            }
+            if resolved.len() == before {
+                bail!("plan step dependencies form a cycle");
+            }
+        }
+    }
+}
@@ -0,0 +1,551 @@
+//! Secret and PII redaction for journaled and published events.
+//!
+//! [`Redactor`] finds secrets — tokens with a known API-key prefix,
+//! private-key blocks, high-entropy strings and the values of the secret
+//! scopes a session may read — plus configurable PII patterns, and
+//! replaces each match with `[REDACTED:<class>]`.
+//!
+//! It is applied in two places:
+//!
+//! - [`RedactingEventStore`] wraps the event store and redacts every record
+//!   before it is persisted, whoever appends it.
+//! - [`KernelRuntime::with_redactor`](crate::KernelRuntime::with_redactor)
+//!   redacts every event the runtime journals before it reaches the store,
+//!   so live subscribers only ever see the redacted form. A record that had
+//!   something removed is followed in the journal by a `redaction.audit`
+//!   custom event naming the record and the classes and counts removed
+//!   (never the values). It also binds each session, when it is created or re-attached, to the secret
+//!   scopes its policy grants (`secrets:read:<scope>`), so those values are
+//!   redacted from every event of the session. It also redacts ephemeral
+//!   records (streamed text deltas) before broadcasting, and tool outputs
+//!   spilled to the blob store before they are stored.
+//!   Streamed text is held back until no secret can still be completing
+//!   across a delta boundary (see [`StreamedText`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use aios_protocol::{
+    BranchId, Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelError,
+    KernelResult, PolicySet, SessionId,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde_json::Value;
+
+/// Custom event type journaled after every redacted record.
+pub const REDACTION_AUDIT_EVENT: &str = "redaction.audit";
+
+/// Secret values shorter than this are never redacted: they would match
+/// ordinary text.
+const MIN_SCOPED_SECRET_LEN: usize = 6;
+
+/// A named PII pattern. Matches are replaced with `[REDACTED:pii.<class>]`.
+#[derive(Debug, Clone)]
+pub struct PiiPattern {
+    pub class: String,
+    /// Regular expression (`regex` crate syntax).
+    pub pattern: String,
+}
+
+impl PiiPattern {
+    pub fn new(class: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            class: class.into(),
+            pattern: pattern.into(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RedactionConfig {
+    /// Prefixes of well-known API keys and tokens (`sk-`, `ghp_`, `AKIA`…).
+    /// A token starting with one of them followed by at least 16 key
+    /// characters is a secret.
+    pub secret_prefixes: Vec<String>,
+    /// Shortest token checked for high entropy. `0` disables the check.
+    pub entropy_min_len: usize,
+    /// Shannon entropy, in bits per character, at or above which a token
+    /// mixing upper case, lower case and digits is treated as a secret.
+    pub entropy_threshold: f64,
+    pub pii_patterns: Vec<PiiPattern>,
+    /// Secret values by scope. A session whose policy grants
+    /// `secrets:read:<scope>` has that scope's values redacted.
+    pub secret_scopes: HashMap<String, Vec<String>>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            secret_prefixes: [
+                "sk-ant-",
+                "sk-proj-",
+                "sk-",
+                "sk_live_",
+                "rk_live_",
+                "ghp_",
+                "gho_",
+                "ghu_",
+                "ghs_",
+                "ghr_",
+                "github_pat_",
+                "glpat-",
+                "xoxb-",
+                "xoxp-",
+                "xoxa-",
+                "AKIA",
+                "ASIA",
+                "AIza",
+                "hf_",
+                "npm_",
+            ]
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect(),
+            entropy_min_len: 32,
+            entropy_threshold: 4.2,
+            pii_patterns: vec![
+                PiiPattern::new(
+                    "email",
+                    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+                ),
+                PiiPattern::new("us_ssn", r"\b\d{3}-\d{2}-\d{4}\b"),
+                PiiPattern::new("credit_card", r"\b(?:\d{4}[ -]){3}\d{4}\b"),
+                PiiPattern::new(
+                    "phone",
+                    r"(?:\+\d{1,3}[ .-]?)?\(?\b\d{3}\)?[ .-]\d{3}[ .-]\d{4}\b",
+                ),
+            ],
+            secret_scopes: HashMap::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for RedactionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedactionConfig")
+            .field("secret_prefixes", &self.secret_prefixes)
+            .field("entropy_min_len", &self.entropy_min_len)
+            .field("entropy_threshold", &self.entropy_threshold)
+            .field("pii_patterns", &self.pii_patterns)
+            .field(
+                "secret_scopes",
+                &self.secret_scopes.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl RedactionConfig {
+    pub fn with_pii_pattern(
+        mut self,
+        class: impl Into<String>,
+        pattern: impl Into<String>,
+    ) -> Self {
+        self.pii_patterns.push(PiiPattern::new(class, pattern));
+        self
+    }
+
+    pub fn with_secret_scope<I, S>(mut self, scope: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.secret_scopes
+            .entry(scope.into())
+            .or_default()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Detects and removes secrets and PII. Cheap to clone; clones share the
+/// session → secret-scope bindings.
+#[derive(Clone)]
+pub struct Redactor {
+    inner: Arc<RedactorInner>,
+}
+
+struct RedactorInner {
+    /// `(class, regex)` in precedence order: earlier rules win overlaps.
+    rules: Vec<(String, Regex)>,
+    entropy_candidates: Regex,
+    entropy_min_len: usize,
+    entropy_threshold: f64,
+    secret_scopes: HashMap<String, Vec<String>>,
+    /// Secret values each session may read, bound by
+    /// [`Redactor::bind_policy`].
+    session_secrets: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl std::fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Redactor")
+            .field(
+                "rules",
+                &self
+                    .inner
+                    .rules
+                    .iter()
+                    .map(|(class, _)| class)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl Redactor {
+    pub fn new(config: RedactionConfig) -> Result<Self> {
+        let mut rules = vec![(
+            "secret.private_key".to_owned(),
+            Regex::new(
+                r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+            )?,
+        )];
+        if !config.secret_prefixes.is_empty() {
+            let prefixes = config
+                .secret_prefixes
+                .iter()
+                .map(|prefix| regex::escape(prefix))
+                .collect::<Vec<_>>()
+                .join("|");
+            rules.push((
+                "secret.api_key".to_owned(),
+                Regex::new(&format!(
+                    r"(?:^|[^A-Za-z0-9_-])((?:{prefixes})[A-Za-z0-9_-]{{16,}})"
+                ))?,
+            ));
+        }
+        for pattern in &config.pii_patterns {
+            let regex = Regex::new(&pattern.pattern)
+                .with_context(|| format!("invalid PII pattern `{}`", pattern.class))?;
+            rules.push((format!("pii.{}", pattern.class), regex));
+        }
+
+        Ok(Self {
+            inner: Arc::new(RedactorInner {
+                rules,
+                entropy_candidates: Regex::new(r"[A-Za-z0-9+/=_-]+")?,
+                entropy_min_len: config.entropy_min_len,
+                entropy_threshold: config.entropy_threshold,
+                secret_scopes: config.secret_scopes,
+                session_secrets: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// Redact the values of every secret scope that `capabilities` can
+    /// read (`secrets:read:<scope>`, wildcards included) from the
+    /// session's events.
+    pub fn bind_session(&self, session_id: &SessionId, capabilities: &[Capability]) {
+        let mut values: Vec<String> = self
+            .inner
+            .secret_scopes
+            .iter()
+            .filter(|(scope, _)| {
+                let requested = Capability::secrets(scope);
+                capabilities
+                    .iter()
+                    .any(|capability| capability.covers(&requested))
+            })
+            .flat_map(|(_, values)| values.iter().cloned())
+            .filter(|value| value.len() >= MIN_SCOPED_SECRET_LEN)
+            .collect();
+        // Longest first, so a value containing another is removed whole.
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        values.dedup();
+        self.inner
+            .session_secrets
+            .lock()
+            .insert(session_id.as_str().to_owned(), values);
+    }
+
+    /// [`Self::bind_session`] to what `policy` allows or gates.
+    pub fn bind_policy(&self, session_id: &SessionId, policy: &PolicySet) {
+        let capabilities: Vec<Capability> = policy
+            .allow_capabilities
+            .iter()
+            .chain(&policy.gate_capabilities)
+            .cloned()
+            .collect();
+        self.bind_session(session_id, &capabilities);
+    }
+
+    /// Redact `text`, counting what was removed by class.
+    pub fn redact_text(
+        &self,
+        session_id: &SessionId,
+        text: &str,
+        removed: &mut BTreeMap<String, usize>,
+    ) -> String {
+        let spans = self.spans(session_id, text);
+        if spans.is_empty() {
+            return text.to_owned();
+        }
+        let mut redacted = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for (start, end, class) in spans {
+            redacted.push_str(&text[cursor..start]);
+            redacted.push_str(&format!("[REDACTED:{class}]"));
+            *removed.entry(class).or_default() += 1;
+            cursor = end;
+        }
+        redacted.push_str(&text[cursor..]);
+        redacted
+    }
+
+    /// The disjoint `(start, end, class)` byte ranges of `text` that would
+    /// be redacted, in order.
+    fn spans(&self, session_id: &SessionId, text: &str) -> Vec<(usize, usize, String)> {
+        let mut spans: Vec<(usize, usize, String)> = Vec::new();
+        let mut claim = |start: usize, end: usize, class: &str| {
+            if !spans.iter().any(|(s, e, _)| start < *e && *s < end) {
+                spans.push((start, end, class.to_owned()));
+            }
+        };
+
+        if let Some(values) = self.inner.session_secrets.lock().get(session_id.as_str()) {
+            for value in values {
+                for (start, _) in text.match_indices(value.as_str()) {
+                    claim(start, start + value.len(), "secret.session_scope");
+                }
+            }
+        }
+        for (class, regex) in &self.inner.rules {
+            for captures in regex.captures_iter(text) {
+                // The API-key rule captures the key past its boundary.
+                let matched = captures.get(1).or_else(|| captures.get(0));
+                if let Some(matched) = matched {
+                    claim(matched.start(), matched.end(), class);
+                }
+            }
+        }
+        if self.inner.entropy_min_len > 0 {
+            for candidate in self.inner.entropy_candidates.find_iter(text) {
+                if is_high_entropy_token(
+                    candidate.as_str(),
+                    self.inner.entropy_min_len,
+                    self.inner.entropy_threshold,
+                ) {
+                    claim(candidate.start(), candidate.end(), "secret.high_entropy");
+                }
+            }
+        }
+
+        spans.sort_by_key(|(start, _, _)| *start);
+        spans
+    }
+
+    /// Redact every string in `value` (object keys are kept).
+    pub fn redact_value(
+        &self,
+        session_id: &SessionId,
+        value: &mut Value,
+        removed: &mut BTreeMap<String, usize>,
+    ) {
+        match value {
+            Value::String(text) => {
+                let redacted = self.redact_text(session_id, text, removed);
+                if redacted != *text {
+                    *text = redacted;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_value(session_id, item, removed);
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values_mut() {
+                    self.redact_value(session_id, item, removed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact the payload of `record` in place, returning the classes and
+    /// counts removed. Audit events are left alone.
+    pub fn redact_record(&self, record: &mut EventRecord) -> Result<BTreeMap<String, usize>> {
+        self.redact_kind(&record.session_id, &mut record.kind)
+    }
+
+    /// [`Self::redact_record`] for a payload not yet wrapped in a record.
+    pub fn redact_kind(
+        &self,
+        session_id: &SessionId,
+        kind: &mut EventKind,
+    ) -> Result<BTreeMap<String, usize>> {
+        let mut removed = BTreeMap::new();
+        if matches!(
+            kind,
+            EventKind::Custom { event_type, .. } if event_type == REDACTION_AUDIT_EVENT
+        ) {
+            return Ok(removed);
+        }
+        let mut payload = serde_json::to_value(&*kind)?;
+        self.redact_value(session_id, &mut payload, &mut removed);
+        if !removed.is_empty() {
+            *kind = serde_json::from_value(payload)
+                .context("redacted event payload no longer deserializes")?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Streamed text kept back from publication: the redactor's patterns
+/// match far shorter runs, so a secret split across deltas is whole by the
+/// time any of it leaves this window.
+const STREAM_HOLD_BACK: usize = 256;
+
+/// Streamed text deltas, released for publication in chunks that can each
+/// be redacted on their own.
+///
+/// The last [`STREAM_HOLD_BACK`] bytes are held back, as is everything from
+/// a private-key header whose footer has not streamed yet, and a chunk
+/// never ends inside a match.
+#[derive(Debug, Default)]
+pub(crate) struct StreamedText {
+    text: String,
+    released: usize,
+}
+
+impl StreamedText {
+    pub(crate) fn push(&mut self, delta: &str) {
+        self.text.push_str(delta);
+    }
+
+    /// The next chunk that is safe to publish, if any. `finished` releases
+    /// everything left.
+    pub(crate) fn release(
+        &mut self,
+        redactor: &Redactor,
+        session_id: &SessionId,
+        finished: bool,
+    ) -> Option<String> {
+        let pending = &self.text[self.released..];
+        let mut cut = pending.len();
+        if !finished {
+            cut = cut.saturating_sub(STREAM_HOLD_BACK);
+            if let Some(header) = pending.rfind("-----BEGIN")
+                && !pending[header..].contains("-----END")
+            {
+                cut = cut.min(header);
+            }
+            for (start, end, _) in redactor.spans(session_id, pending) {
+                if start < cut && cut < end {
+                    cut = start;
+                }
+            }
+            while !pending.is_char_boundary(cut) {
+                cut -= 1;
+            }
+        }
+        if cut == 0 {
+            return None;
+        }
+        let chunk = pending[..cut].to_owned();
+        self.released += cut;
+        Some(chunk)
+    }
+}
+
+/// A token is a likely secret when it is long, mixes upper case, lower
+/// case and digits (which rules out hex digests and UUIDs), and its
+/// characters are close to uniformly distributed.
+fn is_high_entropy_token(token: &str, min_len: usize, threshold: f64) -> bool {
+    if token.len() < min_len
+        || !token.bytes().any(|byte| byte.is_ascii_uppercase())
+        || !token.bytes().any(|byte| byte.is_ascii_lowercase())
+        || !token.bytes().any(|byte| byte.is_ascii_digit())
+    {
+        return false;
+    }
+    let mut counts = [0_usize; 256];
+    for byte in token.bytes() {
+        counts[usize::from(byte)] += 1;
+    }
+    let len = token.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy >= threshold
+}
+
+/// [`EventStorePort`] wrapper that redacts records before the wrapped store
+/// persists (and, for journals with a live hub, publishes) them.
+///
+/// Records keep the sequence they were handed. Audits are journaled by the
+/// runtime, which redacts before appending; see
+/// [`KernelRuntime::with_redactor`](crate::KernelRuntime::with_redactor).
+pub struct RedactingEventStore {
+    inner: Arc<dyn EventStorePort>,
+    redactor: Redactor,
+}
+
+impl RedactingEventStore {
+    pub fn new(inner: Arc<dyn EventStorePort>, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+#[async_trait]
+impl EventStorePort for RedactingEventStore {
+    async fn append(&self, mut event: EventRecord) -> KernelResult<EventRecord> {
+        self.redactor
+            .redact_record(&mut event)
+            .map_err(|error| KernelError::Serialization(format!("{error:#}")))?;
+        self.inner.append(event).await
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        self.inner
+            .read(session_id, branch_id, from_sequence, limit)
+            .await
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        self.inner.head(session_id, branch_id).await
+    }
+
+    async fn subscribe(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        self.inner
+            .subscribe(session_id, branch_id, after_sequence)
+            .await
+    }
+}
+
+/// The `redaction.audit` event journaled after `record`, which had
+/// `removed` taken out of it.
+pub(crate) fn audit_kind(record: &EventRecord, removed: BTreeMap<String, usize>) -> EventKind {
+    let event_type = match &record.kind {
+        EventKind::Custom { event_type, .. } => event_type.clone(),
+        other => other.variant_name().to_owned(),
+    };
+    EventKind::Custom {
+        event_type: REDACTION_AUDIT_EVENT.to_owned(),
+        data: serde_json::json!({
+            "event_id": record.event_id,
+            "event_type": event_type,
+            "removed": removed,
+        }),
+    }
+}
@@ -0,0 +1,365 @@
+//! Level-1 stability: the homeostasis loop as a recursive controlled system.
+//!
+//! [`HomeostaticController`] exposes a session's controller as a
+//! [`RecursiveControlledSystem<L1>`]: its state is the
+//! [`AgentStateVector`], its control input the [`OperatingMode`]. The
+//! [`HomeostaticLyapunov`] candidate weighs uncertainty, the error streak
+//! and budget depletion; [`StabilityMonitor`] estimates the decay rate of
+//! that candidate online from tick-to-tick observations, and the switching
+//! cost `(ln ν)/τ_a` from the jumps observed at mode switches and the
+//! average dwell time between them.
+//!
+//! Once registered with
+//! [`KernelRuntime::with_stability_monitor`](crate::KernelRuntime::with_stability_monitor),
+//! every tick journals an `rcs.stability` custom event with the candidate's
+//! value and the budget breakdown. While the margin `λ` is negative the
+//! controller's shield replaces any mode but `Recover`, `AskHuman` and
+//! `Sleep` with the configured safe mode.
+
+use aios_protocol::{
+    AgentStateVector, BudgetState, L1, LyapunovCandidate, OperatingMode, RecursiveControlledSystem,
+    RiskLevel, StabilityBreakdown, StabilityBudget,
+};
+
+/// Custom event type journaled at the end of every monitored tick.
+pub const STABILITY_EVENT: &str = "rcs.stability";
+
+/// Keeps `ln` finite when the candidate reaches zero.
+const VALUE_FLOOR: f64 = 1e-3;
+
+/// `V(x) = w_u·uncertainty + w_e·errors + w_b·depletion` over the state
+/// vector, each term normalized to `[0, 1]`.
+#[derive(Debug, Clone)]
+pub struct HomeostaticLyapunov {
+    pub uncertainty_weight: f64,
+    pub error_weight: f64,
+    pub budget_weight: f64,
+    /// Error streak at which the error term saturates.
+    pub error_scale: u32,
+    /// Fraction of the baseline budget that can be spent before depletion
+    /// counts, so a healthy session's candidate can settle.
+    pub budget_slack: f64,
+    /// Budget depletion is measured against.
+    pub baseline: BudgetState,
+}
+
+impl Default for HomeostaticLyapunov {
+    fn default() -> Self {
+        Self {
+            uncertainty_weight: 1.0,
+            error_weight: 1.0,
+            budget_weight: 0.5,
+            error_scale: 3,
+            budget_slack: 0.5,
+            baseline: BudgetState::default(),
+        }
+    }
+}
+
+impl HomeostaticLyapunov {
+    /// Spent share of the most depleted resource beyond the slack.
+    fn depletion(&self, budget: &BudgetState) -> f64 {
+        let spent = |remaining: f64, baseline: f64| {
+            if baseline > 0.0 {
+                (1.0 - remaining / baseline).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        let depleted = [
+            spent(
+                budget.tool_calls_remaining as f64,
+                self.baseline.tool_calls_remaining as f64,
+            ),
+            spent(
+                budget.tokens_remaining as f64,
+                self.baseline.tokens_remaining as f64,
+            ),
+            spent(
+                budget.error_budget_remaining as f64,
+                self.baseline.error_budget_remaining as f64,
+            ),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max);
+        let slack = self.budget_slack.clamp(0.0, 0.99);
+        ((depleted - slack) / (1.0 - slack)).max(0.0)
+    }
+}
+
+impl LyapunovCandidate<L1> for HomeostaticLyapunov {
+    type State = AgentStateVector;
+
+    fn evaluate(&self, state: &AgentStateVector) -> f64 {
+        let errors = if self.error_scale == 0 {
+            0.0
+        } else {
+            (f64::from(state.error_streak) / f64::from(self.error_scale)).min(1.0)
+        };
+        self.uncertainty_weight * f64::from(state.uncertainty)
+            + self.error_weight * errors
+            + self.budget_weight * self.depletion(&state.budget)
+    }
+
+    /// Change of `V` over one successful tool step of the homeostasis
+    /// controller.
+    fn decrease_rate(&self, state: &AgentStateVector) -> f64 {
+        let mut next = state.clone();
+        homeostatic_step(&mut next, true);
+        self.evaluate(&next) - self.evaluate(state)
+    }
+}
+
+/// The homeostasis controller's response to one tool result.
+pub(crate) fn homeostatic_step(state: &mut AgentStateVector, succeeded: bool) {
+    state.budget.tool_calls_remaining = state.budget.tool_calls_remaining.saturating_sub(1);
+    state.budget.tokens_remaining = state.budget.tokens_remaining.saturating_sub(750);
+    state.budget.time_remaining_ms = state.budget.time_remaining_ms.saturating_sub(1200);
+
+    if succeeded {
+        state.uncertainty = (state.uncertainty * 0.85).max(0.05);
+        state.error_streak = 0;
+        state.side_effect_pressure = (state.side_effect_pressure + 0.2).min(1.0);
+    } else {
+        state.error_streak += 1;
+        state.uncertainty = (state.uncertainty + 0.18).min(1.0);
+        state.budget.error_budget_remaining = state.budget.error_budget_remaining.saturating_sub(1);
+        state.side_effect_pressure = (state.side_effect_pressure * 0.5).max(0.1);
+    }
+
+    state.context_pressure = (state.context_pressure + 0.03).min(1.0);
+    state.human_dependency = if state.error_streak >= 2 { 0.6 } else { 0.0 };
+
+    state.risk_level = if state.uncertainty > 0.75 || state.side_effect_pressure > 0.7 {
+        RiskLevel::High
+    } else if state.uncertainty > 0.45 || state.side_effect_pressure > 0.4 {
+        RiskLevel::Medium
+    } else {
+        RiskLevel::Low
+    };
+}
+
+/// Configuration of the stability monitor. See the module docs.
+#[derive(Debug, Clone)]
+pub struct StabilityConfig {
+    /// Template candidate; each session's baseline budget and error scale
+    /// are filled in when its monitor starts.
+    pub lyapunov: HomeostaticLyapunov,
+    /// Weight of the newest observation in the running estimates.
+    pub smoothing: f64,
+    /// Observations before a negative margin forces the safe mode.
+    pub warmup: u32,
+    pub safe_mode: OperatingMode,
+}
+
+impl Default for StabilityConfig {
+    fn default() -> Self {
+        Self {
+            lyapunov: HomeostaticLyapunov::default(),
+            smoothing: 0.3,
+            warmup: 3,
+            safe_mode: OperatingMode::Recover,
+        }
+    }
+}
+
+impl StabilityConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_lyapunov(mut self, lyapunov: HomeostaticLyapunov) -> Self {
+        self.lyapunov = lyapunov;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_warmup(mut self, warmup: u32) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    pub fn with_safe_mode(mut self, safe_mode: OperatingMode) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+}
+
+/// Online estimate of a session's stability budget.
+#[derive(Debug, Clone)]
+pub struct StabilityMonitor {
+    lyapunov: HomeostaticLyapunov,
+    smoothing: f64,
+    warmup: u32,
+    /// γ: smoothed `ln(V_{k-1}/V_k)` over ticks without a mode switch.
+    decay_rate: f64,
+    /// Smoothed `ln ν` over ticks with a mode switch.
+    log_jump: f64,
+    observations: u32,
+    switches: u32,
+    last_value: Option<f64>,
+}
+
+impl StabilityMonitor {
+    /// Monitor a session whose budget starts at `baseline` and whose
+    /// circuit breaker trips after `error_scale` errors.
+    pub fn new(config: &StabilityConfig, baseline: BudgetState, error_scale: u32) -> Self {
+        Self {
+            lyapunov: HomeostaticLyapunov {
+                baseline,
+                error_scale,
+                ..config.lyapunov.clone()
+            },
+            smoothing: config.smoothing,
+            warmup: config.warmup,
+            decay_rate: 0.0,
+            log_jump: 0.0,
+            observations: 0,
+            switches: 0,
+            last_value: None,
+        }
+    }
+
+    pub fn lyapunov(&self) -> &HomeostaticLyapunov {
+        &self.lyapunov
+    }
+
+    /// `V` at the last observed state.
+    pub fn last_value(&self) -> Option<f64> {
+        self.last_value
+    }
+
+    /// Observe the state at the end of a control interval; `switched`
+    /// when the interval ended in a different mode than it began.
+    pub fn record(&mut self, state: &AgentStateVector, switched: bool) -> f64 {
+        let value = self.lyapunov.evaluate(state);
+        if let Some(previous) = self.last_value {
+            self.observations += 1;
+            let log_ratio = ((value + VALUE_FLOOR) / (previous + VALUE_FLOOR)).ln();
+            if switched {
+                self.switches += 1;
+                self.log_jump += self.smoothing * (log_ratio.max(0.0) - self.log_jump);
+            } else {
+                self.decay_rate += self.smoothing * (-log_ratio - self.decay_rate);
+            }
+        }
+        self.last_value = Some(value);
+        value
+    }
+
+    /// `λ = γ − (ln ν)/τ_a`, with the dwell time `τ_a` in ticks. Level 1
+    /// has no adaptation, design or delay terms of its own.
+    pub fn budget(&self) -> StabilityBudget {
+        let switching_cost = if self.switches == 0 {
+            0.0
+        } else {
+            let dwell = f64::from(self.observations) / f64::from(self.switches);
+            self.log_jump / dwell
+        };
+        StabilityBudget {
+            decay_rate: self.decay_rate,
+            adaptation_cost: 0.0,
+            design_cost: 0.0,
+            delay_cost: 0.0,
+            switching_cost,
+        }
+    }
+
+    /// Whether enough observations were made to act on the margin.
+    pub fn is_warm(&self) -> bool {
+        self.observations >= self.warmup
+    }
+
+    pub fn requires_safe_mode(&self) -> bool {
+        self.is_warm() && self.budget().margin() < 0.0
+    }
+}
+
+/// What [`HomeostaticController::observe`] reports.
+#[derive(Debug, Clone)]
+pub struct HomeostaticObservation {
+    pub state: AgentStateVector,
+    pub mode: OperatingMode,
+    pub lyapunov_value: f64,
+    pub stability: StabilityBreakdown,
+}
+
+/// A session's homeostasis controller at level 1. See the module docs.
+#[derive(Debug, Clone)]
+pub struct HomeostaticController {
+    state: AgentStateVector,
+    mode: OperatingMode,
+    monitor: StabilityMonitor,
+    safe_mode: OperatingMode,
+}
+
+impl HomeostaticController {
+    pub fn new(
+        state: AgentStateVector,
+        mode: OperatingMode,
+        monitor: StabilityMonitor,
+        safe_mode: OperatingMode,
+    ) -> Self {
+        Self {
+            state,
+            mode,
+            monitor,
+            safe_mode,
+        }
+    }
+
+    /// Feed back the state the plant reached under the current mode.
+    pub fn set_state(&mut self, state: AgentStateVector) {
+        self.state = state;
+    }
+
+    pub fn mode(&self) -> OperatingMode {
+        self.mode
+    }
+
+    pub fn monitor(&self) -> &StabilityMonitor {
+        &self.monitor
+    }
+
+    pub fn into_monitor(self) -> StabilityMonitor {
+        self.monitor
+    }
+}
+
+impl RecursiveControlledSystem<L1> for HomeostaticController {
+    type State = AgentStateVector;
+    type Observation = HomeostaticObservation;
+    type Control = OperatingMode;
+
+    fn observe(&self) -> HomeostaticObservation {
+        HomeostaticObservation {
+            state: self.state.clone(),
+            mode: self.mode,
+            lyapunov_value: self.monitor.lyapunov.evaluate(&self.state),
+            stability: self.monitor.budget().breakdown(),
+        }
+    }
+
+    /// Close the control interval at the current state and continue in
+    /// `control`.
+    fn step(&mut self, control: &OperatingMode) {
+        self.monitor.record(&self.state, *control != self.mode);
+        self.mode = *control;
+    }
+
+    fn shield(&self, proposed: OperatingMode) -> OperatingMode {
+        let safe = matches!(
+            proposed,
+            OperatingMode::Recover | OperatingMode::AskHuman | OperatingMode::Sleep
+        );
+        if !safe && self.monitor.requires_safe_mode() {
+            self.safe_mode
+        } else {
+            proposed
+        }
+    }
+}
@@ -0,0 +1,706 @@
+//! Built-in declarative workflow engine.
+//!
+//! [`DeclarativeWorkflowDispatcher`] runs [`TickKind::Workflow`](crate::TickKind)
+//! ticks from workflows declared in YAML or JSON, so simple multi-step
+//! operations need no external workflow crate. A workflow is a list of
+//! steps:
+//!
+//! ```yaml
+//! name: triage
+//! input_schema:
+//!   type: object
+//!   required: [report]
+//! steps:
+//!   - id: classify
+//!     model:
+//!       prompt: "Classify this report as JSON {\"severity\": ...}: {{input.report}}"
+//!       json: true
+//!   - id: escalate
+//!     if:
+//!       when: { path: steps.classify.severity, equals: high }
+//!       then:
+//!         - id: notify
+//!           tool:
+//!             name: fs.write
+//!             input: { path: artifacts/escalation.md, content: "{{input.report}}" }
+//!             capabilities: ["fs:write:/session/artifacts/**"]
+//! output: "{{steps.classify}}"
+//! ```
+//!
+//! Step kinds are `model` (one completion, optionally parsed as JSON),
+//! `tool` (one kernel tool call), `if` (branch on a value of the run
+//! context) and `loop` (repeat nested steps until a condition holds, at most
+//! `max_iterations` times). Strings in step inputs are templates: `{{path}}`
+//! resolves a dotted path into the run context — `input`, `objective`,
+//! `steps.<id>` and, inside a loop, `loop.iteration`. A string that is
+//! exactly one placeholder takes the referenced JSON value as-is.
+//!
+//! Input and output are checked against the optional `input_schema` /
+//! `output_schema`, which support the JSON Schema keywords `type`,
+//! `required`, `properties`, `additionalProperties: false`, `items` and
+//! `enum`. Every executed step is journaled as a `workflow.step` custom
+//! event sharing the tick's run id.
+//!
+//! Model and tool steps run through the tick's
+//! [`WorkflowStepHost`](crate::WorkflowStepHost), so
+//! they get the session's model routing and the same guards, gates and
+//! journaling as the calls of a direct tick.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use aios_protocol::{
+    Capability, EventKind, ModelCompletionRequest, ModelDirective, ToolCall, ToolOutcome,
+};
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{debug, instrument};
+
+use crate::{WorkflowTickDispatcher, WorkflowTickInvocation, WorkflowTickOutcome};
+
+/// Upper bound on `loop.max_iterations`.
+const MAX_LOOP_ITERATIONS: u32 = 100;
+
+/// Upper bound on steps executed by one workflow run, across all loops.
+const MAX_EXECUTED_STEPS: u32 = 1_000;
+
+/// A workflow declared in YAML or JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Schema the tick's workflow input must satisfy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    /// Schema the workflow output must satisfy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    pub steps: Vec<WorkflowStep>,
+    /// Output template rendered against the final run context. Defaults to
+    /// the output of the last executed top-level step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Unique within the workflow; the step's output is `steps.<id>`.
+    pub id: String,
+    #[serde(flatten)]
+    pub action: WorkflowAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowAction {
+    Model(ModelStep),
+    Tool(ToolStep),
+    If(IfStep),
+    Loop(LoopStep),
+}
+
+/// One completion. The step output is the completion's text, or the JSON
+/// it parses to when `json` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStep {
+    pub prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub json: bool,
+    /// Schema the parsed JSON output must satisfy (requires `json`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+/// One kernel tool call. The step output is the tool's success output; a
+/// failed, blocked or gated call fails the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolStep {
+    pub name: String,
+    #[serde(default)]
+    pub input: Value,
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IfStep {
+    pub when: WorkflowCondition,
+    #[serde(default)]
+    pub then: Vec<WorkflowStep>,
+    #[serde(default, rename = "else")]
+    pub otherwise: Vec<WorkflowStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopStep {
+    pub max_iterations: u32,
+    /// Checked after each iteration; the loop stops once it holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<WorkflowCondition>,
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// Test on a value of the run context: equality with `equals` when given,
+/// otherwise truthiness (present and not `null`, `false`, `0`, `""`, `[]`
+/// or `{}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowCondition {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<Value>,
+}
+
+impl WorkflowDefinition {
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        let definition: Self = serde_yaml_ng::from_str(source).context("invalid workflow YAML")?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self> {
+        let definition: Self = serde_json::from_str(source).context("invalid workflow JSON")?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Structural checks run at load time: non-empty step lists, unique
+    /// step ids and bounded loops.
+    pub fn validate(&self) -> Result<()> {
+        fn walk<'a>(steps: &'a [WorkflowStep], ids: &mut HashSet<&'a str>) -> Result<()> {
+            for step in steps {
+                if step.id.is_empty() {
+                    bail!("workflow step ids must not be empty");
+                }
+                if !ids.insert(step.id.as_str()) {
+                    bail!("duplicate workflow step id `{}`", step.id);
+                }
+                match &step.action {
+                    WorkflowAction::Model(model) => {
+                        if model.schema.is_some() && !model.json {
+                            bail!("step `{}`: `schema` requires `json: true`", step.id);
+                        }
+                    }
+                    WorkflowAction::Tool(_) => {}
+                    WorkflowAction::If(branch) => {
+                        walk(&branch.then, ids)?;
+                        walk(&branch.otherwise, ids)?;
+                    }
+                    WorkflowAction::Loop(repeat) => {
+                        if repeat.max_iterations == 0 || repeat.max_iterations > MAX_LOOP_ITERATIONS
+                        {
+                            bail!(
+                                "step `{}`: max_iterations must be between 1 and {MAX_LOOP_ITERATIONS}",
+                                step.id
+                            );
+                        }
+                        if repeat.steps.is_empty() {
+                            bail!("step `{}`: loop has no steps", step.id);
+                        }
+                        walk(&repeat.steps, ids)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        if self.steps.is_empty() {
+            bail!("workflow `{}` has no steps", self.name);
+        }
+        walk(&self.steps, &mut HashSet::new())
+    }
+}
+
+/// Default [`WorkflowTickDispatcher`]: runs [`WorkflowDefinition`]s that
+/// were registered up front or live as `<name>.yaml`, `<name>.yml` or
+/// `<name>.json` in the workflow directory.
+#[derive(Debug, Clone, Default)]
+pub struct DeclarativeWorkflowDispatcher {
+    workflows: HashMap<String, WorkflowDefinition>,
+    directory: Option<PathBuf>,
+}
+
+impl DeclarativeWorkflowDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up workflows that are not registered in `directory`. Files are
+    /// read on every tick, so edits apply without a restart.
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    pub fn with_workflow(mut self, definition: WorkflowDefinition) -> Self {
+        self.workflows.insert(definition.name.clone(), definition);
+        self
+    }
+
+    async fn resolve(&self, name: &str) -> Result<WorkflowDefinition> {
+        if let Some(definition) = self.workflows.get(name) {
+            return Ok(definition.clone());
+        }
+        let directory = self
+            .directory
+            .as_deref()
+            .with_context(|| format!("unknown workflow `{name}`"))?;
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+            bail!("invalid workflow name `{name}`");
+        }
+        for extension in ["yaml", "yml", "json"] {
+            let path = directory.join(format!("{name}.{extension}"));
+            if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                continue;
+            }
+            return load_definition(&path, extension == "json").await;
+        }
+        bail!(
+            "unknown workflow `{name}`: not registered and no {name}.yaml, {name}.yml or \
+             {name}.json in {}",
+            directory.display()
+        )
+    }
+}
+
+async fn load_definition(path: &Path, json: bool) -> Result<WorkflowDefinition> {
+    let source = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed reading workflow {path:?}"))?;
+    let definition = if json {
+        WorkflowDefinition::from_json(&source)
+    } else {
+        WorkflowDefinition::from_yaml(&source)
+    };
+    definition.with_context(|| format!("failed loading workflow {path:?}"))
+}
+
+#[async_trait]
+impl WorkflowTickDispatcher for DeclarativeWorkflowDispatcher {
+    #[instrument(skip(self, invocation), fields(workflow = %invocation.workflow_name))]
+    async fn dispatch(
+        &self,
+        invocation: WorkflowTickInvocation<'_>,
+    ) -> Result<WorkflowTickOutcome> {
+        let definition = self.resolve(invocation.workflow_name).await?;
+        if let Some(schema) = &definition.input_schema {
+            validate_schema(invocation.workflow_input, schema, "input")
+                .context("workflow input does not match input_schema")?;
+        }
+
+        let mut run = WorkflowRun {
+            invocation: &invocation,
+            context: serde_json::json!({
+                "input": invocation.workflow_input,
+                "objective": invocation.objective,
+                "steps": {},
+            }),
+            steps_executed: 0,
+            step_index: 0,
+        };
+        let last = run.run_steps(&definition.steps).await?;
+
+        let output = match &definition.output {
+            Some(template) => render(template, &run.context)?,
+            None => last,
+        };
+        if let Some(schema) = &definition.output_schema {
+            validate_schema(&output, schema, "output")
+                .context("workflow output does not match output_schema")?;
+        }
+        debug!(
+            steps_executed = run.steps_executed,
+            "declarative workflow finished"
+        );
+
+        // Every event went through the host, which the kernel counts.
+        Ok(WorkflowTickOutcome {
+            events_emitted: 0,
+            output,
+            next_mode: None,
+        })
+    }
+}
+
+struct WorkflowRun<'a, 'b> {
+    invocation: &'a WorkflowTickInvocation<'b>,
+    /// `{input, objective, steps: {<id>: output}, loop?: {iteration}}`.
+    context: Value,
+    steps_executed: u32,
+    /// Model request `step_index`, incremented per model step.
+    step_index: u32,
+}
+
+type StepFuture<'f> = Pin<Box<dyn Future<Output = Result<Value>> + Send + 'f>>;
+
+impl<'a, 'b> WorkflowRun<'a, 'b> {
+    /// Run `steps` in order, returning the output of the last one (`null`
+    /// when empty). Boxed because `if` and `loop` recurse.
+    fn run_steps<'f>(&'f mut self, steps: &'f [WorkflowStep]) -> StepFuture<'f> {
+        Box::pin(async move {
+            let mut last = Value::Null;
+            for step in steps {
+                last = self.run_step(step).await?;
+            }
+            Ok(last)
+        })
+    }
+
+    async fn run_step(&mut self, step: &WorkflowStep) -> Result<Value> {
+        self.steps_executed += 1;
+        if self.steps_executed > MAX_EXECUTED_STEPS {
+            bail!("workflow exceeded {MAX_EXECUTED_STEPS} executed steps");
+        }
+
+        let (kind, result) = match &step.action {
+            WorkflowAction::Model(model) => ("model", self.run_model(model).await),
+            WorkflowAction::Tool(tool) => ("tool", self.run_tool(&step.id, tool).await),
+            WorkflowAction::If(branch) => {
+                let taken = condition_holds(&branch.when, &self.context);
+                self.journal(&step.id, "if", serde_json::json!({ "branch": taken }))
+                    .await?;
+                let steps = if taken {
+                    &branch.then
+                } else {
+                    &branch.otherwise
+                };
+                ("if", self.run_steps(steps).await)
+            }
+            WorkflowAction::Loop(repeat) => ("loop", self.run_loop(repeat).await),
+        };
+
+        match result {
+            Ok(output) => {
+                if matches!(
+                    step.action,
+                    WorkflowAction::Model(_) | WorkflowAction::Tool(_)
+                ) {
+                    self.journal(
+                        &step.id,
+                        kind,
+                        serde_json::json!({ "status": "ok", "output": output }),
+                    )
+                    .await?;
+                }
+                self.context["steps"][step.id.as_str()] = output.clone();
+                Ok(output)
+            }
+            Err(error) => {
+                self.journal(
+                    &step.id,
+                    kind,
+                    serde_json::json!({ "status": "error", "error": format!("{error:#}") }),
+                )
+                .await?;
+                Err(error.context(format!("workflow step `{}` failed", step.id)))
+            }
+        }
+    }
+
+    async fn run_model(&mut self, step: &ModelStep) -> Result<Value> {
+        let invocation = self.invocation;
+        let prompt = match render(&Value::String(step.prompt.clone()), &self.context)? {
+            Value::String(prompt) => prompt,
+            other => other.to_string(),
+        };
+        let request = ModelCompletionRequest {
+            session_id: invocation.session_id.clone(),
+            branch_id: invocation.branch_id.clone(),
+            run_id: invocation.run_id.clone(),
+            step_index: self.step_index,
+            objective: prompt,
+            proposed_tool: None,
+            system_prompt: step
+                .system_prompt
+                .clone()
+                .or_else(|| invocation.system_prompt.map(ToOwned::to_owned)),
+            // Model steps answer in text; tools run only as `tool` steps.
+            allowed_tools: Some(Vec::new()),
+            conversation_history: Vec::new(),
+            client_tools: Vec::new(),
+            model: step.model.clone(),
+            temperature: step
+                .temperature
+                .or(Some(invocation.manifest.model_routing.temperature)),
+        };
+        self.step_index += 1;
+
+        let completion = invocation.host.complete(request).await?;
+        let mut text = String::new();
+        for directive in &completion.directives {
+            match directive {
+                ModelDirective::Message { role, content } if role == "assistant" => {
+                    text.push_str(content);
+                }
+                ModelDirective::TextDelta { delta, .. } => text.push_str(delta),
+                _ => {}
+            }
+        }
+        if text.is_empty()
+            && let Some(answer) = completion.final_answer
+        {
+            text = answer;
+        }
+
+        if !step.json {
+            return Ok(Value::String(text));
+        }
+        let parsed: Value = serde_json::from_str(strip_code_fence(&text))
+            .with_context(|| format!("model output is not JSON: {text}"))?;
+        if let Some(schema) = &step.schema {
+            validate_schema(&parsed, schema, "output")?;
+        }
+        Ok(parsed)
+    }
+
+    async fn run_tool(&mut self, step_id: &str, step: &ToolStep) -> Result<Value> {
+        let invocation = self.invocation;
+        if let Some(allowed) = invocation.allowed_tools
+            && !allowed.iter().any(|name| name == &step.name)
+        {
+            bail!("tool `{}` is not in this tick's allowed_tools", step.name);
+        }
+        let input = render(&step.input, &self.context)?;
+
+        let report = invocation
+            .host
+            .call_tool(ToolCall {
+                call_id: format!("{}:{step_id}", invocation.run_id),
+                tool_name: step.name.clone(),
+                input,
+                requested_capabilities: step.capabilities.clone(),
+            })
+            .await?;
+        match report.outcome {
+            ToolOutcome::Success { output } => Ok(output),
+            ToolOutcome::Failure { error } => bail!("tool `{}` failed: {error}", step.name),
+        }
+    }
+
+    async fn run_loop(&mut self, step: &LoopStep) -> Result<Value> {
+        let outer = self.context.get("loop").cloned();
+        let mut iterations = 0;
+        let mut completed = false;
+        while iterations < step.max_iterations {
+            self.context["loop"] = serde_json::json!({ "iteration": iterations });
+            self.run_steps(&step.steps).await?;
+            iterations += 1;
+            if let Some(until) = &step.until
+                && condition_holds(until, &self.context)
+            {
+                completed = true;
+                break;
+            }
+        }
+        match outer {
+            Some(outer) => self.context["loop"] = outer,
+            None => {
+                if let Some(context) = self.context.as_object_mut() {
+                    context.remove("loop");
+                }
+            }
+        }
+        Ok(serde_json::json!({
+            "iterations": iterations,
+            "until_met": completed,
+        }))
+    }
+
+    /// Journal a `workflow.step` event through the kernel.
+    async fn journal(&mut self, step_id: &str, kind: &str, mut data: Value) -> Result<()> {
+        let invocation = self.invocation;
+        data["workflow"] = Value::String(invocation.workflow_name.to_owned());
+        data["step"] = Value::String(step_id.to_owned());
+        data["kind"] = Value::String(kind.to_owned());
+        if let Some(iteration) = self.context.pointer("/loop/iteration") {
+            data["iteration"] = iteration.clone();
+        }
+        invocation
+            .host
+            .journal(EventKind::Custom {
+                event_type: "workflow.step".to_owned(),
+                data,
+            })
+            .await
+    }
+}
+
+/// Strip a surrounding Markdown code fence, which models often add around
+/// JSON answers.
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(body) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    let body = body.split_once('\n').map_or("", |(_, rest)| rest);
+    body.strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// Resolve a dotted path (`steps.classify.severity`, `input.items.0`)
+/// against the run context.
+fn lookup<'v>(context: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(context, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+fn condition_holds(condition: &WorkflowCondition, context: &Value) -> bool {
+    let value = lookup(context, &condition.path);
+    match &condition.equals {
+        Some(expected) => value == Some(expected),
+        None => match value {
+            None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+            Some(Value::Number(number)) => number.as_f64() != Some(0.0),
+            Some(Value::String(text)) => !text.is_empty(),
+            Some(Value::Array(items)) => !items.is_empty(),
+            Some(Value::Object(map)) => !map.is_empty(),
+            Some(Value::Bool(true)) => true,
+        },
+    }
+}
+
+/// Substitute `{{path}}` placeholders in every string of `template`.
+fn render(template: &Value, context: &Value) -> Result<Value> {
+    Ok(match template {
+        Value::String(text) => render_string(text, context)?,
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render(item, context))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| Ok((key.clone(), render(value, context)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn render_string(text: &str, context: &Value) -> Result<Value> {
+    let resolve = |path: &str| {
+        lookup(context, path.trim())
+            .with_context(|| format!("template path `{}` is not set", path.trim()))
+    };
+
+    let trimmed = text.trim();
+    if let Some(path) = trimmed
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        && !path.contains("{{")
+        && !path.contains("}}")
+    {
+        return resolve(path).cloned();
+    }
+
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match resolve(&rest[start + 2..start + end])? {
+            Value::String(value) => rendered.push_str(value),
+            value => rendered.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(Value::String(rendered))
+}
+
+/// Validate `value` against the supported JSON Schema subset, naming the
+/// first offending location (`input.items[2].name`).
+fn validate_schema(value: &Value, schema: &Value, at: &str) -> Result<()> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            bail!(
+                "{at}: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            );
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        bail!(
+            "{at}: {value} is not one of {}",
+            Value::Array(allowed.clone())
+        );
+    }
+
+    if let Value::Object(map) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(key) {
+                    bail!("{at}: missing required property `{key}`");
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, property) in map {
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property_schema) => {
+                    validate_schema(property, property_schema, &format!("{at}.{key}"))?;
+                }
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    bail!("{at}: unexpected property `{key}`");
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_schema(item, item_schema, &format!("{at}[{index}]"))?;
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
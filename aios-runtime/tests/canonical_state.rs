@@ -0,0 +1,381 @@
+//! Event-sourced canonical state per session branch.
+//!
+//! `state.patch` commits a `StatePatch` against the branch's current
+//! version; the state is the fold of `StatePatchCommitted` events, a fork
+//! starts from its parent's state at the fork point, and a restarted
+//! runtime rebuilds it from the journal.
+
+use std::sync::Arc;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelResult,
+    ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelRouting,
+    ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, ProvenanceRef, SessionId,
+    SpanStatus, ToolCall, ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome,
+    ToolRunId,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, STATE_PATCH_TOOL, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::json;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Provider and harness ──────────────────────────────────────────────
+
+/// Answers in text, without tool calls.
+struct AnsweringProvider;
+
+#[async_trait]
+impl ModelProviderPort for AnsweringProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "noted".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("noted".to_owned()),
+        })
+    }
+}
+
+/// `state.patch` never reaches the harness; anything else succeeds.
+struct OkHarness;
+
+#[async_trait]
+impl ToolHarnessPort for OkHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        assert_ne!(request.call.tool_name, STATE_PATCH_TOOL);
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: json!({ "ok": true }),
+            },
+        })
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+fn runtime(root: &std::path::Path, store: &Arc<MemEventStore>) -> KernelRuntime {
+    KernelRuntime::new(
+        RuntimeConfig::new(root),
+        store.clone() as Arc<dyn EventStorePort>,
+        Arc::new(AnsweringProvider) as Arc<dyn ModelProviderPort>,
+        Arc::new(OkHarness) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    )
+}
+
+async fn open_session(runtime: &KernelRuntime, session: &SessionId) {
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "canonical-state-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+}
+
+async fn patch(
+    runtime: &KernelRuntime,
+    session: &SessionId,
+    branch: &BranchId,
+    input: serde_json::Value,
+) -> String {
+    let call = ToolCall::new(STATE_PATCH_TOOL, input, Vec::new());
+    let call_id = call.call_id.clone();
+    runtime
+        .tick_on_branch(
+            session,
+            branch,
+            TickInput {
+                objective: "remember this".to_owned(),
+                proposed_tool: Some(call),
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+    call_id
+}
+
+fn set(base_version: u64, path: &str, value: serde_json::Value) -> serde_json::Value {
+    json!({
+        "base_version": base_version,
+        "ops": [{ "op": "set", "path": path, "value": value }],
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn patches_commit_against_the_current_version_and_survive_a_restart() {
+    let root =
+        std::env::temp_dir().join(format!("aios-runtime-canonical-{}", uuid::Uuid::new_v4()));
+    let store = Arc::new(MemEventStore::default());
+    let session = SessionId::default();
+    let main = BranchId::main();
+    let first = runtime(&root, &store);
+    open_session(&first, &session).await;
+
+    let initial = first.canonical_state(&session, &main).await.expect("state");
+    assert_eq!(initial.version, 0);
+
+    let call_id = patch(
+        &first,
+        &session,
+        &main,
+        set(0, "/session/goal", json!("ship")),
+    )
+    .await;
+    patch(
+        &first,
+        &session,
+        &main,
+        json!({
+            "base_version": 1,
+            "ops": [{ "op": "append", "path": "/agent/notes", "values": ["a", "b"] }],
+        }),
+    )
+    .await;
+    let state = first.canonical_state(&session, &main).await.expect("state");
+    assert_eq!(state.version, 2);
+    assert_eq!(state.state.session["goal"], "ship");
+    assert_eq!(state.state.agent["notes"], json!(["a", "b"]));
+
+    // The commit cites the call that proposed it.
+    let events = store.events.lock().clone();
+    let requested = events
+        .iter()
+        .find(|record| {
+            matches!(&record.kind, EventKind::ToolCallRequested { call_id: id, .. } if *id == call_id)
+        })
+        .expect("tool call requested");
+    let committed: Vec<_> = events
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::StatePatchCommitted { new_version, patch } => Some((*new_version, patch)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(committed.len(), 2);
+    assert_eq!(committed[0].0, 1);
+    assert_eq!(
+        committed[0].1.provenance,
+        vec![ProvenanceRef::Event {
+            event_id: requested.event_id.to_string(),
+        }]
+    );
+
+    // A patch written against a stale version is rejected and changes nothing.
+    let stale = patch(
+        &first,
+        &session,
+        &main,
+        set(1, "/session/goal", json!("stale")),
+    )
+    .await;
+    let rejection = store
+        .events
+        .lock()
+        .iter()
+        .find_map(|record| match &record.kind {
+            EventKind::ToolCallCompleted {
+                call_id: Some(id),
+                result,
+                status,
+                ..
+            } if *id == stale => Some((result.to_string(), *status)),
+            _ => None,
+        })
+        .expect("stale patch completed");
+    assert_eq!(rejection.1, SpanStatus::Error);
+    assert!(
+        rejection.0.contains("base version mismatch"),
+        "{}",
+        rejection.0
+    );
+    let state = first.canonical_state(&session, &main).await.expect("state");
+    assert_eq!(state.version, 2);
+    assert_eq!(state.state.session["goal"], "ship");
+
+    // A fresh runtime over the same journal folds the same state.
+    let second = runtime(&root, &store);
+    open_session(&second, &session).await;
+    assert_eq!(
+        second
+            .canonical_state(&session, &main)
+            .await
+            .expect("state"),
+        state
+    );
+}
+
+#[tokio::test]
+async fn a_branch_starts_from_its_parent_state_at_the_fork_point() {
+    let root =
+        std::env::temp_dir().join(format!("aios-runtime-canonical-{}", uuid::Uuid::new_v4()));
+    let store = Arc::new(MemEventStore::default());
+    let session = SessionId::default();
+    let main = BranchId::main();
+    let runtime = runtime(&root, &store);
+    open_session(&runtime, &session).await;
+
+    patch(
+        &runtime,
+        &session,
+        &main,
+        set(0, "/session/plan", json!("a")),
+    )
+    .await;
+    let branch = BranchId::from_string("experiment");
+    runtime
+        .create_branch(&session, branch.clone(), Some(main.clone()), None)
+        .await
+        .expect("create branch");
+    patch(
+        &runtime,
+        &session,
+        &main,
+        set(1, "/session/plan", json!("main")),
+    )
+    .await;
+    patch(
+        &runtime,
+        &session,
+        &branch,
+        set(1, "/session/plan", json!("branch")),
+    )
+    .await;
+
+    let on_main = runtime
+        .canonical_state(&session, &main)
+        .await
+        .expect("main");
+    let on_branch = runtime
+        .canonical_state(&session, &branch)
+        .await
+        .expect("branch");
+    assert_eq!((on_main.version, on_branch.version), (2, 2));
+    assert_eq!(on_main.state.session["plan"], "main");
+    assert_eq!(on_branch.state.session["plan"], "branch");
+}
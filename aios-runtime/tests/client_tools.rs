@@ -241,17 +241,9 @@ impl PolicyGatePort for RecordingPolicy {
     ) -> KernelResult<PolicyGateDecision> {
         self.evaluated.store(true, Ordering::SeqCst);
         if self.require_approval {
-            return Ok(PolicyGateDecision {
-                allowed: Vec::new(),
-                requires_approval: requested,
-                denied: Vec::new(),
-            });
+            return Ok(PolicyGateDecision::new(Vec::new(), requested, Vec::new()));
         }
-        Ok(PolicyGateDecision {
-            allowed: requested,
-            requires_approval: Vec::new(),
-            denied: Vec::new(),
-        })
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
     }
 }
 
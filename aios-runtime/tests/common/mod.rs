@@ -0,0 +1,385 @@
+//! Wiring shared by the runtime integration tests.
+//!
+//! [`Fixture`] builds a [`KernelRuntime`] over an in-memory journal, no-op
+//! approvals and (unless a test brings its own gate) an allow-all policy
+//! gate, and opens one session on it. Test files keep their scripted
+//! providers and harnesses, and add helpers with an `impl` block on their
+//! own `Fixture<Provider, Harness>`.
+
+// Each test file uses a different subset.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelError,
+    KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort,
+    ModelRouting, ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId,
+    ToolCall, ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind, TickOutput};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+pub struct MemEventStore {
+    pub events: Mutex<Vec<EventRecord>>,
+}
+
+impl MemEventStore {
+    /// Every record journaled for `session`, in append order.
+    pub fn session_events(&self, session: &SessionId) -> Vec<EventRecord> {
+        self.events
+            .lock()
+            .iter()
+            .filter(|record| &record.session_id == session)
+            .cloned()
+            .collect()
+    }
+
+    /// Payloads of the custom events of type `event_type`.
+    pub fn custom_events(&self, event_type: &str) -> Vec<serde_json::Value> {
+        self.events
+            .lock()
+            .iter()
+            .filter_map(|record| match &record.kind {
+                EventKind::Custom {
+                    event_type: kind,
+                    data,
+                } if kind == event_type => Some(data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    /// Enforces contiguous sequences per branch, like `EventJournal`.
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        let mut events = self.events.lock();
+        let head = events
+            .iter()
+            .filter(|e| e.session_id == event.session_id && e.branch_id == event.branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0);
+        if event.sequence != head + 1 {
+            return Err(KernelError::SequenceConflict {
+                expected: head + 1,
+                actual: event.sequence,
+            });
+        }
+        events.push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Provider and harness ──────────────────────────────────────────────
+
+/// Answers in text, without tool calls.
+pub struct AnsweringProvider;
+
+#[async_trait]
+impl ModelProviderPort for AnsweringProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "summary written".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("summary written".to_owned()),
+        })
+    }
+}
+
+/// Fails calls whose `path` mentions "missing".
+pub struct PathHarness;
+
+#[async_trait]
+impl ToolHarnessPort for PathHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        let failed = request.call.input["path"]
+            .as_str()
+            .is_some_and(|path| path.contains("missing"));
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: i32::from(failed),
+            duration_ms: 0,
+            outcome: if failed {
+                ToolOutcome::Failure {
+                    error: "not found".to_owned(),
+                }
+            } else {
+                ToolOutcome::Success {
+                    output: serde_json::json!({ "ok": true }),
+                }
+            },
+        })
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+pub struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+pub struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+/// A runtime, its journal and one open session.
+pub struct Fixture<P, H> {
+    pub runtime: KernelRuntime,
+    pub store: Arc<MemEventStore>,
+    pub provider: Arc<P>,
+    pub harness: Arc<H>,
+    pub root: PathBuf,
+    pub session: SessionId,
+}
+
+type StoreLayer = Box<dyn FnOnce(Arc<dyn EventStorePort>) -> Arc<dyn EventStorePort>>;
+
+pub struct FixtureBuilder<P, H> {
+    name: String,
+    config: RuntimeConfig,
+    provider: Arc<P>,
+    harness: Arc<H>,
+    gate: Arc<dyn PolicyGatePort>,
+    routing: ModelRouting,
+    store_layer: Option<StoreLayer>,
+}
+
+impl<P, H> Fixture<P, H>
+where
+    P: ModelProviderPort + 'static,
+    H: ToolHarnessPort + 'static,
+{
+    /// Start wiring a runtime rooted in a fresh temporary directory named
+    /// after `name`.
+    pub fn builder(name: &str, provider: P, harness: H) -> FixtureBuilder<P, H> {
+        let root =
+            std::env::temp_dir().join(format!("aios-runtime-{name}-{}", uuid::Uuid::new_v4()));
+        FixtureBuilder {
+            name: name.to_owned(),
+            config: RuntimeConfig::new(root),
+            provider: Arc::new(provider),
+            harness: Arc::new(harness),
+            gate: Arc::new(AllowAllGate),
+            routing: ModelRouting::default(),
+            store_layer: None,
+        }
+    }
+
+    /// Open another session owned by `owner`.
+    pub async fn open_session(&self, owner: &str, policy: PolicySet) -> SessionId {
+        let session = SessionId::default();
+        self.runtime
+            .create_session_with_id(session.clone(), owner, policy, ModelRouting::default())
+            .await
+            .expect("create session");
+        session
+    }
+
+    /// A direct tick on `session`.
+    pub async fn tick_on(
+        &self,
+        session: &SessionId,
+        objective: &str,
+        proposed_tool: Option<ToolCall>,
+    ) -> TickOutput {
+        self.runtime
+            .tick_on_branch(
+                session,
+                &BranchId::main(),
+                TickInput {
+                    objective: objective.to_owned(),
+                    proposed_tool,
+                    system_prompt: None,
+                    allowed_tools: None,
+                    client_tools: Vec::new(),
+                    kind: TickKind::Direct,
+                },
+            )
+            .await
+            .expect("tick")
+    }
+
+    /// Kinds of every record journaled so far, in append order.
+    pub fn kinds(&self) -> Vec<EventKind> {
+        self.store
+            .events
+            .lock()
+            .iter()
+            .map(|record| record.kind.clone())
+            .collect()
+    }
+}
+
+impl<P, H> FixtureBuilder<P, H>
+where
+    P: ModelProviderPort + 'static,
+    H: ToolHarnessPort + 'static,
+{
+    pub fn config(mut self, edit: impl FnOnce(&mut RuntimeConfig)) -> Self {
+        edit(&mut self.config);
+        self
+    }
+
+    pub fn gate(mut self, gate: impl PolicyGatePort + 'static) -> Self {
+        self.gate = Arc::new(gate);
+        self
+    }
+
+    /// Routing of the fixture's session.
+    pub fn routing(mut self, routing: ModelRouting) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Wrap the in-memory journal before the runtime sees it.
+    pub fn store_layer(
+        mut self,
+        layer: impl FnOnce(Arc<dyn EventStorePort>) -> Arc<dyn EventStorePort> + 'static,
+    ) -> Self {
+        self.store_layer = Some(Box::new(layer));
+        self
+    }
+
+    pub async fn build(self) -> Fixture<P, H> {
+        self.build_with(|runtime| runtime).await
+    }
+
+    /// [`Self::build`], passing the runtime through `wire` before the
+    /// session is opened.
+    pub async fn build_with(
+        self,
+        wire: impl FnOnce(KernelRuntime) -> KernelRuntime,
+    ) -> Fixture<P, H> {
+        let root = self.config.root.clone();
+        let store = Arc::new(MemEventStore::default());
+        let mut event_store = store.clone() as Arc<dyn EventStorePort>;
+        if let Some(layer) = self.store_layer {
+            event_store = layer(event_store);
+        }
+        let runtime = wire(KernelRuntime::new(
+            self.config,
+            event_store,
+            self.provider.clone() as Arc<dyn ModelProviderPort>,
+            self.harness.clone() as Arc<dyn ToolHarnessPort>,
+            Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+            self.gate,
+        ));
+        let session = SessionId::default();
+        runtime
+            .create_session_with_id(
+                session.clone(),
+                format!("{}-test", self.name),
+                PolicySet::default(),
+                self.routing,
+            )
+            .await
+            .expect("create session");
+        Fixture {
+            runtime,
+            store,
+            provider: self.provider,
+            harness: self.harness,
+            root,
+            session,
+        }
+    }
+}
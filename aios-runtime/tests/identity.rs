@@ -0,0 +1,281 @@
+//! Agent persona in the system prompt.
+//!
+//! With an identity port wired, every tick opens its system prompt with
+//! the agent's persona block, read fresh so soul updates apply to the
+//! next turn.
+
+use std::sync::Arc;
+
+use aios_protocol::ports::IdentityPort;
+use aios_protocol::{
+    AgentId, ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, Belief,
+    BeliefFilter, BranchId, Capability, EventRecord, EventRecordStream, EventStorePort,
+    KernelError, KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective,
+    ModelProviderPort, ModelRouting, ModelStopReason, PolicyGateDecision, PolicyGatePort,
+    PolicySet, SessionId, SoulProfile, SoulUpdate, ToolExecutionReport, ToolExecutionRequest,
+    ToolHarnessPort,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Identity, provider and harness ────────────────────────────────────
+
+/// Souls kept in memory; `fail` makes every lookup error.
+#[derive(Default)]
+struct MemIdentity {
+    soul: Mutex<SoulProfile>,
+    fail: bool,
+}
+
+#[async_trait]
+impl IdentityPort for MemIdentity {
+    async fn get_soul(&self, _agent: AgentId) -> KernelResult<SoulProfile> {
+        if self.fail {
+            return Err(KernelError::Runtime("identity store offline".to_owned()));
+        }
+        Ok(self.soul.lock().clone())
+    }
+
+    async fn update_soul(&self, _agent: AgentId, update: SoulUpdate) -> KernelResult<SoulProfile> {
+        let mut soul = self.soul.lock();
+        update.apply_to(&mut soul);
+        Ok(soul.clone())
+    }
+
+    async fn get_beliefs(
+        &self,
+        _agent: AgentId,
+        _filter: BeliefFilter,
+    ) -> KernelResult<Vec<Belief>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Answers in text and records the system prompt of every request.
+#[derive(Default)]
+struct RecordingProvider {
+    prompts: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait]
+impl ModelProviderPort for RecordingProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.prompts.lock().push(request.system_prompt);
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "hello".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("hello".to_owned()),
+        })
+    }
+}
+
+struct UnusedHarness;
+
+#[async_trait]
+impl ToolHarnessPort for UnusedHarness {
+    async fn execute(&self, _request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        Err(KernelError::Runtime("no tools in this test".to_owned()))
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+async fn prompts_for(identity: Arc<MemIdentity>, updates: Vec<SoulUpdate>) -> Vec<Option<String>> {
+    let root = std::env::temp_dir().join(format!("aios-runtime-identity-{}", uuid::Uuid::new_v4()));
+    let provider = Arc::new(RecordingProvider::default());
+    let agent = AgentId::from_string("agent-1");
+    let runtime = KernelRuntime::new(
+        RuntimeConfig::new(root),
+        Arc::new(MemEventStore::default()) as Arc<dyn EventStorePort>,
+        provider.clone() as Arc<dyn ModelProviderPort>,
+        Arc::new(UnusedHarness) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    )
+    .with_identity(identity.clone(), agent.clone());
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "identity-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+
+    let tick = |system_prompt: Option<&str>| TickInput {
+        objective: "say hello".to_owned(),
+        proposed_tool: None,
+        system_prompt: system_prompt.map(str::to_owned),
+        allowed_tools: None,
+        client_tools: Vec::new(),
+        kind: TickKind::Direct,
+    };
+    runtime
+        .tick_on_branch(&session, &BranchId::main(), tick(Some("Be brief.")))
+        .await
+        .expect("first tick");
+    for update in updates {
+        identity.update_soul(agent.clone(), update).await.unwrap();
+    }
+    runtime
+        .tick_on_branch(&session, &BranchId::main(), tick(None))
+        .await
+        .expect("second tick");
+
+    provider.prompts.lock().clone()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn persona_opens_the_system_prompt_and_follows_soul_updates() {
+    let identity = Arc::new(MemIdentity::default());
+    identity
+        .update_soul(
+            AgentId::from_string("agent-1"),
+            SoulUpdate::new().with_display_name("Ada"),
+        )
+        .await
+        .unwrap();
+
+    let prompts = prompts_for(
+        identity,
+        vec![SoulUpdate::new().add_trait("curious").add_trait("precise")],
+    )
+    .await;
+
+    let first = prompts[0].as_deref().expect("first prompt");
+    assert!(first.starts_with("You are Ada"), "{first}");
+    assert!(first.ends_with("\n\nBe brief."), "{first}");
+    assert!(!first.contains("Traits:"));
+
+    let second = prompts[1].as_deref().expect("second prompt");
+    assert!(second.starts_with("You are Ada"), "{second}");
+    assert!(second.contains("Traits: curious, precise"), "{second}");
+}
+
+#[tokio::test]
+async fn identity_failures_leave_the_system_prompt_unchanged() {
+    let identity = Arc::new(MemIdentity {
+        fail: true,
+        ..MemIdentity::default()
+    });
+
+    let prompts = prompts_for(identity, Vec::new()).await;
+
+    assert_eq!(prompts, [Some("Be brief.".to_owned()), None]);
+}
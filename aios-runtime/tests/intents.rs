@@ -0,0 +1,273 @@
+//! Intent gate between deliberation and execution.
+//!
+//! Completions planning side-effecting calls are proposed as intents,
+//! evaluated against policy and the configured evaluator, and executed,
+//! held for approval above the risk threshold, or rejected with feedback
+//! to the model.
+
+use std::sync::Arc;
+
+use aios_protocol::{
+    Capability, EventKind, KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective,
+    ModelProviderPort, ModelStopReason, OperatingMode, RiskLevel, SessionId, ToolCall,
+    ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{Intent, IntentEvaluator, IntentGate, IntentVerdict, TickOutput};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+mod common;
+
+// ── Provider, harness and evaluator ───────────────────────────────────
+
+/// Answers in text and records the history it was sent.
+#[derive(Default)]
+struct RecordingProvider {
+    histories: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ModelProviderPort for RecordingProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.histories.lock().push(
+            request
+                .conversation_history
+                .iter()
+                .map(|turn| turn.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "noted".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("noted".to_owned()),
+        })
+    }
+}
+
+/// Succeeds and records which tools ran.
+#[derive(Default)]
+struct RecordingHarness {
+    executed: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ToolHarnessPort for RecordingHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        self.executed.lock().push(request.call.tool_name.clone());
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: serde_json::json!({ "ok": true }),
+            },
+        })
+    }
+}
+
+/// Rejects intents that would touch `secrets/`.
+struct ScopeEvaluator;
+
+#[async_trait]
+impl IntentEvaluator for ScopeEvaluator {
+    async fn evaluate(
+        &self,
+        _session_id: &SessionId,
+        intent: &Intent,
+    ) -> anyhow::Result<IntentVerdict> {
+        let touches_secrets = intent
+            .planned_calls
+            .iter()
+            .any(|call| call.input.to_string().contains("secrets/"));
+        Ok(if touches_secrets {
+            IntentVerdict::reject("secrets/ is out of scope for this task")
+        } else {
+            IntentVerdict::allow()
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+type Fixture = common::Fixture<RecordingProvider, RecordingHarness>;
+
+impl Fixture {
+    async fn new() -> Self {
+        Fixture::builder(
+            "intents",
+            RecordingProvider::default(),
+            RecordingHarness::default(),
+        )
+        .build_with(|runtime| {
+            runtime.with_intent_gate(
+                IntentGate::new()
+                    .with_approval_threshold(RiskLevel::High)
+                    .with_evaluator(Arc::new(ScopeEvaluator)),
+            )
+        })
+        .await
+    }
+
+    async fn tick(&self, proposed_tool: Option<ToolCall>) -> TickOutput {
+        self.tick_on(&self.session, "tidy the workspace", proposed_tool)
+            .await
+    }
+
+    fn intent_lifecycle(&self) -> Vec<String> {
+        self.kinds()
+            .into_iter()
+            .filter_map(|kind| match kind {
+                EventKind::IntentProposed { risk, .. } => Some(format!("proposed:{risk:?}")),
+                EventKind::IntentEvaluated {
+                    allowed,
+                    requires_approval,
+                    ..
+                } => Some(format!("evaluated:{allowed}:{requires_approval}")),
+                EventKind::IntentApproved { actor, .. } => Some(format!("approved:{actor:?}")),
+                EventKind::IntentRejected { .. } => Some("rejected".to_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn write(path: &str) -> ToolCall {
+    ToolCall::new(
+        "fs.write",
+        serde_json::json!({ "path": path, "content": "x" }),
+        vec![Capability::fs_write("/session/**")],
+    )
+}
+
+fn exec(command: &str) -> ToolCall {
+    ToolCall::new(
+        "shell.exec",
+        serde_json::json!({ "command": command }),
+        vec![Capability::exec(command)],
+    )
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn low_risk_intents_are_approved_and_executed() {
+    let fixture = Fixture::new().await;
+
+    fixture.tick(Some(write("notes.md"))).await;
+
+    assert_eq!(
+        fixture.intent_lifecycle(),
+        [
+            "proposed:Some(Medium)",
+            "evaluated:true:false",
+            "approved:None"
+        ]
+    );
+    assert_eq!(*fixture.harness.executed.lock(), ["fs.write"]);
+    match &fixture.kinds()[..]
+        .iter()
+        .find(|kind| matches!(kind, EventKind::IntentProposed { .. }))
+    {
+        Some(EventKind::IntentProposed {
+            summary,
+            planned_calls,
+            ..
+        }) => {
+            assert_eq!(summary, "tidy the workspace");
+            assert_eq!(planned_calls[0].tool_name, "fs.write");
+        }
+        _ => panic!("no intent proposed"),
+    }
+}
+
+#[tokio::test]
+async fn rejected_intents_do_not_run_and_are_fed_back_to_the_model() {
+    let fixture = Fixture::new().await;
+
+    fixture.tick(Some(write("secrets/token.txt"))).await;
+    assert_eq!(
+        fixture.intent_lifecycle(),
+        ["proposed:Some(Medium)", "evaluated:false:false", "rejected"]
+    );
+    assert!(fixture.harness.executed.lock().is_empty());
+    assert!(
+        !fixture
+            .kinds()
+            .iter()
+            .any(|kind| matches!(kind, EventKind::ToolCallRequested { .. }))
+    );
+
+    fixture.tick(None).await;
+    let history = fixture.provider.histories.lock()[0].clone();
+    assert!(
+        history.contains("[intent rejected; its tool calls did not run: secrets/ is out of scope"),
+        "{history}"
+    );
+}
+
+#[tokio::test]
+async fn high_risk_intents_wait_for_approval_and_run_when_reissued() {
+    let fixture = Fixture::new().await;
+
+    let output = fixture.tick(Some(exec("cargo clean"))).await;
+    assert_eq!(output.mode, OperatingMode::AskHuman);
+    assert_eq!(
+        fixture.intent_lifecycle(),
+        ["proposed:Some(High)", "evaluated:true:true"]
+    );
+    assert!(fixture.harness.executed.lock().is_empty());
+    let approval_id = fixture
+        .kinds()
+        .into_iter()
+        .find_map(|kind| match kind {
+            EventKind::ApprovalRequested {
+                approval_id,
+                tool_name,
+                risk,
+                ..
+            } => {
+                assert_eq!(tool_name, "intent:tool_batch");
+                assert_eq!(risk, RiskLevel::High);
+                Some(approval_id)
+            }
+            _ => None,
+        })
+        .expect("approval requested");
+
+    fixture
+        .runtime
+        .resolve_approval(
+            &fixture.session,
+            approval_id.as_str().parse().unwrap(),
+            true,
+            "operator",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.intent_lifecycle().last().unwrap(),
+        "approved:Some(\"operator\")"
+    );
+
+    fixture.tick(Some(exec("cargo clean"))).await;
+    assert_eq!(*fixture.harness.executed.lock(), ["shell.exec"]);
+    assert_eq!(
+        fixture.intent_lifecycle().len(),
+        3,
+        "the approved intent is not proposed again"
+    );
+
+    // The approval covered one execution; the same calls are gated anew.
+    fixture.tick(Some(exec("cargo clean"))).await;
+    assert_eq!(fixture.harness.executed.lock().len(), 1);
+}
@@ -0,0 +1,393 @@
+//! Cycle-aware loop detection.
+//!
+//! `LoopDetectionMiddleware` compares calls by a canonical signature that
+//! ignores formatting, case and path spelling, and flags identical repeats,
+//! periodic cycles (A-B-A-B…) and ticks that keep calling tools while
+//! `progress` stays flat. Its history is kept per branch and rebuilt from
+//! the journal, so a restarted runtime keeps counting.
+
+use std::sync::Arc;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelResult,
+    ModelCompletion, ModelCompletionRequest, ModelProviderPort, ModelRouting, PolicyGateDecision,
+    PolicyGatePort, PolicySet, SessionId, ToolCall, ToolExecutionReport, ToolExecutionRequest,
+    ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{
+    KernelRuntime, LoopDetectionConfig, LoopDetectionMiddleware, RuntimeConfig, TickInput, TickKind,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Provider and harness ──────────────────────────────────────────────
+//
+// Every tick proposes its tool inline, so the provider is never asked.
+// The harness fails calls whose path contains "missing".
+
+struct UnusedProvider;
+
+#[async_trait]
+impl ModelProviderPort for UnusedProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        panic!("ticks propose their tool inline");
+    }
+}
+
+struct PathHarness;
+
+#[async_trait]
+impl ToolHarnessPort for PathHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        let failed = request.call.input["path"]
+            .as_str()
+            .is_some_and(|path| path.contains("missing"));
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: i32::from(failed),
+            duration_ms: 0,
+            outcome: if failed {
+                ToolOutcome::Failure {
+                    error: "not found".to_owned(),
+                }
+            } else {
+                ToolOutcome::Success {
+                    output: serde_json::json!({ "ok": true }),
+                }
+            },
+        })
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+fn runtime(store: &Arc<MemEventStore>) -> KernelRuntime {
+    runtime_with(store, LoopDetectionConfig::default())
+}
+
+fn runtime_with(store: &Arc<MemEventStore>, config: LoopDetectionConfig) -> KernelRuntime {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-loop-detection-{}",
+        uuid::Uuid::new_v4()
+    ));
+    KernelRuntime::with_turn_middlewares(
+        RuntimeConfig::new(root),
+        store.clone() as Arc<dyn EventStorePort>,
+        Arc::new(UnusedProvider) as Arc<dyn ModelProviderPort>,
+        Arc::new(PathHarness) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+        vec![Arc::new(LoopDetectionMiddleware::new(config))],
+    )
+}
+
+async fn open_session(runtime: &KernelRuntime, session: &SessionId) {
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "loop-detection-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+}
+
+fn call(tool_name: &str, path: &str) -> ToolCall {
+    ToolCall::new(
+        tool_name,
+        serde_json::json!({ "path": path }),
+        vec![Capability::fs_read("/session/**")],
+    )
+}
+
+async fn tick(runtime: &KernelRuntime, session: &SessionId, call: ToolCall) {
+    tick_on(runtime, session, &BranchId::main(), call).await;
+}
+
+async fn tick_on(runtime: &KernelRuntime, session: &SessionId, branch: &BranchId, call: ToolCall) {
+    runtime
+        .tick_on_branch(
+            session,
+            branch,
+            TickInput {
+                objective: "work".to_owned(),
+                proposed_tool: Some(call),
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+}
+
+/// `(event_type, message)` of every loop-detection event.
+fn loop_events(store: &MemEventStore) -> Vec<(String, String)> {
+    store
+        .events
+        .lock()
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::Custom { event_type, data } if event_type.starts_with("loop_detection.") => {
+                Some((
+                    event_type.clone(),
+                    data["message"].as_str().unwrap_or_default().to_owned(),
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn alternating_calls_are_detected_as_a_cycle() {
+    let store = Arc::new(MemEventStore::default());
+    // Without a plan, successful calls do not move `progress`; keep the
+    // no-progress check out of the way of the cycle under test.
+    let runtime = runtime_with(
+        &store,
+        LoopDetectionConfig {
+            no_progress_ticks: 0,
+            ..LoopDetectionConfig::default()
+        },
+    );
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+
+    for _ in 0..3 {
+        tick(&runtime, &session, call("fs.read", "notes/a.md")).await;
+        tick(&runtime, &session, call("fs.write", "notes/a.md")).await;
+    }
+
+    let events = loop_events(&store);
+    assert_eq!(events.len(), 1, "third A-B cycle warns: {events:?}");
+    assert_eq!(events[0].0, "loop_detection.warning");
+    assert!(events[0].1.contains("cycled 3 times"));
+}
+
+#[tokio::test]
+async fn calls_differing_only_in_whitespace_count_as_repeats() {
+    let store = Arc::new(MemEventStore::default());
+    let runtime = runtime(&store);
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+
+    for path in ["notes/a.md", " notes/a.md", "notes/a.md  "] {
+        tick(&runtime, &session, call("fs.read", path)).await;
+    }
+
+    let events = loop_events(&store);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].1.contains("repeated 3 times"));
+}
+
+#[tokio::test]
+async fn calls_differing_in_path_spelling_count_as_repeats() {
+    let store = Arc::new(MemEventStore::default());
+    let runtime = runtime(&store);
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+
+    for path in ["notes/a.md", "./notes//a.md", "notes/drafts/../a.md/"] {
+        tick(&runtime, &session, call("fs.read", path)).await;
+    }
+
+    let events = loop_events(&store);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].1.contains("repeated 3 times"));
+}
+
+#[tokio::test]
+async fn calls_naming_different_files_are_not_repeats() {
+    let store = Arc::new(MemEventStore::default());
+    let runtime = runtime(&store);
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+
+    // Case matters to the filesystem.
+    for path in ["notes/a.md", "notes/A.md", "Notes/a.md"] {
+        tick(&runtime, &session, call("fs.read", path)).await;
+    }
+
+    assert!(loop_events(&store).is_empty());
+}
+
+#[tokio::test]
+async fn branches_keep_separate_histories() {
+    let store = Arc::new(MemEventStore::default());
+    let runtime = runtime(&store);
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+    let feature = BranchId::from_string("feature");
+    runtime
+        .create_branch(&session, feature.clone(), None, None)
+        .await
+        .expect("create branch");
+
+    for branch in [BranchId::main(), feature.clone(), BranchId::main(), feature] {
+        tick_on(&runtime, &session, &branch, call("fs.read", "notes/a.md")).await;
+    }
+
+    assert!(
+        loop_events(&store).is_empty(),
+        "two calls per branch stay under the warning threshold"
+    );
+}
+
+#[tokio::test]
+async fn failing_calls_without_progress_raise_a_warning() {
+    let store = Arc::new(MemEventStore::default());
+    let runtime = runtime(&store);
+    let session = SessionId::default();
+    open_session(&runtime, &session).await;
+
+    for index in 0..4 {
+        tick(
+            &runtime,
+            &session,
+            call("fs.read", &format!("missing/{index}.md")),
+        )
+        .await;
+    }
+
+    let events = loop_events(&store);
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert!(
+        events[0]
+            .1
+            .contains("4 turns of tool calls without progress")
+    );
+}
+
+#[tokio::test]
+async fn history_is_rebuilt_from_the_journal_after_a_restart() {
+    let store = Arc::new(MemEventStore::default());
+    let session = SessionId::default();
+
+    let before_restart = runtime(&store);
+    open_session(&before_restart, &session).await;
+    for _ in 0..2 {
+        tick(&before_restart, &session, call("fs.read", "notes/a.md")).await;
+    }
+    drop(before_restart);
+    assert!(loop_events(&store).is_empty());
+
+    let after_restart = runtime(&store);
+    open_session(&after_restart, &session).await;
+    tick(&after_restart, &session, call("fs.read", "notes/a.md")).await;
+
+    let events = loop_events(&store);
+    assert_eq!(events.len(), 1, "journaled calls count toward the limit");
+    assert!(events[0].1.contains("repeated 3 times"));
+}
@@ -0,0 +1,355 @@
+//! Memory pipeline: observations with event-range provenance, periodic
+//! reflection, `memory.propose` commits gated by policy and evaluation,
+//! and recall into the system prompt of later ticks.
+
+use aios_protocol::{
+    BranchId, Capability, EventKind, KernelResult, MemoryId, MemoryScope, ModelCompletion,
+    ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelStopReason, PolicyGateDecision,
+    PolicyGatePort, PolicySet, SessionId, ToolCall, ToolExecutionReport, ToolExecutionRequest,
+    ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{MEMORY_PROPOSE_TOOL, MEMORY_RECALLED_EVENT};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::json;
+
+mod common;
+
+// ── Provider and harness ──────────────────────────────────────────────
+
+/// Answers in text and keeps the system prompt of every request.
+#[derive(Default)]
+struct PromptRecordingProvider {
+    system_prompts: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait]
+impl ModelProviderPort for PromptRecordingProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.system_prompts.lock().push(request.system_prompt);
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "done".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("done".to_owned()),
+        })
+    }
+}
+
+struct OkHarness;
+
+#[async_trait]
+impl ToolHarnessPort for OkHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: json!({ "listing": ["Cargo.toml", "src"] }),
+            },
+        })
+    }
+}
+
+// ── Policy gate ───────────────────────────────────────────────────────
+
+/// Allows everything except `memory:write:*`, which needs approval.
+struct MemoryWriteGate;
+
+#[async_trait]
+impl PolicyGatePort for MemoryWriteGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        let (requires_approval, allowed) = requested
+            .into_iter()
+            .partition(|capability| capability.as_str().starts_with("memory:write:"));
+        Ok(PolicyGateDecision::new(
+            allowed,
+            requires_approval,
+            Vec::new(),
+        ))
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+type Fixture = common::Fixture<PromptRecordingProvider, OkHarness>;
+
+impl Fixture {
+    async fn new(reflect_every: usize) -> Self {
+        Fixture::builder("memory", PromptRecordingProvider::default(), OkHarness)
+            .config(|config| config.memory_reflect_every = reflect_every)
+            .gate(MemoryWriteGate)
+            .build()
+            .await
+    }
+
+    async fn session(&self, owner: &str) -> SessionId {
+        self.open_session(owner, PolicySet::default()).await
+    }
+
+    /// The output (or error) `memory.propose` returned for `call_id`.
+    fn tool_result(&self, session: &SessionId, call_id: &str) -> serde_json::Value {
+        self.store
+            .session_events(session)
+            .into_iter()
+            .find_map(|record| match record.kind {
+                EventKind::ToolCallCompleted {
+                    call_id: Some(id),
+                    result,
+                    ..
+                } if id == call_id => Some(result),
+                _ => None,
+            })
+            .expect("tool call completed")
+    }
+}
+
+fn propose(input: serde_json::Value) -> ToolCall {
+    ToolCall::new(MEMORY_PROPOSE_TOOL, input, Vec::new())
+}
+
+fn list_files() -> ToolCall {
+    ToolCall::new("fs.list", json!({ "path": "." }), Vec::new())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn tool_results_become_observations_that_reflect_into_summaries() {
+    let fixture = Fixture::new(2).await;
+    let session = fixture.session("memory-test").await;
+
+    fixture
+        .tick_on(&session, "look around", Some(list_files()))
+        .await;
+    let events = fixture.store.session_events(&session);
+    let started = events
+        .iter()
+        .find(|record| matches!(record.kind, EventKind::ToolCallStarted { .. }))
+        .expect("tool started")
+        .sequence;
+    let completed = events
+        .iter()
+        .find(|record| matches!(record.kind, EventKind::ToolCallCompleted { .. }))
+        .expect("tool completed")
+        .sequence;
+    assert!(events.iter().any(|record| matches!(
+        &record.kind,
+        EventKind::ObservationAppended {
+            scope: MemoryScope::Session,
+            source_run_id: Some(_),
+            ..
+        }
+    )));
+    assert!(
+        !events
+            .iter()
+            .any(|record| matches!(record.kind, EventKind::ReflectionCompacted { .. })),
+        "one observation is below the reflection threshold"
+    );
+
+    fixture
+        .tick_on(&session, "look around again", Some(list_files()))
+        .await;
+    let reflections = fixture
+        .runtime
+        .memory_reflections(&session, MemoryScope::Session)
+        .await
+        .expect("reflections");
+    assert_eq!(reflections.len(), 1);
+    let reflection = &reflections[0];
+    assert_eq!(reflection.observations_covered, 2);
+    assert_eq!(reflection.provenance.event_start, started);
+    assert!(reflection.provenance.event_end > completed);
+    assert!(reflection.summary.starts_with("2 observations"));
+    assert!(reflection.summary.contains("fs.list"));
+    assert!(fixture.store.session_events(&session).iter().any(|record| matches!(
+        &record.kind,
+        EventKind::ReflectionCompacted { scope: MemoryScope::Session, covers_through_seq, .. }
+            if *covers_through_seq == reflection.provenance.event_end
+    )));
+}
+
+#[tokio::test]
+async fn proposed_session_memories_commit_and_are_recalled_on_later_ticks() {
+    let fixture = Fixture::new(0).await;
+    let session = fixture.session("memory-test").await;
+
+    let call = propose(json!({
+        "entries": [
+            { "text": "The release branch is cut every Thursday", "tags": ["release"] },
+            { "text": "   " },
+        ],
+    }));
+    let call_id = call.call_id.clone();
+    fixture
+        .tick_on(&session, "note the release cadence", Some(call))
+        .await;
+    let result = fixture.tool_result(&session, &call_id);
+    assert_eq!(result["output"]["status"], "committed");
+    assert_eq!(
+        result["output"]["rejected"][0]["reason"],
+        "memory text is empty"
+    );
+    let memories = fixture
+        .runtime
+        .memories(&session, MemoryScope::Session)
+        .await
+        .expect("memories");
+    assert_eq!(memories.len(), 1);
+    let events = fixture.store.session_events(&session);
+    assert!(events.iter().any(|record| matches!(
+        &record.kind,
+        EventKind::MemoryProposed {
+            scope: MemoryScope::Session,
+            ..
+        }
+    )));
+    assert!(events.iter().any(|record| matches!(
+        &record.kind,
+        EventKind::MemoryCommitted { memory_id, .. } if *memory_id == memories[0].memory_id
+    )));
+
+    // A repeat of a live memory is rejected by the evaluator.
+    let repeat = propose(json!({
+        "entries": [{ "text": "the release branch is cut   every thursday" }],
+    }));
+    let repeat_id = repeat.call_id.clone();
+    fixture
+        .tick_on(&session, "note it again", Some(repeat))
+        .await;
+    assert!(
+        fixture.tool_result(&session, &repeat_id)["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("duplicates memory"))
+    );
+
+    // An unrelated objective recalls nothing; a related one gets the memory.
+    fixture.tick_on(&session, "say hello", None).await;
+    fixture
+        .tick_on(&session, "When is the next release branch cut?", None)
+        .await;
+    let prompts = fixture.provider.system_prompts.lock().clone();
+    assert_eq!(prompts.len(), 2);
+    assert_eq!(prompts[0], None);
+    let recalled = prompts[1].as_deref().expect("recalled memories");
+    assert!(recalled.contains("[session] The release branch is cut every Thursday"));
+    assert!(
+        fixture
+            .store
+            .session_events(&session)
+            .iter()
+            .any(|record| matches!(
+                &record.kind,
+                EventKind::Custom { event_type, .. } if event_type == MEMORY_RECALLED_EVENT
+            ))
+    );
+
+    // A tombstoned memory is no longer recalled.
+    fixture
+        .runtime
+        .forget_memory(
+            &session,
+            &BranchId::main(),
+            MemoryScope::Session,
+            &memories[0].memory_id,
+            "cadence changed",
+        )
+        .await
+        .expect("forget");
+    fixture
+        .tick_on(&session, "When is the next release branch cut?", None)
+        .await;
+    assert_eq!(fixture.provider.system_prompts.lock().last(), Some(&None));
+}
+
+#[tokio::test]
+async fn user_memories_wait_for_policy_and_are_shared_across_the_owners_sessions() {
+    let fixture = Fixture::new(0).await;
+    let session = fixture.session("ada").await;
+
+    let call = propose(json!({
+        "scope": "user",
+        "entries": [{ "text": "Prefers answers with code samples in Rust" }],
+    }));
+    let call_id = call.call_id.clone();
+    fixture
+        .tick_on(&session, "remember my preference", Some(call))
+        .await;
+    let result = fixture.tool_result(&session, &call_id);
+    assert_eq!(result["output"]["status"], "pending");
+    let proposal_id = MemoryId::from_string(
+        result["output"]["proposal_id"]
+            .as_str()
+            .expect("proposal id"),
+    );
+    assert!(
+        fixture
+            .runtime
+            .memories(&session, MemoryScope::User)
+            .await
+            .expect("memories")
+            .is_empty()
+    );
+
+    let committed = fixture
+        .runtime
+        .commit_memory_proposal(&session, &BranchId::main(), MemoryScope::User, &proposal_id)
+        .await
+        .expect("commit");
+    assert_eq!(committed.len(), 1);
+    assert!(
+        fixture
+            .runtime
+            .commit_memory_proposal(&session, &BranchId::main(), MemoryScope::User, &proposal_id)
+            .await
+            .is_err(),
+        "a proposal commits once"
+    );
+    assert!(fixture.root.join("memory/user/ada/memories.json").exists());
+
+    // Another session of the same owner sees it; other owners do not.
+    let sibling = fixture.session("ada").await;
+    let stranger = fixture.session("grace").await;
+    assert_eq!(
+        fixture
+            .runtime
+            .memories(&sibling, MemoryScope::User)
+            .await
+            .expect("memories")[0]
+            .memory_id,
+        committed[0]
+    );
+    assert!(
+        fixture
+            .runtime
+            .memories(&stranger, MemoryScope::User)
+            .await
+            .expect("memories")
+            .is_empty()
+    );
+    fixture
+        .tick_on(&sibling, "write a Rust snippet for parsing", None)
+        .await;
+    let prompt = fixture
+        .provider
+        .system_prompts
+        .lock()
+        .last()
+        .cloned()
+        .flatten();
+    assert!(prompt.is_some_and(|prompt| prompt.contains("[user] Prefers answers")));
+}
@@ -0,0 +1,327 @@
+//! Model routing: retries with backoff and fallback models.
+//!
+//! The runtime routes each completion through the session's
+//! `ModelRouting`: the routed model and temperature travel in the
+//! `ModelCompletionRequest`, transient failures are retried on the same
+//! model, permanent failures (or exhausted retries) fall through to the
+//! next fallback, and every attempt is journaled as `model.attempt`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelError,
+    KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort,
+    ModelRouting, ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId,
+    ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Scripted provider ─────────────────────────────────────────────────
+//
+// Each model has a queue of scripted failures; once a model's queue is
+// drained it answers with its own name.
+
+#[derive(Default)]
+struct FlakyProvider {
+    failures: Mutex<HashMap<String, Vec<KernelError>>>,
+    seen: Mutex<Vec<(Option<String>, Option<f32>)>>,
+}
+
+impl FlakyProvider {
+    fn failing(model: &str, errors: Vec<KernelError>) -> Self {
+        let provider = Self::default();
+        provider.failures.lock().insert(model.to_owned(), errors);
+        provider
+    }
+}
+
+#[async_trait]
+impl ModelProviderPort for FlakyProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.seen
+            .lock()
+            .push((request.model.clone(), request.temperature));
+        let model = request.model.unwrap_or_default();
+        if let Some(errors) = self.failures.lock().get_mut(&model)
+            && !errors.is_empty()
+        {
+            return Err(errors.remove(0));
+        }
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: model.clone(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: format!("answered by {model}"),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: None,
+        })
+    }
+}
+
+struct NoTools;
+
+#[async_trait]
+impl ToolHarnessPort for NoTools {
+    async fn execute(&self, _request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        panic!("no tool calls expected");
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+fn routing() -> ModelRouting {
+    ModelRouting {
+        primary_model: "primary".to_owned(),
+        fallback_models: vec!["fallback".to_owned()],
+        temperature: 0.7,
+    }
+}
+
+async fn run_tick(provider: Arc<FlakyProvider>) -> Arc<MemEventStore> {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-model-routing-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let mut config = RuntimeConfig::new(root);
+    config.model_max_retries = 2;
+    config.model_retry_backoff_ms = 0;
+    let store = Arc::new(MemEventStore::default());
+    let runtime = KernelRuntime::new(
+        config,
+        store.clone() as Arc<dyn EventStorePort>,
+        provider as Arc<dyn ModelProviderPort>,
+        Arc::new(NoTools) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    );
+
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "model-routing-test",
+            PolicySet::default(),
+            routing(),
+        )
+        .await
+        .expect("create session");
+    runtime
+        .tick_on_branch(
+            &session,
+            &BranchId::main(),
+            TickInput {
+                objective: "answer".to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+    store
+}
+
+/// `(model, ok)` for every journaled `model.attempt`.
+fn attempts(store: &MemEventStore) -> Vec<(String, bool)> {
+    store
+        .events
+        .lock()
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::Custom { event_type, data } if event_type == "model.attempt" => Some((
+                data["model"].as_str().unwrap_or_default().to_owned(),
+                data["ok"] == Value::Bool(true),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn run_errored(store: &MemEventStore) -> bool {
+    store
+        .events
+        .lock()
+        .iter()
+        .any(|record| matches!(record.kind, EventKind::RunErrored { .. }))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn transient_failures_are_retried_on_the_routed_model() {
+    let provider = Arc::new(FlakyProvider::failing(
+        "primary",
+        vec![
+            KernelError::Transient("429 rate limited".to_owned()),
+            KernelError::Io("connection reset".to_owned()),
+        ],
+    ));
+    let store = run_tick(provider.clone()).await;
+
+    assert_eq!(
+        attempts(&store),
+        vec![
+            ("primary".to_owned(), false),
+            ("primary".to_owned(), false),
+            ("primary".to_owned(), true),
+        ]
+    );
+    assert!(!run_errored(&store));
+    assert!(
+        provider
+            .seen
+            .lock()
+            .iter()
+            .all(|(_, temperature)| *temperature == Some(0.7)),
+        "the routed temperature must reach the provider"
+    );
+}
+
+#[tokio::test]
+async fn permanent_failure_falls_through_to_the_next_model() {
+    let provider = Arc::new(FlakyProvider::failing(
+        "primary",
+        vec![KernelError::InvalidState("unknown model".to_owned())],
+    ));
+    let store = run_tick(provider).await;
+
+    assert_eq!(
+        attempts(&store),
+        vec![("primary".to_owned(), false), ("fallback".to_owned(), true)]
+    );
+    assert!(!run_errored(&store));
+}
+
+#[tokio::test]
+async fn run_errors_only_after_every_model_is_exhausted() {
+    let provider = Arc::new(FlakyProvider::default());
+    for model in ["primary", "fallback"] {
+        provider.failures.lock().insert(
+            model.to_owned(),
+            (0..3)
+                .map(|_| KernelError::Transient("503 overloaded".to_owned()))
+                .collect(),
+        );
+    }
+    let store = run_tick(provider).await;
+
+    let recorded = attempts(&store);
+    assert_eq!(recorded.len(), 6, "three attempts per model");
+    assert!(recorded.iter().all(|(_, ok)| !ok));
+    assert!(run_errored(&store));
+}
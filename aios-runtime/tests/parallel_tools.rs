@@ -0,0 +1,403 @@
+//! Parallel dispatch of independent tool calls within one completion.
+//!
+//! When a completion carries several `ModelDirective::ToolCall` entries,
+//! calls to tools annotated read-only or idempotent (per
+//! `ToolAnnotations`) run concurrently, bounded by the per-session
+//! `RuntimeConfig::max_parallel_tool_calls`. Side-effecting calls — and
+//! tools with no annotations — keep executing one at a time.
+//!
+//! Contract under test:
+//! 1. Read-only calls overlap, never exceeding the concurrency limit.
+//! 2. The journal stays deterministic: `ToolCallRequested` events appear in
+//!    directive order before the batch's results, and `ToolCallCompleted`
+//!    events carry their `call_id` in directive order.
+//! 3. Destructive / unannotated calls never overlap and keep the
+//!    Requested → Completed interleaving per call.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelResult,
+    ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelRouting,
+    ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId, ToolAnnotations,
+    ToolCall, ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Scripted provider ─────────────────────────────────────────────────
+//
+// The first completion proposes every scripted call at once; later
+// completions answer plainly.
+
+struct BatchProvider {
+    calls: Vec<ToolCall>,
+    answered: AtomicBool,
+}
+
+#[async_trait]
+impl ModelProviderPort for BatchProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        if !self.answered.swap(true, Ordering::SeqCst) {
+            return Ok(ModelCompletion {
+                provider: "scripted".to_owned(),
+                model: "scripted-deterministic".to_owned(),
+                llm_call_record: None,
+                directives: self
+                    .calls
+                    .iter()
+                    .cloned()
+                    .map(|call| ModelDirective::ToolCall { call })
+                    .collect(),
+                stop_reason: ModelStopReason::ToolCall,
+                usage: None,
+                final_answer: None,
+            });
+        }
+
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "done".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("done".to_owned()),
+        })
+    }
+}
+
+// ── Harness that measures overlap ─────────────────────────────────────
+//
+// Each execution yields a few times while "in flight", so concurrently
+// spawned executions interleave even on a current-thread runtime.
+
+#[derive(Default)]
+struct OverlapHarness {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+#[async_trait]
+impl ToolHarnessPort for OverlapHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        for _ in 0..4 {
+            tokio::task::yield_now().await;
+        }
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: serde_json::json!({ "ok": true }),
+            },
+        })
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+fn call(index: usize, tool_name: &str) -> ToolCall {
+    ToolCall {
+        call_id: format!("call-{index}"),
+        tool_name: tool_name.to_owned(),
+        input: serde_json::json!({ "path": format!("notes/{index}.md") }),
+        requested_capabilities: vec![Capability::fs_read("/session/**")],
+    }
+}
+
+struct Fixture {
+    runtime: KernelRuntime,
+    store: Arc<MemEventStore>,
+    harness: Arc<OverlapHarness>,
+}
+
+fn build_runtime(calls: Vec<ToolCall>, max_parallel_tool_calls: usize) -> Fixture {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-parallel-tools-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let mut config = RuntimeConfig::new(root);
+    config.max_parallel_tool_calls = max_parallel_tool_calls;
+    let store = Arc::new(MemEventStore::default());
+    let harness = Arc::new(OverlapHarness::default());
+
+    let runtime = KernelRuntime::new(
+        config,
+        store.clone() as Arc<dyn EventStorePort>,
+        Arc::new(BatchProvider {
+            calls,
+            answered: AtomicBool::new(false),
+        }) as Arc<dyn ModelProviderPort>,
+        harness.clone() as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    )
+    .with_tool_annotations(vec![
+        (
+            "read_file",
+            ToolAnnotations {
+                read_only: true,
+                idempotent: true,
+                ..ToolAnnotations::default()
+            },
+        ),
+        (
+            "write_file",
+            ToolAnnotations {
+                destructive: true,
+                ..ToolAnnotations::default()
+            },
+        ),
+    ]);
+
+    Fixture {
+        runtime,
+        store,
+        harness,
+    }
+}
+
+async fn run_one_tick(fixture: &Fixture) {
+    let session = SessionId::default();
+    fixture
+        .runtime
+        .create_session_with_id(
+            session.clone(),
+            "parallel-tools-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    fixture
+        .runtime
+        .tick_on_branch(
+            &session,
+            &BranchId::main(),
+            TickInput {
+                objective: "read several files".to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+}
+
+/// Tool lifecycle events of the journal as `("requested" | "completed", call_id)`.
+fn tool_timeline(store: &MemEventStore) -> Vec<(&'static str, String)> {
+    store
+        .events
+        .lock()
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::ToolCallRequested { call_id, .. } => Some(("requested", call_id.clone())),
+            EventKind::ToolCallCompleted { call_id, .. } => {
+                Some(("completed", call_id.clone().unwrap_or_default()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn read_only_calls_run_concurrently_within_the_session_limit() {
+    let fixture = build_runtime(
+        vec![
+            call(0, "read_file"),
+            call(1, "read_file"),
+            call(2, "read_file"),
+        ],
+        2,
+    );
+    run_one_tick(&fixture).await;
+
+    assert_eq!(
+        fixture.harness.max_in_flight.load(Ordering::SeqCst),
+        2,
+        "three read-only calls with a limit of two must overlap exactly two-wide"
+    );
+
+    let expected: Vec<(&str, String)> = vec![
+        ("requested", "call-0".to_owned()),
+        ("requested", "call-1".to_owned()),
+        ("requested", "call-2".to_owned()),
+        ("completed", "call-0".to_owned()),
+        ("completed", "call-1".to_owned()),
+        ("completed", "call-2".to_owned()),
+    ];
+    assert_eq!(tool_timeline(&fixture.store), expected);
+}
+
+#[tokio::test]
+async fn side_effecting_calls_stay_serialized() {
+    let fixture = build_runtime(
+        vec![
+            call(0, "write_file"),
+            call(1, "unannotated_tool"),
+            call(2, "write_file"),
+        ],
+        4,
+    );
+    run_one_tick(&fixture).await;
+
+    assert_eq!(fixture.harness.max_in_flight.load(Ordering::SeqCst), 1);
+
+    let expected: Vec<(&str, String)> = vec![
+        ("requested", "call-0".to_owned()),
+        ("completed", "call-0".to_owned()),
+        ("requested", "call-1".to_owned()),
+        ("completed", "call-1".to_owned()),
+        ("requested", "call-2".to_owned()),
+        ("completed", "call-2".to_owned()),
+    ];
+    assert_eq!(tool_timeline(&fixture.store), expected);
+}
+
+#[tokio::test]
+async fn side_effecting_call_flushes_the_pending_read_batch() {
+    let fixture = build_runtime(
+        vec![
+            call(0, "read_file"),
+            call(1, "read_file"),
+            call(2, "write_file"),
+            call(3, "read_file"),
+        ],
+        4,
+    );
+    run_one_tick(&fixture).await;
+
+    let expected: Vec<(&str, String)> = vec![
+        ("requested", "call-0".to_owned()),
+        ("requested", "call-1".to_owned()),
+        ("completed", "call-0".to_owned()),
+        ("completed", "call-1".to_owned()),
+        ("requested", "call-2".to_owned()),
+        ("completed", "call-2".to_owned()),
+        ("requested", "call-3".to_owned()),
+        ("completed", "call-3".to_owned()),
+    ];
+    assert_eq!(tool_timeline(&fixture.store), expected);
+}
@@ -0,0 +1,201 @@
+//! Session plan kept in sync with execution.
+//!
+//! `plan.update` edits `state/plan.yaml` / `state/task_graph.json`; steps
+//! start when a tick begins, complete when their bound tool succeeds or a
+//! tick answers without tools, and `progress` follows completed steps.
+
+use std::path::PathBuf;
+
+use aios_protocol::{Capability, ToolCall};
+use aios_runtime::{PLAN_UPDATE_TOOL, Plan, PlanStepStatus, TickOutput};
+
+mod common;
+
+use common::{AnsweringProvider, PathHarness};
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+type Fixture = common::Fixture<AnsweringProvider, PathHarness>;
+
+impl Fixture {
+    async fn new() -> Self {
+        Fixture::builder("plans", AnsweringProvider, PathHarness)
+            .build()
+            .await
+    }
+
+    async fn tick(&self, proposed_tool: Option<ToolCall>) -> TickOutput {
+        self.tick_on(&self.session, "summarize the notes", proposed_tool)
+            .await
+    }
+
+    fn state_dir(&self) -> PathBuf {
+        let manifest = self
+            .runtime
+            .session_manifest(&self.session)
+            .expect("session manifest");
+        PathBuf::from(manifest.workspace_root).join("state")
+    }
+
+    fn plan_on_disk(&self) -> Plan {
+        let source =
+            std::fs::read_to_string(self.state_dir().join("plan.yaml")).expect("plan.yaml");
+        serde_yaml_ng::from_str(&source).expect("plan.yaml parses")
+    }
+
+    fn statuses(&self) -> Vec<(String, PlanStepStatus)> {
+        self.runtime
+            .session_plan(&self.session)
+            .expect("session plan")
+            .steps
+            .into_iter()
+            .map(|step| (step.id, step.status))
+            .collect()
+    }
+
+    fn plan_update_reasons(&self) -> Vec<String> {
+        self.store
+            .custom_events("plan.updated")
+            .iter()
+            .map(|data| data["reason"].as_str().unwrap_or_default().to_owned())
+            .collect()
+    }
+}
+
+fn plan_update(input: serde_json::Value) -> ToolCall {
+    ToolCall::new(PLAN_UPDATE_TOOL, input, Vec::new())
+}
+
+fn read(path: &str) -> ToolCall {
+    ToolCall::new(
+        "fs.read",
+        serde_json::json!({ "path": path }),
+        vec![Capability::fs_read("/session/**")],
+    )
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn plan_steps_advance_with_ticks_and_tools_and_drive_progress() {
+    use PlanStepStatus::{Completed, Pending};
+    let fixture = Fixture::new().await;
+
+    let output = fixture
+        .tick(Some(plan_update(serde_json::json!({
+            "steps": [
+                { "id": "gather", "title": "Read the notes", "tool": "fs.read" },
+                { "id": "summarize", "depends_on": ["gather"] },
+            ],
+        }))))
+        .await;
+    // The seeded bootstrap step gave way to the model's plan.
+    assert_eq!(
+        fixture.statuses(),
+        [
+            ("gather".to_owned(), Pending),
+            ("summarize".to_owned(), Pending)
+        ]
+    );
+    assert_eq!(output.state.progress, 0.0);
+
+    let output = fixture.tick(Some(read("notes.md"))).await;
+    assert_eq!(
+        fixture.statuses(),
+        [
+            ("gather".to_owned(), Completed),
+            ("summarize".to_owned(), Pending)
+        ]
+    );
+    assert_eq!(output.state.progress, 0.5);
+
+    // `summarize` starts with the tick and completes with its answer.
+    let output = fixture.tick(None).await;
+    assert_eq!(
+        fixture.statuses(),
+        [
+            ("gather".to_owned(), Completed),
+            ("summarize".to_owned(), Completed)
+        ]
+    );
+    assert_eq!(output.state.progress, 1.0);
+
+    assert_eq!(
+        fixture.plan_on_disk(),
+        fixture.runtime.session_plan(&fixture.session).unwrap()
+    );
+    let graph: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(fixture.state_dir().join("task_graph.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(graph["nodes"][0]["title"], "Read the notes");
+    assert_eq!(graph["nodes"][1]["status"], "completed");
+    assert_eq!(
+        graph["edges"],
+        serde_json::json!([{ "from": "gather", "to": "summarize" }])
+    );
+    assert_eq!(
+        fixture.plan_update_reasons(),
+        [
+            "tick_started",
+            "tool_finished",
+            "tick_started",
+            "tool_finished",
+            "tick_started",
+            "turn_answered"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failed_bound_tool_fails_its_step_and_invalid_updates_are_rejected() {
+    let fixture = Fixture::new().await;
+    fixture
+        .tick(Some(plan_update(serde_json::json!({
+            "steps": [{ "id": "gather", "tool": "fs.read" }],
+        }))))
+        .await;
+
+    fixture.tick(Some(read("missing.md"))).await;
+    assert_eq!(
+        fixture.statuses(),
+        [("gather".to_owned(), PlanStepStatus::Failed)]
+    );
+
+    // A retry that succeeds completes the failed step.
+    fixture.tick(Some(read("notes.md"))).await;
+    assert_eq!(
+        fixture.statuses(),
+        [("gather".to_owned(), PlanStepStatus::Completed)]
+    );
+
+    fixture
+        .tick(Some(plan_update(serde_json::json!({
+            "steps": [
+                { "id": "a", "depends_on": ["b"] },
+                { "id": "b", "depends_on": ["a"] },
+            ],
+        }))))
+        .await;
+    assert_eq!(
+        fixture.statuses(),
+        [("gather".to_owned(), PlanStepStatus::Completed)],
+        "a cyclic update leaves the plan untouched"
+    );
+}
+
+#[tokio::test]
+async fn sessions_without_a_plan_keep_the_bootstrap_step_in_progress() {
+    let fixture = Fixture::new().await;
+
+    let output = fixture.tick(Some(read("notes.md"))).await;
+    let output_after_answer = fixture.tick(None).await;
+
+    assert_eq!(
+        fixture.statuses(),
+        [("bootstrap".to_owned(), PlanStepStatus::InProgress)]
+    );
+    assert_eq!(output.state.progress, 0.5);
+    assert_eq!(output_after_answer.state.progress, 0.5);
+    assert_eq!(fixture.plan_on_disk().steps[0].id, "bootstrap");
+}
@@ -0,0 +1,350 @@
+//! Secret and PII redaction.
+//!
+//! A runtime with a `Redactor` scrubs API keys, session-scope secrets,
+//! high-entropy tokens and PII from events before they are journaled or
+//! broadcast, and journals a `redaction.audit` event after each redacted
+//! one naming what was removed.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use aios_protocol::{
+    BranchId, Capability, EventKind, EventRecord, KernelResult, ModelCompletion,
+    ModelCompletionRequest, ModelCompletionStream, ModelDirective, ModelProviderPort,
+    ModelStopReason, ModelStreamChunk, PolicySet, SessionId, StreamingModelProviderPort, ToolCall,
+    ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{
+    REDACTION_AUDIT_EVENT, RedactingEventStore, RedactionConfig, Redactor, TickInput, TickKind,
+};
+use async_trait::async_trait;
+
+mod common;
+
+use common::AnsweringProvider;
+
+const API_KEY: &str = "sk-ant-REDACTED";
+const DB_PASSWORD: &str = "correct-horse-battery";
+
+// ── Provider and harness ──────────────────────────────────────────────
+
+/// Streams an answer that quotes the API key, split across two deltas.
+struct SplitKeyProvider;
+
+const SPLIT_KEY_DELTAS: [&str; 2] = [
+    "the key is sk-ant-",
+    "api03-Zx81KqLmN0pQ7rStUvWx, keep it safe",
+];
+
+#[async_trait]
+impl ModelProviderPort for SplitKeyProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        panic!("the runtime must prefer the streaming provider");
+    }
+}
+
+#[async_trait]
+impl StreamingModelProviderPort for SplitKeyProvider {
+    async fn stream(
+        &self,
+        _request: ModelCompletionRequest,
+    ) -> KernelResult<ModelCompletionStream> {
+        let mut chunks: Vec<KernelResult<ModelStreamChunk>> = SPLIT_KEY_DELTAS
+            .iter()
+            .map(|delta| {
+                Ok(ModelStreamChunk::Directive {
+                    directive: ModelDirective::TextDelta {
+                        delta: (*delta).to_owned(),
+                        index: None,
+                    },
+                })
+            })
+            .collect();
+        chunks.push(Ok(ModelStreamChunk::Finished {
+            completion: ModelCompletion {
+                provider: "scripted".to_owned(),
+                model: "scripted-stream".to_owned(),
+                llm_call_record: None,
+                directives: Vec::new(),
+                stop_reason: ModelStopReason::Completed,
+                usage: None,
+                final_answer: None,
+            },
+        }));
+        Ok(Box::pin(futures_util::stream::iter(chunks)))
+    }
+}
+
+/// Prints a config file full of credentials.
+struct LeakyHarness;
+
+#[async_trait]
+impl ToolHarnessPort for LeakyHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: serde_json::json!({
+                    "stdout": format!(
+                        "ANTHROPIC_API_KEY={API_KEY}\nDB_PASSWORD={DB_PASSWORD}\nOWNER=jane.doe@example.com"
+                    ),
+                }),
+            },
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+type Fixture = common::Fixture<AnsweringProvider, LeakyHarness>;
+
+impl Fixture {
+    async fn new() -> Self {
+        Self::build(None).await
+    }
+
+    async fn streaming(provider: Arc<dyn StreamingModelProviderPort>) -> Self {
+        Self::build(Some(provider)).await
+    }
+
+    async fn build(streaming: Option<Arc<dyn StreamingModelProviderPort>>) -> Self {
+        let redactor = Redactor::new(
+            RedactionConfig::default().with_secret_scope("db", [DB_PASSWORD.to_owned()]),
+        )
+        .expect("redactor");
+        let store_redactor = redactor.clone();
+        Fixture::builder("redaction", AnsweringProvider, LeakyHarness)
+            .store_layer(move |store| Arc::new(RedactingEventStore::new(store, store_redactor)))
+            .build_with(|runtime| {
+                let runtime = runtime.with_redactor(redactor);
+                match streaming {
+                    Some(provider) => runtime.with_streaming_provider(provider),
+                    None => runtime,
+                }
+            })
+            .await
+    }
+
+    async fn session(&self, policy: PolicySet) -> SessionId {
+        self.open_session("redaction-test", policy).await
+    }
+
+    async fn print_config(&self, session: &SessionId) {
+        let call = ToolCall::new("fs.read", serde_json::json!({ "path": ".env" }), Vec::new());
+        self.tick_on(session, "show me the config", Some(call))
+            .await;
+    }
+
+    fn journal(&self, session: &SessionId) -> Vec<EventRecord> {
+        self.store.session_events(session)
+    }
+}
+
+fn tool_output(events: &[EventRecord]) -> String {
+    events
+        .iter()
+        .find_map(|event| match &event.kind {
+            EventKind::ToolCallCompleted { result, .. } => Some(result.to_string()),
+            _ => None,
+        })
+        .expect("tool call completed")
+}
+
+fn audits(events: &[EventRecord]) -> Vec<serde_json::Value> {
+    events
+        .iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::Custom { event_type, data } if event_type == REDACTION_AUDIT_EVENT => {
+                Some(data.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn secrets_and_pii_are_redacted_before_journaling_and_publishing() {
+    let fixture = Fixture::new().await;
+    let session = fixture.session(PolicySet::default()).await;
+    let mut live = fixture.runtime.subscribe_events();
+
+    fixture.print_config(&session).await;
+
+    let events = fixture.journal(&session);
+    let output = tool_output(&events);
+    assert!(!output.contains(API_KEY), "{output}");
+    assert!(output.contains("[REDACTED:secret.api_key]"), "{output}");
+    assert!(!output.contains("jane.doe@example.com"), "{output}");
+    assert!(output.contains("[REDACTED:pii.email]"), "{output}");
+
+    let audits = audits(&events);
+    assert!(
+        audits.iter().any(|audit| {
+            audit["removed"]["secret.api_key"] == 1 && audit["removed"]["pii.email"] == 1
+        }),
+        "{audits:?}"
+    );
+    let audit_text = serde_json::to_string(&audits).expect("audits serialize");
+    assert!(!audit_text.contains(API_KEY));
+
+    let sequences: Vec<u64> = events.iter().map(|event| event.sequence).collect();
+    let expected: Vec<u64> = (1..=sequences.len() as u64).collect();
+    assert_eq!(sequences, expected, "journal sequences stay contiguous");
+
+    // The audit follows the record it describes.
+    let completed = events
+        .iter()
+        .position(|event| matches!(event.kind, EventKind::ToolCallCompleted { .. }))
+        .expect("tool call completed");
+    match &events[completed + 1].kind {
+        EventKind::Custom { event_type, data } if event_type == REDACTION_AUDIT_EVENT => {
+            assert_eq!(
+                data["event_id"],
+                serde_json::json!(events[completed].event_id)
+            );
+        }
+        other => panic!("expected a redaction audit, got {other:?}"),
+    }
+
+    let mut published_audits = 0;
+    while let Ok(event) = live.try_recv() {
+        let published = serde_json::to_string(&event).expect("event serializes");
+        assert!(!published.contains(API_KEY), "{published}");
+        assert!(!published.contains("jane.doe@example.com"), "{published}");
+        if matches!(
+            &event.kind,
+            EventKind::Custom { event_type, .. } if event_type == REDACTION_AUDIT_EVENT
+        ) {
+            published_audits += 1;
+        }
+    }
+    assert_eq!(published_audits, audits.len(), "audits are broadcast too");
+}
+
+#[tokio::test]
+async fn session_scope_secrets_are_redacted_for_sessions_that_can_read_them() {
+    let fixture = Fixture::new().await;
+    let reader = fixture
+        .session(PolicySet {
+            allow_capabilities: vec![Capability::secrets("db")],
+            ..PolicySet::default()
+        })
+        .await;
+    let outsider = fixture.session(PolicySet::default()).await;
+
+    fixture.print_config(&reader).await;
+    fixture.print_config(&outsider).await;
+
+    let output = tool_output(&fixture.journal(&reader));
+    assert!(!output.contains(DB_PASSWORD), "{output}");
+    assert!(
+        output.contains("[REDACTED:secret.session_scope]"),
+        "{output}"
+    );
+
+    // The outsider never had the scope, so its value is ordinary text.
+    let output = tool_output(&fixture.journal(&outsider));
+    assert!(output.contains(DB_PASSWORD), "{output}");
+}
+
+#[tokio::test]
+async fn session_scope_secrets_are_redacted_before_the_first_tick() {
+    let fixture = Fixture::new().await;
+    let session = fixture
+        .session(PolicySet {
+            allow_capabilities: vec![Capability::secrets("db")],
+            ..PolicySet::default()
+        })
+        .await;
+
+    fixture
+        .runtime
+        .record_external_event(
+            &session,
+            EventKind::Custom {
+                event_type: "host.note".to_owned(),
+                data: serde_json::json!({ "text": format!("password is {DB_PASSWORD}") }),
+            },
+        )
+        .await
+        .expect("record event");
+
+    let note = fixture
+        .journal(&session)
+        .into_iter()
+        .find_map(|event| match event.kind {
+            EventKind::Custom { event_type, data } if event_type == "host.note" => Some(data),
+            _ => None,
+        })
+        .expect("note journaled");
+    let note = note.to_string();
+    assert!(!note.contains(DB_PASSWORD), "{note}");
+    assert!(note.contains("[REDACTED:secret.session_scope]"), "{note}");
+}
+
+#[tokio::test]
+async fn a_secret_split_across_streamed_deltas_is_redacted_before_publishing() {
+    let fixture = Fixture::streaming(Arc::new(SplitKeyProvider)).await;
+    let session = fixture.session(PolicySet::default()).await;
+    let mut live = fixture.runtime.subscribe_events();
+
+    fixture
+        .runtime
+        .tick_on_branch(
+            &session,
+            &BranchId::main(),
+            TickInput {
+                objective: "what is the key?".to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+
+    let mut deltas = Vec::new();
+    while let Ok(event) = live.try_recv() {
+        if let EventKind::TextDelta { delta, .. } = event.kind {
+            deltas.push(delta);
+        }
+    }
+    assert!(
+        deltas.iter().all(|delta| !delta.contains("sk-ant-")),
+        "{deltas:?}"
+    );
+    assert_eq!(
+        deltas.concat(),
+        "the key is [REDACTED:secret.api_key], keep it safe"
+    );
+}
+
+#[test]
+fn entropy_check_spares_digests_and_identifiers() {
+    let redactor = Redactor::new(RedactionConfig::default()).expect("redactor");
+    let session = SessionId::default();
+    let mut removed = BTreeMap::new();
+
+    let text = "commit 3f786850e387550fdab836ed7e6dc881de23001b3f786850e387550f \
+                id 0b7e4a1c-5d2f-4c8e-9a3b-6f1d2e8c7a90 ssn 123-45-6789 \
+                token q8ZrT2vXk9LmP4sW7yB1nC6hJ3dF0gKaE5uR";
+    let redacted = redactor.redact_text(&session, text, &mut removed);
+
+    assert!(redacted.contains("3f786850e387550fdab836ed7e6dc881de23001b3f786850e387550f"));
+    assert!(redacted.contains("0b7e4a1c-5d2f-4c8e-9a3b-6f1d2e8c7a90"));
+    assert!(redacted.contains("ssn [REDACTED:pii.us_ssn]"), "{redacted}");
+    assert!(
+        redacted.contains("token [REDACTED:secret.high_entropy]"),
+        "{redacted}"
+    );
+    assert_eq!(removed.get("pii.us_ssn"), Some(&1));
+    assert_eq!(removed.get("secret.high_entropy"), Some(&1));
+}
@@ -0,0 +1,177 @@
+//! Level-1 stability monitoring of the homeostasis loop.
+//!
+//! With a stability monitor registered, every tick journals the Lyapunov
+//! candidate and the estimated stability margin; while the margin is
+//! negative the session is forced into its safe mode.
+
+use aios_protocol::{
+    AgentStateVector, BudgetState, Capability, EventKind, LyapunovCandidate, OperatingMode,
+    RecursiveControlledSystem, ToolCall,
+};
+use aios_runtime::{
+    HomeostaticController, HomeostaticLyapunov, STABILITY_EVENT, StabilityConfig, StabilityMonitor,
+    TickOutput,
+};
+
+mod common;
+
+use common::{AnsweringProvider, PathHarness};
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+type Fixture = common::Fixture<AnsweringProvider, PathHarness>;
+
+impl Fixture {
+    async fn new() -> Self {
+        Fixture::builder("stability", AnsweringProvider, PathHarness)
+            // Keep the circuit breaker out of the way of the stability shield.
+            .config(|config| config.circuit_breaker_errors = 100)
+            .build_with(|runtime| {
+                runtime.with_stability_monitor(StabilityConfig::new().with_warmup(2))
+            })
+            .await
+    }
+
+    async fn tick(&self, proposed_tool: Option<ToolCall>) -> TickOutput {
+        self.tick_on(&self.session, "inspect the repository", proposed_tool)
+            .await
+    }
+
+    fn stability_events(&self) -> Vec<serde_json::Value> {
+        self.store.custom_events(STABILITY_EVENT)
+    }
+
+    fn forced_mode_changes(&self) -> Vec<OperatingMode> {
+        self.store
+            .events
+            .lock()
+            .iter()
+            .filter_map(|record| match &record.kind {
+                EventKind::ModeChanged { to, reason, .. } if reason.starts_with("stability") => {
+                    Some(*to)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn read(path: &str) -> ToolCall {
+    ToolCall::new(
+        "fs.read",
+        serde_json::json!({ "path": path }),
+        vec![Capability::fs_read("/session/**")],
+    )
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[test]
+fn candidate_decreases_under_successful_steps_and_counts_errors_and_depletion() {
+    let lyapunov = HomeostaticLyapunov::default();
+    let state = AgentStateVector::default();
+    assert!((lyapunov.evaluate(&state) - 0.7).abs() < 1e-6);
+    assert!(lyapunov.decrease_rate(&state) < 0.0);
+
+    let failing = AgentStateVector {
+        error_streak: 3,
+        ..AgentStateVector::default()
+    };
+    assert!((lyapunov.evaluate(&failing) - 1.7).abs() < 1e-6);
+
+    // Spending three quarters of the tool calls is half the budget term.
+    let depleted = AgentStateVector {
+        budget: BudgetState {
+            tool_calls_remaining: BudgetState::default().tool_calls_remaining / 4,
+            ..BudgetState::default()
+        },
+        ..AgentStateVector::default()
+    };
+    assert!((lyapunov.evaluate(&depleted) - 0.95).abs() < 1e-6);
+}
+
+#[test]
+fn the_shield_forces_the_safe_mode_once_the_margin_is_negative() {
+    let config = StabilityConfig::new().with_warmup(2);
+    let mut state = AgentStateVector::default();
+    let mut controller = HomeostaticController::new(
+        state.clone(),
+        OperatingMode::Execute,
+        StabilityMonitor::new(&config, BudgetState::default(), 3),
+        config.safe_mode,
+    );
+    for streak in 1..=2 {
+        controller.step(&OperatingMode::Execute);
+        assert_eq!(
+            controller.shield(OperatingMode::Execute),
+            OperatingMode::Execute,
+            "not warmed up yet"
+        );
+        state.error_streak = streak;
+        controller.set_state(state.clone());
+    }
+    controller.step(&OperatingMode::Execute);
+
+    let observation = controller.observe();
+    assert!(observation.stability.margin < 0.0);
+    assert_eq!(
+        controller.shield(OperatingMode::Execute),
+        OperatingMode::Recover
+    );
+    assert_eq!(
+        controller.shield(OperatingMode::AskHuman),
+        OperatingMode::AskHuman
+    );
+}
+
+#[tokio::test]
+async fn converging_sessions_journal_a_non_negative_margin() {
+    let fixture = Fixture::new().await;
+
+    for _ in 0..4 {
+        let output = fixture.tick(Some(read("src/lib.rs"))).await;
+        assert_ne!(output.mode, OperatingMode::Recover);
+    }
+
+    let events = fixture.stability_events();
+    assert_eq!(events.len(), 4);
+    let values: Vec<f64> = events
+        .iter()
+        .map(|event| event["lyapunov"].as_f64().unwrap())
+        .collect();
+    assert!(
+        values.windows(2).all(|pair| pair[1] < pair[0]),
+        "{values:?}"
+    );
+    assert!(
+        events
+            .iter()
+            .all(|event| event["margin"].as_f64().unwrap() >= 0.0)
+    );
+    assert!(fixture.forced_mode_changes().is_empty());
+
+    let controller = fixture
+        .runtime
+        .homeostatic_controller(&fixture.session)
+        .expect("stability is monitored");
+    assert!(controller.monitor().is_warm());
+}
+
+#[tokio::test]
+async fn diverging_sessions_are_forced_into_the_safe_mode() {
+    let fixture = Fixture::new().await;
+
+    let mut modes = Vec::new();
+    for _ in 0..4 {
+        modes.push(fixture.tick(Some(read("missing.txt"))).await.mode);
+    }
+
+    assert_eq!(modes.last(), Some(&OperatingMode::Recover));
+    assert_eq!(
+        fixture.forced_mode_changes().first(),
+        Some(&OperatingMode::Recover)
+    );
+    let last = fixture.stability_events().pop().unwrap();
+    assert!(last["margin"].as_f64().unwrap() < 0.0, "{last}");
+    assert_eq!(last["mode"], "recover");
+}
@@ -0,0 +1,366 @@
+//! Streaming model completions.
+//!
+//! When the runtime has a `StreamingModelProviderPort`, text deltas are
+//! broadcast to live subscribers as ephemeral records (sequence 0, never
+//! journaled) while the model is still producing them. Only the committed
+//! assistant `Message` reaches the event store.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelError,
+    KernelResult, ModelCompletion, ModelCompletionRequest, ModelCompletionStream, ModelDirective,
+    ModelProviderPort, ModelRouting, ModelStopReason, ModelStreamChunk, PolicyGateDecision,
+    PolicyGatePort, PolicySet, SessionId, StreamingModelProviderPort, ToolExecutionReport,
+    ToolExecutionRequest, ToolHarnessPort,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Streaming provider ────────────────────────────────────────────────
+
+const DELTAS: [&str; 3] = ["Hel", "lo, ", "world"];
+
+struct DeltaProvider;
+
+fn finished_completion() -> ModelCompletion {
+    ModelCompletion {
+        provider: "scripted".to_owned(),
+        model: "scripted-stream".to_owned(),
+        llm_call_record: None,
+        directives: Vec::new(),
+        stop_reason: ModelStopReason::Completed,
+        usage: None,
+        final_answer: Some(DELTAS.concat()),
+    }
+}
+
+#[async_trait]
+impl ModelProviderPort for DeltaProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        panic!("the runtime must prefer the streaming provider");
+    }
+}
+
+#[async_trait]
+impl StreamingModelProviderPort for DeltaProvider {
+    async fn stream(
+        &self,
+        _request: ModelCompletionRequest,
+    ) -> KernelResult<ModelCompletionStream> {
+        let mut chunks: Vec<KernelResult<ModelStreamChunk>> = DELTAS
+            .iter()
+            .map(|delta| {
+                Ok(ModelStreamChunk::Directive {
+                    directive: ModelDirective::TextDelta {
+                        delta: (*delta).to_owned(),
+                        index: None,
+                    },
+                })
+            })
+            .collect();
+        chunks.push(Ok(ModelStreamChunk::Finished {
+            completion: finished_completion(),
+        }));
+        Ok(Box::pin(futures_util::stream::iter(chunks)))
+    }
+}
+
+/// Fails its first stream after one delta, then streams normally.
+#[derive(Default)]
+struct FlakyDeltaProvider {
+    attempts: AtomicU32,
+}
+
+#[async_trait]
+impl ModelProviderPort for FlakyDeltaProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        panic!("the runtime must prefer the streaming provider");
+    }
+}
+
+#[async_trait]
+impl StreamingModelProviderPort for FlakyDeltaProvider {
+    async fn stream(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletionStream> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            let chunks: Vec<KernelResult<ModelStreamChunk>> = vec![
+                Ok(ModelStreamChunk::Directive {
+                    directive: ModelDirective::TextDelta {
+                        delta: "Hel".to_owned(),
+                        index: None,
+                    },
+                }),
+                Err(KernelError::Transient("connection reset".to_owned())),
+            ];
+            return Ok(Box::pin(futures_util::stream::iter(chunks)));
+        }
+        DeltaProvider.stream(request).await
+    }
+}
+
+struct NoTools;
+
+#[async_trait]
+impl ToolHarnessPort for NoTools {
+    async fn execute(&self, _request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        panic!("no tool calls expected");
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn text_deltas_are_broadcast_live_and_only_the_message_is_journaled() {
+    let root =
+        std::env::temp_dir().join(format!("aios-runtime-streaming-{}", uuid::Uuid::new_v4()));
+    let store = Arc::new(MemEventStore::default());
+    let provider = Arc::new(DeltaProvider);
+    let runtime = KernelRuntime::new(
+        RuntimeConfig::new(root),
+        store.clone() as Arc<dyn EventStorePort>,
+        provider.clone() as Arc<dyn ModelProviderPort>,
+        Arc::new(NoTools) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    )
+    .with_streaming_provider(provider as Arc<dyn StreamingModelProviderPort>);
+
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "streaming-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    let mut live = runtime.subscribe_events();
+
+    runtime
+        .tick_on_branch(
+            &session,
+            &BranchId::main(),
+            TickInput {
+                objective: "say hello".to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+
+    let mut live_deltas = Vec::new();
+    while let Ok(record) = live.try_recv() {
+        if let EventKind::TextDelta { delta, .. } = &record.kind {
+            assert!(
+                record.is_ephemeral(),
+                "streamed deltas must not be journaled"
+            );
+            live_deltas.push(delta.clone());
+        }
+    }
+    assert_eq!(live_deltas, DELTAS);
+
+    let journal = store.events.lock();
+    assert!(
+        !journal
+            .iter()
+            .any(|record| matches!(record.kind, EventKind::TextDelta { .. })),
+        "the store must not hold ephemeral deltas"
+    );
+    let messages: Vec<&str> = journal
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::Message { role, content, .. } if role == "assistant" => {
+                Some(content.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(messages, vec!["Hello, world"]);
+}
+
+#[tokio::test]
+async fn a_failed_streamed_attempt_is_followed_by_a_reset_before_the_retry() {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-streaming-retry-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let store = Arc::new(MemEventStore::default());
+    let provider = Arc::new(FlakyDeltaProvider::default());
+    let mut config = RuntimeConfig::new(root);
+    config.model_retry_backoff_ms = 1;
+    let runtime = KernelRuntime::new(
+        config,
+        store.clone() as Arc<dyn EventStorePort>,
+        provider.clone() as Arc<dyn ModelProviderPort>,
+        Arc::new(NoTools) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    )
+    .with_streaming_provider(provider as Arc<dyn StreamingModelProviderPort>);
+
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "streaming-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    let mut live = runtime.subscribe_events();
+
+    runtime
+        .tick_on_branch(
+            &session,
+            &BranchId::main(),
+            TickInput {
+                objective: "say hello".to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("tick");
+
+    let mut live_stream = Vec::new();
+    while let Ok(record) = live.try_recv() {
+        match &record.kind {
+            EventKind::TextDelta { delta, .. } => live_stream.push(delta.clone()),
+            EventKind::Custom { event_type, .. } if event_type == "model.stream_reset" => {
+                assert!(record.is_ephemeral(), "resets are never journaled");
+                live_stream.push("<reset>".to_owned());
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(live_stream, ["Hel", "<reset>", "Hel", "lo, ", "world"]);
+
+    let journal = store.events.lock();
+    let messages: Vec<&str> = journal
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::Message { role, content, .. } if role == "assistant" => {
+                Some(content.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(messages, vec!["Hello, world"]);
+}
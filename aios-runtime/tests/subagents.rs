@@ -0,0 +1,469 @@
+//! Sub-agent spawning through the runtime's `agent.spawn` tool.
+//!
+//! A parent tick that calls `agent.spawn` creates a child session whose
+//! policy is narrowed to the parent's, ticks it until it stops calling
+//! tools, and gets the child's final answer back as the tool result. The
+//! child starts from the parent's remaining budget and whatever it spends
+//! is deducted from the parent.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    BudgetState, Capability, EventKind, EventRecord, EventRecordStream, EventStorePort,
+    KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort,
+    ModelRouting, ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId,
+    ToolCall, ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{KernelRuntime, RuntimeConfig, SPAWN_SUBAGENT_TOOL, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Scripted provider ─────────────────────────────────────────────────
+//
+// The parent's objective spawns a child. A child's first completion reads a
+// file; its second answers.
+
+const PARENT_OBJECTIVE: &str = "summarize the project";
+const CHILD_ANSWER: &str = "the notes say hi";
+
+#[derive(Default)]
+struct DelegatingProvider {
+    requests_by_session: Mutex<HashMap<String, usize>>,
+}
+
+fn completion(directives: Vec<ModelDirective>, final_answer: Option<&str>) -> ModelCompletion {
+    ModelCompletion {
+        provider: "scripted".to_owned(),
+        model: "scripted-deterministic".to_owned(),
+        llm_call_record: None,
+        stop_reason: if final_answer.is_some() {
+            ModelStopReason::Completed
+        } else {
+            ModelStopReason::ToolCall
+        },
+        directives,
+        usage: None,
+        final_answer: final_answer.map(ToOwned::to_owned),
+    }
+}
+
+#[async_trait]
+impl ModelProviderPort for DelegatingProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        if request.objective == PARENT_OBJECTIVE {
+            let call = ToolCall {
+                call_id: "spawn-0".to_owned(),
+                tool_name: SPAWN_SUBAGENT_TOOL.to_owned(),
+                input: serde_json::json!({
+                    "objective": "read the notes",
+                    "policy": PolicySet {
+                        allow_capabilities: vec![
+                            Capability::fs_read("/session/**"),
+                            Capability::exec("*"),
+                        ],
+                        gate_capabilities: Vec::new(),
+                        deny_capabilities: Vec::new(),
+                        max_tool_runtime_secs: 600,
+                        max_events_per_turn: 64,
+                    },
+                }),
+                requested_capabilities: Vec::new(),
+            };
+            return Ok(completion(vec![ModelDirective::ToolCall { call }], None));
+        }
+
+        let seen = {
+            let mut requests = self.requests_by_session.lock();
+            let seen = requests
+                .entry(request.session_id.as_str().to_owned())
+                .or_default();
+            *seen += 1;
+            *seen
+        };
+        if seen == 1 {
+            let call = ToolCall {
+                call_id: "read-0".to_owned(),
+                tool_name: "read_file".to_owned(),
+                input: serde_json::json!({ "path": "notes.md" }),
+                requested_capabilities: vec![Capability::fs_read("/session/**")],
+            };
+            return Ok(completion(vec![ModelDirective::ToolCall { call }], None));
+        }
+        Ok(completion(
+            vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: CHILD_ANSWER.to_owned(),
+            }],
+            Some(CHILD_ANSWER),
+        ))
+    }
+}
+
+// ── Harness and policy gate ───────────────────────────────────────────
+
+struct EchoHarness;
+
+#[async_trait]
+impl ToolHarnessPort for EchoHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: serde_json::json!({ "content": "hi" }),
+            },
+        })
+    }
+}
+
+/// Allows everything and records the policy installed for each session,
+/// reporting it back as the enforced one.
+#[derive(Default)]
+struct RecordingGate {
+    policies: Mutex<HashMap<String, PolicySet>>,
+}
+
+#[async_trait]
+impl PolicyGatePort for RecordingGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+
+    async fn set_policy(&self, session_id: SessionId, policy: PolicySet) -> KernelResult<()> {
+        self.policies
+            .lock()
+            .insert(session_id.as_str().to_owned(), policy);
+        Ok(())
+    }
+
+    async fn policy(&self, session_id: SessionId) -> KernelResult<Option<PolicySet>> {
+        Ok(self.policies.lock().get(session_id.as_str()).cloned())
+    }
+}
+
+// ── No-op approvals ───────────────────────────────────────────────────
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+struct Fixture {
+    runtime: KernelRuntime,
+    store: Arc<MemEventStore>,
+    gate: Arc<RecordingGate>,
+}
+
+fn build_runtime(config: impl FnOnce(&mut RuntimeConfig)) -> Fixture {
+    let root =
+        std::env::temp_dir().join(format!("aios-runtime-subagents-{}", uuid::Uuid::new_v4()));
+    let mut runtime_config = RuntimeConfig::new(root);
+    config(&mut runtime_config);
+    let store = Arc::new(MemEventStore::default());
+    let gate = Arc::new(RecordingGate::default());
+
+    let runtime = KernelRuntime::new(
+        runtime_config,
+        store.clone() as Arc<dyn EventStorePort>,
+        Arc::new(DelegatingProvider::default()) as Arc<dyn ModelProviderPort>,
+        Arc::new(EchoHarness) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        gate.clone() as Arc<dyn PolicyGatePort>,
+    );
+
+    Fixture {
+        runtime,
+        store,
+        gate,
+    }
+}
+
+async fn new_session(runtime: &KernelRuntime) -> SessionId {
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "subagents-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    session
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn spawned_child_answers_as_a_tool_result_and_is_charged_to_the_parent() {
+    let fixture = build_runtime(|_| {});
+    let parent = new_session(&fixture.runtime).await;
+
+    let output = fixture
+        .runtime
+        .tick_on_branch(
+            &parent,
+            &BranchId::main(),
+            TickInput {
+                objective: PARENT_OBJECTIVE.to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("parent tick");
+
+    let result = fixture
+        .store
+        .events
+        .lock()
+        .iter()
+        .find_map(|record| match &record.kind {
+            EventKind::ToolCallCompleted {
+                tool_name, result, ..
+            } if record.session_id == parent && tool_name == SPAWN_SUBAGENT_TOOL => {
+                Some(result["output"].clone())
+            }
+            _ => None,
+        })
+        .expect("agent.spawn completed in the parent");
+    assert_eq!(result["final_answer"], CHILD_ANSWER);
+    assert_eq!(result["ticks"], 2);
+    assert_eq!(result["budget_spent"]["tool_calls"], 1);
+    assert_eq!(result["budget_spent"]["tokens"], 750);
+
+    // One tool call for the spawn itself plus the child's read.
+    let initial = BudgetState::default();
+    assert_eq!(
+        output.state.budget.tool_calls_remaining,
+        initial.tool_calls_remaining - 2
+    );
+    assert_eq!(
+        output.state.budget.tokens_remaining,
+        initial.tokens_remaining - 1_500
+    );
+
+    let child_id = result["child_session_id"].as_str().unwrap().to_owned();
+    let child = fixture
+        .runtime
+        .list_sessions()
+        .into_iter()
+        .find(|manifest| manifest.session_id.as_str() == child_id)
+        .expect("child session exists");
+    assert_eq!(child.parent_session.as_ref(), Some(&parent));
+
+    let child_policy = fixture.gate.policies.lock()[&child_id].clone();
+    assert_eq!(
+        child_policy.allow_capabilities,
+        vec![Capability::fs_read("/session/**")]
+    );
+    assert_eq!(
+        child_policy.gate_capabilities,
+        PolicySet::default().gate_capabilities
+    );
+    assert_eq!(child_policy.max_tool_runtime_secs, 30);
+}
+
+#[tokio::test]
+async fn a_child_that_fails_is_still_charged_to_the_parent() {
+    // One tick is only enough for the child's read, so it ends unanswered.
+    let fixture = build_runtime(|config| config.subagent_max_ticks = 1);
+    let parent = new_session(&fixture.runtime).await;
+
+    let output = fixture
+        .runtime
+        .tick_on_branch(
+            &parent,
+            &BranchId::main(),
+            TickInput {
+                objective: PARENT_OBJECTIVE.to_owned(),
+                proposed_tool: None,
+                system_prompt: None,
+                allowed_tools: None,
+                client_tools: Vec::new(),
+                kind: TickKind::Direct,
+            },
+        )
+        .await
+        .expect("parent tick");
+
+    let error = fixture
+        .store
+        .events
+        .lock()
+        .iter()
+        .find_map(|record| match &record.kind {
+            EventKind::ToolCallCompleted {
+                tool_name, result, ..
+            } if record.session_id == parent && tool_name == SPAWN_SUBAGENT_TOOL => {
+                result["error"].as_str().map(ToOwned::to_owned)
+            }
+            _ => None,
+        })
+        .expect("agent.spawn failed in the parent");
+    assert!(error.contains("finished without an answer"), "{error}");
+    assert!(error.contains("budget_spent"), "{error}");
+
+    let initial = BudgetState::default();
+    assert_eq!(
+        output.state.budget.tool_calls_remaining,
+        initial.tool_calls_remaining - 2
+    );
+    assert_eq!(
+        output.state.budget.tokens_remaining,
+        initial.tokens_remaining - 1_500
+    );
+}
+
+#[tokio::test]
+async fn child_sessions_stop_at_the_depth_limit() {
+    let fixture = build_runtime(|config| config.subagent_max_depth = 1);
+    let parent = new_session(&fixture.runtime).await;
+
+    let child = fixture
+        .runtime
+        .create_child_session(&parent, PolicySet::default())
+        .await
+        .expect("first level is allowed");
+    let error = fixture
+        .runtime
+        .create_child_session(&child.session_id, PolicySet::default())
+        .await
+        .expect_err("second level exceeds the limit");
+    assert!(error.to_string().contains("depth limit"), "{error}");
+}
+
+#[tokio::test]
+async fn children_are_narrowed_against_the_policy_the_gate_enforces() {
+    let fixture = build_runtime(|_| {});
+    let parent = new_session(&fixture.runtime).await;
+    let tightened = PolicySet {
+        allow_capabilities: vec![Capability::fs_read("/session/**")],
+        gate_capabilities: vec![],
+        deny_capabilities: vec![],
+        max_tool_runtime_secs: 10,
+        max_events_per_turn: 64,
+    };
+    fixture
+        .gate
+        .set_policy(parent.clone(), tightened)
+        .await
+        .expect("tighten the parent");
+
+    let child = fixture
+        .runtime
+        .create_child_session(&parent, PolicySet::default())
+        .await
+        .expect("create child");
+    let enforced = fixture
+        .gate
+        .policies
+        .lock()
+        .get(child.session_id.as_str())
+        .cloned()
+        .expect("child policy installed");
+    assert_eq!(
+        enforced.allow_capabilities,
+        vec![Capability::fs_read("/session/**")]
+    );
+    assert!(enforced.gate_capabilities.is_empty());
+    assert_eq!(enforced.max_tool_runtime_secs, 10);
+}
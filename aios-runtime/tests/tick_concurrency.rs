@@ -0,0 +1,417 @@
+//! Single-flight ticks per session and branch.
+//!
+//! Concurrent ticks on the same `(session, branch)` never overlap: under
+//! `TickConcurrency::Queue` they run one after another, under `Reject`
+//! the late caller gets `TickInProgress`, and under `Coalesce` it shares
+//! the running tick's output. Ticks on different branches still overlap,
+//! and each one's budget spend reaches the session state.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BranchId,
+    Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelResult,
+    ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelRouting,
+    ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId, ToolCall,
+    ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{
+    KernelRuntime, RuntimeConfig, TickConcurrency, TickInProgress, TickInput, TickKind,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio::sync::Semaphore;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── Gated provider ────────────────────────────────────────────────────
+//
+// Every completion waits for a permit on `release`, so a test decides
+// when a tick may finish while it observes how many are in flight.
+
+struct GatedProvider {
+    release: Semaphore,
+    calls: AtomicUsize,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl GatedProvider {
+    fn new() -> Self {
+        Self {
+            release: Semaphore::new(0),
+            calls: AtomicUsize::new(0),
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait until `n` completions are blocked on the gate.
+    async fn wait_in_flight(&self, n: usize) {
+        while self.in_flight.load(Ordering::SeqCst) < n {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ModelProviderPort for GatedProvider {
+    async fn complete(&self, _request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        self.release
+            .acquire()
+            .await
+            .expect("gate never closes")
+            .forget();
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-gated".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "done".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("done".to_owned()),
+        })
+    }
+}
+
+/// Tool executions block until released, like [`GatedProvider`].
+struct GatedTools {
+    release: Semaphore,
+    in_flight: AtomicUsize,
+}
+
+impl GatedTools {
+    async fn wait_in_flight(&self, n: usize) {
+        while self.in_flight.load(Ordering::SeqCst) < n {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHarnessPort for GatedTools {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        self.release
+            .acquire()
+            .await
+            .expect("gate never closes")
+            .forget();
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: serde_json::json!({ "ok": true }),
+            },
+        })
+    }
+}
+
+// ── Allow-all gate and no-op approvals ────────────────────────────────
+
+struct AllowAllGate;
+
+#[async_trait]
+impl PolicyGatePort for AllowAllGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+struct Fixture {
+    runtime: KernelRuntime,
+    store: Arc<MemEventStore>,
+    provider: Arc<GatedProvider>,
+    tools: Arc<GatedTools>,
+    session: SessionId,
+}
+
+async fn fixture(tick_concurrency: TickConcurrency) -> Fixture {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-tick-concurrency-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let mut config = RuntimeConfig::new(root);
+    config.tick_concurrency = tick_concurrency;
+    let store = Arc::new(MemEventStore::default());
+    let provider = Arc::new(GatedProvider::new());
+    let tools = Arc::new(GatedTools {
+        release: Semaphore::new(0),
+        in_flight: AtomicUsize::new(0),
+    });
+    let runtime = KernelRuntime::new(
+        config,
+        store.clone() as Arc<dyn EventStorePort>,
+        provider.clone() as Arc<dyn ModelProviderPort>,
+        tools.clone() as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(AllowAllGate) as Arc<dyn PolicyGatePort>,
+    );
+
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "tick-concurrency-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    Fixture {
+        runtime,
+        store,
+        provider,
+        tools,
+        session,
+    }
+}
+
+fn input(objective: &str) -> TickInput {
+    TickInput {
+        objective: objective.to_owned(),
+        proposed_tool: None,
+        system_prompt: None,
+        allowed_tools: None,
+        client_tools: Vec::new(),
+        kind: TickKind::Direct,
+    }
+}
+
+fn spawn_tick(
+    fixture: &Fixture,
+    branch: BranchId,
+    objective: &str,
+) -> tokio::task::JoinHandle<anyhow::Result<aios_runtime::TickOutput>> {
+    let runtime = fixture.runtime.clone();
+    let session = fixture.session.clone();
+    let input = input(objective);
+    tokio::spawn(async move { runtime.tick_on_branch(&session, &branch, input).await })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn queued_ticks_on_one_branch_never_interleave() {
+    let fixture = fixture(TickConcurrency::Queue).await;
+    let first = spawn_tick(&fixture, BranchId::main(), "first");
+    fixture.provider.wait_in_flight(1).await;
+    let second = spawn_tick(&fixture, BranchId::main(), "second");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    fixture.provider.release.add_permits(2);
+    first.await.unwrap().expect("first tick");
+    second.await.unwrap().expect("second tick");
+
+    assert_eq!(fixture.provider.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(fixture.provider.max_in_flight.load(Ordering::SeqCst), 1);
+
+    // Each run's events form one contiguous block.
+    let runs: Vec<bool> = fixture
+        .store
+        .events
+        .lock()
+        .iter()
+        .filter_map(|record| match record.kind {
+            EventKind::RunStarted { .. } => Some(true),
+            EventKind::RunFinished { .. } => Some(false),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(runs, vec![true, false, true, false]);
+}
+
+#[tokio::test]
+async fn rejecting_policy_fails_the_late_tick() {
+    let fixture = fixture(TickConcurrency::Reject).await;
+    let first = spawn_tick(&fixture, BranchId::main(), "first");
+    fixture.provider.wait_in_flight(1).await;
+
+    let error = fixture
+        .runtime
+        .tick_on_branch(&fixture.session, &BranchId::main(), input("second"))
+        .await
+        .expect_err("second tick must be rejected");
+    assert!(error.downcast_ref::<TickInProgress>().is_some());
+
+    fixture.provider.release.add_permits(1);
+    first.await.unwrap().expect("first tick");
+    assert_eq!(fixture.provider.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn coalescing_policy_shares_the_running_tick_output() {
+    let fixture = fixture(TickConcurrency::Coalesce).await;
+    let first = spawn_tick(&fixture, BranchId::main(), "first");
+    fixture.provider.wait_in_flight(1).await;
+    let second = spawn_tick(&fixture, BranchId::main(), "second");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    fixture.provider.release.add_permits(1);
+    let first = first.await.unwrap().expect("first tick");
+    let second = second.await.unwrap().expect("coalesced tick");
+
+    assert_eq!(fixture.provider.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first.last_sequence, second.last_sequence);
+}
+
+#[tokio::test]
+async fn ticks_on_different_branches_run_in_parallel() {
+    let fixture = fixture(TickConcurrency::Reject).await;
+    let feature = BranchId::from_string("feature");
+    fixture
+        .runtime
+        .create_branch(&fixture.session, feature.clone(), None, None)
+        .await
+        .expect("create branch");
+
+    let main = spawn_tick(&fixture, BranchId::main(), "main");
+    let side = spawn_tick(&fixture, feature, "feature");
+    fixture.provider.wait_in_flight(2).await;
+
+    fixture.provider.release.add_permits(2);
+    main.await.unwrap().expect("main tick");
+    side.await.unwrap().expect("feature tick");
+    assert_eq!(fixture.provider.max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn budget_spent_on_concurrent_branches_is_not_lost() {
+    let fixture = fixture(TickConcurrency::Reject).await;
+    fixture.provider.release.add_permits(16);
+    let feature = BranchId::from_string("feature");
+    fixture
+        .runtime
+        .create_branch(&fixture.session, feature.clone(), None, None)
+        .await
+        .expect("create branch");
+
+    let tool_tick = |branch: BranchId| {
+        let runtime = fixture.runtime.clone();
+        let session = fixture.session.clone();
+        let mut input = input("run a tool");
+        input.proposed_tool = Some(ToolCall::new("noop", serde_json::json!({}), Vec::new()));
+        tokio::spawn(async move { runtime.tick_on_branch(&session, &branch, input).await })
+    };
+    let main = tool_tick(BranchId::main());
+    let side = tool_tick(feature);
+    fixture.tools.wait_in_flight(2).await;
+
+    fixture.tools.release.add_permits(2);
+    main.await.unwrap().expect("main tick");
+    side.await.unwrap().expect("feature tick");
+
+    let after = fixture
+        .runtime
+        .tick_on_branch(&fixture.session, &BranchId::main(), input("report"))
+        .await
+        .expect("follow-up tick");
+    let default_calls = aios_protocol::BudgetState::default().tool_calls_remaining;
+    assert_eq!(after.state.budget.tool_calls_remaining, default_calls - 2);
+}
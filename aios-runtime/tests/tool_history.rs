@@ -195,17 +195,9 @@ impl PolicyGatePort for StaticGate {
         requested: Vec<Capability>,
     ) -> KernelResult<PolicyGateDecision> {
         if self.deny_all {
-            return Ok(PolicyGateDecision {
-                allowed: Vec::new(),
-                requires_approval: Vec::new(),
-                denied: requested,
-            });
+            return Ok(PolicyGateDecision::new(Vec::new(), Vec::new(), requested));
         }
-        Ok(PolicyGateDecision {
-            allowed: requested,
-            requires_approval: Vec::new(),
-            denied: Vec::new(),
-        })
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
     }
 }
 
@@ -0,0 +1,440 @@
+//! Spilling oversized tool outputs to blob storage.
+//!
+//! With a blob store wired via `KernelRuntime::with_blob_store`, a tool
+//! output whose serialized size exceeds
+//! `RuntimeConfig::tool_output_spill_bytes` is stored as a
+//! content-addressed blob. `ToolCallCompleted` keeps only a preview, the
+//! total size and a `BlobRef`; the next tick's history shows the preview
+//! with a truncation marker naming `blob.read`. Outputs under the threshold
+//! — and every output when no store is wired — stay inline. With a
+//! redactor wired, the spilled payload and its preview are redacted.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use aios_protocol::blob::BlobMetadata;
+use aios_protocol::ports::BlobStorePort;
+use aios_protocol::{
+    ApprovalId, ApprovalPort, ApprovalRequest, ApprovalResolution, ApprovalTicket, BlobHash,
+    BranchId, Capability, EventKind, EventRecord, EventRecordStream, EventStorePort, KernelError,
+    KernelResult, ModelCompletion, ModelCompletionRequest, ModelDirective, ModelProviderPort,
+    ModelRouting, ModelStopReason, PolicyGateDecision, PolicyGatePort, PolicySet, SessionId,
+    ToolCall, ToolExecutionReport, ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{KernelRuntime, RedactionConfig, Redactor, RuntimeConfig, TickInput, TickKind};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+// ── In-memory event store ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemEventStore {
+    events: Mutex<Vec<EventRecord>>,
+}
+
+#[async_trait]
+impl EventStorePort for MemEventStore {
+    async fn append(&self, event: EventRecord) -> KernelResult<EventRecord> {
+        self.events.lock().push(event.clone());
+        Ok(event)
+    }
+
+    async fn read(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+        from_sequence: u64,
+        limit: usize,
+    ) -> KernelResult<Vec<EventRecord>> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| {
+                e.session_id == session_id
+                    && e.branch_id == branch_id
+                    && e.sequence >= from_sequence
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn head(&self, session_id: SessionId, branch_id: BranchId) -> KernelResult<u64> {
+        let events = self.events.lock();
+        Ok(events
+            .iter()
+            .filter(|e| e.session_id == session_id && e.branch_id == branch_id)
+            .map(|e| e.sequence)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn subscribe(
+        &self,
+        _session_id: SessionId,
+        _branch_id: BranchId,
+        _after_sequence: u64,
+    ) -> KernelResult<EventRecordStream> {
+        Ok(Box::pin(futures_util::stream::empty()))
+    }
+}
+
+// ── History-recording provider ────────────────────────────────────────
+//
+// Records `conversation_history` of every request. The first completion
+// proposes one tool call (registry or client per the test); subsequent
+// completions answer plainly so loops terminate.
+
+struct HistoryProvider {
+    propose_tool: Option<ToolCall>,
+    histories: Mutex<Vec<Vec<aios_protocol::ConversationTurn>>>,
+    answered: AtomicBool,
+}
+
+impl HistoryProvider {
+    fn proposing(call: ToolCall) -> Self {
+        Self {
+            propose_tool: Some(call),
+            histories: Mutex::new(Vec::new()),
+            answered: AtomicBool::new(false),
+        }
+    }
+
+    /// All assistant-turn content seen by request `index`, joined.
+    fn assistant_text_at(&self, index: usize) -> String {
+        self.histories.lock()[index]
+            .iter()
+            .filter(|t| t.role == "assistant")
+            .map(|t| t.content.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn request_count(&self) -> usize {
+        self.histories.lock().len()
+    }
+}
+
+#[async_trait]
+impl ModelProviderPort for HistoryProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        self.histories
+            .lock()
+            .push(request.conversation_history.clone());
+
+        let first = !self.answered.swap(true, Ordering::SeqCst);
+        if first && let Some(call) = self.propose_tool.clone() {
+            return Ok(ModelCompletion {
+                provider: "scripted".to_owned(),
+                model: "scripted-deterministic".to_owned(),
+                llm_call_record: None,
+                directives: vec![ModelDirective::ToolCall { call }],
+                stop_reason: ModelStopReason::ToolCall,
+                usage: None,
+                final_answer: None,
+            });
+        }
+
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: "done".to_owned(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("done".to_owned()),
+        })
+    }
+}
+
+// ── Harness with configurable result size ─────────────────────────────
+
+struct SizedResultHarness {
+    result_payload: serde_json::Value,
+}
+
+#[async_trait]
+impl ToolHarnessPort for SizedResultHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id.clone(),
+            tool_name: request.call.tool_name.clone(),
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success {
+                output: self.result_payload.clone(),
+            },
+        })
+    }
+}
+
+// ── Policy gate: allow-all or deny-all ────────────────────────────────
+
+struct StaticGate {
+    deny_all: bool,
+}
+
+#[async_trait]
+impl PolicyGatePort for StaticGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        if self.deny_all {
+            return Ok(PolicyGateDecision::new(Vec::new(), Vec::new(), requested));
+        }
+        Ok(PolicyGateDecision::new(requested, Vec::new(), Vec::new()))
+    }
+}
+
+// ── No-op approvals ───────────────────────────────────────────────────
+
+#[derive(Default)]
+struct NoopApprovals;
+
+#[async_trait]
+impl ApprovalPort for NoopApprovals {
+    async fn enqueue(&self, request: ApprovalRequest) -> KernelResult<ApprovalTicket> {
+        Ok(ApprovalTicket {
+            approval_id: ApprovalId::default(),
+            session_id: request.session_id,
+            call_id: request.call_id,
+            tool_name: request.tool_name,
+            capability: request.capability,
+            reason: request.reason,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn list_pending(&self, _session_id: SessionId) -> KernelResult<Vec<ApprovalTicket>> {
+        Ok(Vec::new())
+    }
+
+    async fn resolve(
+        &self,
+        approval_id: ApprovalId,
+        approved: bool,
+        actor: String,
+    ) -> KernelResult<ApprovalResolution> {
+        Ok(ApprovalResolution {
+            approval_id,
+            approved,
+            actor,
+            resolved_at: chrono::Utc::now(),
+        })
+    }
+}
+
+// ── In-memory blob store ──────────────────────────────────────────────
+
+#[derive(Default)]
+struct MemBlobStore {
+    blobs: Mutex<HashMap<String, bytes::Bytes>>,
+}
+
+#[async_trait]
+impl BlobStorePort for MemBlobStore {
+    async fn put(
+        &self,
+        payload: bytes::Bytes,
+        _content_type: Option<String>,
+    ) -> KernelResult<BlobHash> {
+        let hash = format!("{:064x}", self.blobs.lock().len() + 1);
+        self.blobs.lock().insert(hash.clone(), payload);
+        Ok(BlobHash::from_hex(hash))
+    }
+
+    async fn get(&self, hash: BlobHash) -> KernelResult<bytes::Bytes> {
+        self.blobs
+            .lock()
+            .get(hash.as_str())
+            .cloned()
+            .ok_or_else(|| KernelError::InvalidState(format!("unknown blob {hash}")))
+    }
+
+    async fn head(&self, hash: BlobHash) -> KernelResult<BlobMetadata> {
+        let size = self.get(hash.clone()).await?.len() as u64;
+        Ok(BlobMetadata::new(hash, size, None, chrono::Utc::now()))
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+fn registry_call() -> ToolCall {
+    ToolCall {
+        call_id: "call-0".to_owned(),
+        tool_name: "read_file".to_owned(),
+        input: serde_json::json!({ "path": "artifacts/log.txt" }),
+        requested_capabilities: vec![Capability::fs_read("/session/**")],
+    }
+}
+
+fn build_runtime(
+    harness_result: serde_json::Value,
+    blob_store: Option<Arc<MemBlobStore>>,
+) -> (KernelRuntime, Arc<HistoryProvider>, Arc<MemEventStore>) {
+    let root = std::env::temp_dir().join(format!(
+        "aios-runtime-tool-output-spill-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let provider = Arc::new(HistoryProvider::proposing(registry_call()));
+    let events = Arc::new(MemEventStore::default());
+
+    let mut config = RuntimeConfig::new(root);
+    config.tool_output_spill_bytes = 1024;
+    config.tool_output_preview_bytes = 128;
+    let mut runtime = KernelRuntime::new(
+        config,
+        events.clone() as Arc<dyn EventStorePort>,
+        provider.clone() as Arc<dyn ModelProviderPort>,
+        Arc::new(SizedResultHarness {
+            result_payload: harness_result,
+        }) as Arc<dyn ToolHarnessPort>,
+        Arc::new(NoopApprovals) as Arc<dyn ApprovalPort>,
+        Arc::new(StaticGate { deny_all: false }) as Arc<dyn PolicyGatePort>,
+    )
+    .with_registry_tool_names(vec!["read_file"]);
+    if let Some(blob_store) = blob_store {
+        runtime = runtime.with_blob_store(blob_store as Arc<dyn BlobStorePort>);
+    }
+
+    (runtime, provider, events)
+}
+
+async fn run_two_ticks(runtime: &KernelRuntime) {
+    let session = SessionId::default();
+    runtime
+        .create_session_with_id(
+            session.clone(),
+            "tool-output-spill-test",
+            PolicySet::default(),
+            ModelRouting::default(),
+        )
+        .await
+        .expect("create session");
+    for objective in ["read the log", ""] {
+        runtime
+            .tick_on_branch(
+                &session,
+                &BranchId::main(),
+                TickInput {
+                    objective: objective.to_owned(),
+                    proposed_tool: None,
+                    system_prompt: None,
+                    allowed_tools: None,
+                    client_tools: Vec::new(),
+                    kind: TickKind::Direct,
+                },
+            )
+            .await
+            .expect("tick");
+    }
+}
+
+fn completed_output(events: &MemEventStore) -> serde_json::Value {
+    events
+        .events
+        .lock()
+        .iter()
+        .find_map(|event| match &event.kind {
+            EventKind::ToolCallCompleted { result, .. } => Some(result["output"].clone()),
+            _ => None,
+        })
+        .expect("tool call completed")
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn oversized_output_is_spilled_and_history_points_at_blob_read() {
+    let log = "line of build output\n".repeat(200);
+    let payload = serde_json::json!({ "path": "artifacts/log.txt", "content": log });
+    let blobs = Arc::new(MemBlobStore::default());
+    let (runtime, provider, events) = build_runtime(payload.clone(), Some(blobs.clone()));
+
+    run_two_ticks(&runtime).await;
+
+    let output = completed_output(&events);
+    assert_eq!(output["truncated"], serde_json::json!(true));
+    let total_bytes = payload.to_string().len() as u64;
+    assert_eq!(output["total_bytes"], serde_json::json!(total_bytes));
+    assert!(output["preview"].as_str().unwrap().len() <= 128);
+    assert!(
+        output.get("content").is_none(),
+        "full payload must not be inline"
+    );
+
+    let blob_id = output["blob"]["blob_id"].as_str().unwrap().to_owned();
+    assert_eq!(output["blob"]["content_type"], "application/json");
+    let stored = blobs
+        .get(BlobHash::from_hex(blob_id.clone()))
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&stored).unwrap(),
+        payload
+    );
+
+    assert_eq!(provider.request_count(), 2);
+    let history_text = provider.assistant_text_at(1);
+    assert!(
+        history_text.contains(&format!("truncated: {total_bytes} bytes total")),
+        "history must mark the truncation; got: {history_text}"
+    );
+    assert!(
+        history_text.contains("blob.read") && history_text.contains(&blob_id),
+        "history must say how to page the blob; got: {history_text}"
+    );
+}
+
+#[tokio::test]
+async fn spilled_output_is_redacted_before_it_is_stored_or_previewed() {
+    let secret = "sk-live0123456789abcdefXYZ";
+    let log = format!("token {secret}\n{}", "line of build output\n".repeat(200));
+    let payload = serde_json::json!({ "content": log });
+    let blobs = Arc::new(MemBlobStore::default());
+    let (runtime, _provider, events) = build_runtime(payload, Some(blobs.clone()));
+    let runtime =
+        runtime.with_redactor(Redactor::new(RedactionConfig::default()).expect("redactor"));
+
+    run_two_ticks(&runtime).await;
+
+    let output = completed_output(&events);
+    let preview = output["preview"].as_str().unwrap();
+    assert!(preview.contains("[REDACTED:"), "{preview}");
+    assert!(!preview.contains(secret), "{preview}");
+
+    let blob_id = output["blob"]["blob_id"].as_str().unwrap().to_owned();
+    let stored = blobs.get(BlobHash::from_hex(blob_id)).await.unwrap();
+    let stored = String::from_utf8(stored.to_vec()).unwrap();
+    assert!(!stored.contains(secret));
+    assert_eq!(output["total_bytes"], serde_json::json!(stored.len()));
+}
+
+#[tokio::test]
+async fn small_output_stays_inline() {
+    let payload = serde_json::json!({ "path": "artifacts/log.txt", "content": "ok" });
+    let blobs = Arc::new(MemBlobStore::default());
+    let (runtime, _provider, events) = build_runtime(payload.clone(), Some(blobs.clone()));
+
+    run_two_ticks(&runtime).await;
+
+    assert_eq!(completed_output(&events), payload);
+    assert!(blobs.blobs.lock().is_empty());
+}
+
+#[tokio::test]
+async fn output_stays_inline_without_a_blob_store() {
+    let payload = serde_json::json!({ "content": "x".repeat(4096) });
+    let (runtime, _provider, events) = build_runtime(payload.clone(), None);
+
+    run_two_ticks(&runtime).await;
+
+    assert_eq!(completed_output(&events), payload);
+}
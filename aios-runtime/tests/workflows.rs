@@ -0,0 +1,432 @@
+//! Declarative workflows on the runtime's built-in dispatcher.
+//!
+//! `TickKind::Workflow` ticks with no external dispatcher registered run
+//! YAML/JSON workflows from `<root>/workflows`: model steps, policy-gated
+//! tool steps, conditionals and bounded loops, each journaled as a
+//! `workflow.step` event. Model and tool steps take the same routing,
+//! gating and journaling paths as a direct tick's calls.
+
+use std::sync::Arc;
+
+use aios_protocol::{
+    BranchId, Capability, EventKind, EventRecord, KernelError, KernelResult, ModelCompletion,
+    ModelCompletionRequest, ModelDirective, ModelProviderPort, ModelRouting, ModelStopReason,
+    OperatingMode, PolicyGateDecision, PolicyGatePort, SessionId, ToolCall, ToolExecutionReport,
+    ToolExecutionRequest, ToolHarnessPort, ToolOutcome, ToolRunId,
+};
+use aios_runtime::{
+    DeclarativeWorkflowDispatcher, KernelRuntime, TickInput, TickKind, TickOutput,
+    WorkflowDefinition,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+mod common;
+
+// ── Scripted provider ─────────────────────────────────────────────────
+//
+// Classification prompts get a fenced JSON verdict, the way models tend to
+// answer; anything else is echoed back. The `unavailable` model always
+// fails.
+
+struct ClassifyingProvider;
+
+#[async_trait]
+impl ModelProviderPort for ClassifyingProvider {
+    async fn complete(&self, request: ModelCompletionRequest) -> KernelResult<ModelCompletion> {
+        assert_eq!(request.allowed_tools.as_deref(), Some(&[][..]));
+        if request.model.as_deref() == Some("unavailable") {
+            return Err(KernelError::InvalidState("unknown model".to_owned()));
+        }
+        let text = if request.objective.starts_with("classify:") {
+            let severity = if request.objective.contains("outage") {
+                "high"
+            } else {
+                "low"
+            };
+            format!("```json\n{{\"severity\": \"{severity}\"}}\n```")
+        } else {
+            request.objective.clone()
+        };
+        Ok(ModelCompletion {
+            provider: "scripted".to_owned(),
+            model: "scripted-deterministic".to_owned(),
+            llm_call_record: None,
+            directives: vec![ModelDirective::Message {
+                role: "assistant".to_owned(),
+                content: text.clone(),
+            }],
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some(text),
+        })
+    }
+}
+
+// ── Harness and policy gate ───────────────────────────────────────────
+
+/// `probe` reports ready from its third attempt on; every other tool
+/// succeeds. Records each call it runs.
+#[derive(Default)]
+struct RecordingHarness {
+    calls: Mutex<Vec<ToolCall>>,
+}
+
+#[async_trait]
+impl ToolHarnessPort for RecordingHarness {
+    async fn execute(&self, request: ToolExecutionRequest) -> KernelResult<ToolExecutionReport> {
+        let output = match request.call.tool_name.as_str() {
+            "probe" => serde_json::json!({
+                "ready": request.call.input["attempt"].as_u64() >= Some(2),
+            }),
+            _ => serde_json::json!({ "sent": true }),
+        };
+        self.calls.lock().push(request.call.clone());
+        Ok(ToolExecutionReport {
+            tool_run_id: ToolRunId::default(),
+            call_id: request.call.call_id,
+            tool_name: request.call.tool_name,
+            exit_status: 0,
+            duration_ms: 0,
+            outcome: ToolOutcome::Success { output },
+        })
+    }
+}
+
+/// Denies network egress, allows everything else.
+struct NoNetworkGate;
+
+#[async_trait]
+impl PolicyGatePort for NoNetworkGate {
+    async fn evaluate(
+        &self,
+        _session_id: SessionId,
+        requested: Vec<Capability>,
+    ) -> KernelResult<PolicyGateDecision> {
+        let (denied, allowed) = requested
+            .into_iter()
+            .partition(|capability| capability.as_str().starts_with("net:"));
+        Ok(PolicyGateDecision::new(allowed, Vec::new(), denied))
+    }
+}
+
+// ── Wiring ────────────────────────────────────────────────────────────
+
+const TRIAGE: &str = r#"
+name: triage
+input_schema:
+  type: object
+  required: [report]
+  properties:
+    report: { type: string }
+output_schema:
+  type: object
+  required: [severity, attempts]
+steps:
+  - id: classify
+    model:
+      prompt: "classify: {{input.report}}"
+      json: true
+      schema:
+        type: object
+        required: [severity]
+        properties:
+          severity: { enum: [low, high] }
+  - id: escalate
+    if:
+      when: { path: steps.classify.severity, equals: high }
+      then:
+        - id: notify
+          tool:
+            name: notify
+            input: { text: "{{input.report}}", severity: "{{steps.classify.severity}}" }
+            capabilities: ["fs:write:/session/artifacts/**"]
+  - id: retry
+    loop:
+      max_iterations: 5
+      until: { path: steps.probe.ready }
+      steps:
+        - id: probe
+          tool:
+            name: probe
+            input: { attempt: "{{loop.iteration}}" }
+output:
+  severity: "{{steps.classify.severity}}"
+  attempts: "{{steps.retry.iterations}}"
+"#;
+
+type Fixture = common::Fixture<ClassifyingProvider, RecordingHarness>;
+
+impl Fixture {
+    async fn new(wire: impl FnOnce(KernelRuntime) -> KernelRuntime) -> Self {
+        Self::with_routing(ModelRouting::default(), wire).await
+    }
+
+    async fn with_routing(
+        routing: ModelRouting,
+        wire: impl FnOnce(KernelRuntime) -> KernelRuntime,
+    ) -> Self {
+        let fixture = Fixture::builder(
+            "workflows",
+            ClassifyingProvider,
+            RecordingHarness::default(),
+        )
+        .gate(NoNetworkGate)
+        .routing(routing)
+        .build_with(wire)
+        .await;
+        // Workflow files are read when a tick names them.
+        std::fs::create_dir_all(fixture.root.join("workflows")).expect("create workflow dir");
+        std::fs::write(fixture.root.join("workflows/triage.yaml"), TRIAGE).expect("write workflow");
+        fixture
+    }
+
+    async fn run(&self, name: &str, input: serde_json::Value) -> TickOutput {
+        self.runtime
+            .tick_on_branch(
+                &self.session,
+                &BranchId::main(),
+                TickInput {
+                    objective: "triage the report".to_owned(),
+                    proposed_tool: None,
+                    system_prompt: None,
+                    allowed_tools: None,
+                    client_tools: Vec::new(),
+                    kind: TickKind::Workflow {
+                        name: name.to_owned(),
+                        input,
+                    },
+                },
+            )
+            .await
+            .expect("workflow tick")
+    }
+
+    fn custom_events(&self, event_type: &str) -> Vec<(EventRecord, serde_json::Value)> {
+        self.store
+            .events
+            .lock()
+            .iter()
+            .filter_map(|record| match &record.kind {
+                EventKind::Custom {
+                    event_type: kind,
+                    data,
+                } if kind == event_type => Some((record.clone(), data.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn run_error(&self) -> Option<String> {
+        self.store
+            .events
+            .lock()
+            .iter()
+            .find_map(|record| match &record.kind {
+                EventKind::RunErrored { error } => Some(error.clone()),
+                _ => None,
+            })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn yaml_workflow_from_the_workflow_directory_runs_every_step_kind() {
+    let fixture = Fixture::new(|runtime| runtime).await;
+    assert!(!fixture.runtime.has_workflow_dispatcher());
+
+    let output = fixture
+        .run(
+            "triage",
+            serde_json::json!({ "report": "checkout outage in eu-west" }),
+        )
+        .await;
+    assert_eq!(fixture.run_error(), None);
+
+    let (_, result) = fixture
+        .custom_events("ergon.workflow_output")
+        .pop()
+        .expect("workflow output journaled");
+    assert_eq!(
+        result["output"],
+        serde_json::json!({ "severity": "high", "attempts": 3 })
+    );
+
+    let calls = fixture.harness.calls.lock().clone();
+    let names: Vec<_> = calls.iter().map(|call| call.tool_name.as_str()).collect();
+    assert_eq!(names, ["notify", "probe", "probe", "probe"]);
+    assert_eq!(
+        calls[0].input,
+        serde_json::json!({ "text": "checkout outage in eu-west", "severity": "high" })
+    );
+    assert_eq!(calls[3].input, serde_json::json!({ "attempt": 2 }));
+
+    let steps = fixture.custom_events("workflow.step");
+    let journaled: Vec<_> = steps
+        .iter()
+        .map(|(_, data)| data["step"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        journaled,
+        ["classify", "escalate", "notify", "probe", "probe", "probe"]
+    );
+    assert_eq!(steps[1].1["branch"], true);
+    assert_eq!(steps[5].1["iteration"], 2);
+    let run_id = steps[0].0.correlation_id.clone().expect("run correlation");
+    assert!(
+        steps
+            .iter()
+            .all(|(record, _)| record.correlation_id.as_ref() == Some(&run_id))
+    );
+
+    // Step events are interleaved with the runtime's own without sequence
+    // collisions.
+    let mut sequences: Vec<_> = fixture
+        .store
+        .events
+        .lock()
+        .iter()
+        .map(|record| record.sequence)
+        .collect();
+    let total = sequences.len();
+    sequences.sort_unstable();
+    sequences.dedup();
+    assert_eq!(sequences.len(), total);
+    assert_eq!(output.last_sequence, *sequences.last().unwrap());
+}
+
+#[tokio::test]
+async fn steps_take_the_kernel_model_and_tool_paths() {
+    let fixture = Fixture::with_routing(
+        ModelRouting {
+            primary_model: "unavailable".to_owned(),
+            fallback_models: vec!["scripted".to_owned()],
+            temperature: 0.2,
+        },
+        |runtime| runtime,
+    )
+    .await;
+
+    fixture
+        .run(
+            "triage",
+            serde_json::json!({ "report": "checkout outage in eu-west" }),
+        )
+        .await;
+    assert_eq!(fixture.run_error(), None);
+
+    // The classify step fell back past the failing primary model.
+    let attempts: Vec<_> = fixture
+        .custom_events("model.attempt")
+        .into_iter()
+        .map(|(_, data)| (data["model"].clone(), data["ok"].clone()))
+        .collect();
+    assert_eq!(
+        attempts,
+        [
+            (serde_json::json!("unavailable"), serde_json::json!(false)),
+            (serde_json::json!("scripted"), serde_json::json!(true)),
+        ]
+    );
+
+    // Every tool step is requested and completed in the journal, tagged
+    // with its call id.
+    let events = fixture.store.events.lock().clone();
+    let requested: Vec<_> = events
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::ToolCallRequested { call_id, .. } => Some(call_id.clone()),
+            _ => None,
+        })
+        .collect();
+    let completed: Vec<_> = events
+        .iter()
+        .filter_map(|record| match &record.kind {
+            EventKind::ToolCallCompleted {
+                call_id: Some(call_id),
+                ..
+            } => Some(call_id.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(requested.len(), 4);
+    assert_eq!(requested, completed);
+    assert!(requested[0].ends_with(":notify"), "{requested:?}");
+}
+
+#[tokio::test]
+async fn input_that_violates_the_schema_fails_the_run_before_any_step() {
+    let fixture = Fixture::new(|runtime| runtime).await;
+
+    let output = fixture
+        .run("triage", serde_json::json!({ "report": 42 }))
+        .await;
+
+    assert_eq!(output.mode, OperatingMode::Recover);
+    let error = fixture.run_error().expect("run errored");
+    assert!(error.contains("input.report: expected string"), "{error}");
+    assert!(fixture.custom_events("workflow.step").is_empty());
+    assert!(fixture.harness.calls.lock().is_empty());
+}
+
+#[tokio::test]
+async fn denied_tool_step_fails_the_run_and_is_journaled() {
+    let definition = WorkflowDefinition::from_json(
+        r#"{
+            "name": "fetch",
+            "steps": [{
+                "id": "download",
+                "tool": {
+                    "name": "http.get",
+                    "input": { "url": "{{input.url}}" },
+                    "capabilities": ["net:egress:example.com"]
+                }
+            }]
+        }"#,
+    )
+    .expect("valid workflow");
+    let fixture = Fixture::new(|runtime| {
+        runtime.with_workflow_dispatcher(Arc::new(
+            DeclarativeWorkflowDispatcher::new().with_workflow(definition),
+        ))
+    })
+    .await;
+
+    let output = fixture
+        .run("fetch", serde_json::json!({ "url": "https://example.com" }))
+        .await;
+
+    assert_eq!(output.mode, OperatingMode::Recover);
+    let error = fixture.run_error().expect("run errored");
+    assert!(error.contains("capabilities denied"), "{error}");
+    let steps = fixture.custom_events("workflow.step");
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].1["status"], "error");
+    assert!(fixture.harness.calls.lock().is_empty());
+    assert!(fixture.store.events.lock().iter().any(|record| matches!(
+        &record.kind,
+        EventKind::ToolCallFailed { tool_name, .. } if tool_name == "http.get"
+    )));
+}
+
+#[tokio::test]
+async fn unknown_workflows_and_malformed_definitions_are_rejected() {
+    let fixture = Fixture::new(|runtime| runtime).await;
+    fixture.run("../triage", serde_json::json!({})).await;
+    let error = fixture.run_error().expect("run errored");
+    assert!(error.contains("invalid workflow name"), "{error}");
+
+    let duplicate = r#"{"name":"w","steps":[
+        {"id":"a","model":{"prompt":"x"}},
+        {"id":"a","model":{"prompt":"y"}}]}"#;
+    assert!(WorkflowDefinition::from_json(duplicate).is_err());
+
+    let unbounded = r#"{"name":"w","steps":[
+        {"id":"l","loop":{"max_iterations":0,"steps":[{"id":"m","model":{"prompt":"x"}}]}}]}"#;
+    assert!(WorkflowDefinition::from_json(unbounded).is_err());
+
+    let schema_without_json =
+        "name: w\nsteps:\n  - id: m\n    model: { prompt: x, schema: { type: object } }\n";
+    assert!(WorkflowDefinition::from_yaml(schema_without_json).is_err());
+}
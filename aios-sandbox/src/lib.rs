@@ -44,6 +44,10 @@ pub struct SandboxExecution {
     pub stdout: String,
     pub stderr: String,
     pub timed_out: bool,
+    /// Whether stdout or stderr exceeded `SandboxLimits.max_output_bytes`
+    /// and was cut. The cut stream ends with a marker line saying so.
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 #[async_trait]
@@ -65,12 +69,19 @@ impl LocalSandboxRunner {
         self.allowed_commands.is_empty() || self.allowed_commands.iter().any(|c| c == command)
     }
 
-    fn truncate(output: Vec<u8>, max_output_bytes: usize) -> String {
-        let mut output = output;
-        if output.len() > max_output_bytes {
-            output.truncate(max_output_bytes);
+    /// Cut `output` to `max_output_bytes`, returning the text and whether
+    /// anything was dropped. A cut stream gets a trailing marker so the
+    /// model never mistakes a prefix for the whole output.
+    fn truncate(output: Vec<u8>, max_output_bytes: usize) -> (String, bool) {
+        let total = output.len();
+        if total <= max_output_bytes {
+            return (String::from_utf8_lossy(&output).into_owned(), false);
         }
-        String::from_utf8_lossy(&output).into_owned()
+        let mut text = String::from_utf8_lossy(&output[..max_output_bytes]).into_owned();
+        text.push_str(&format!(
+            "\n[output truncated: {max_output_bytes} of {total} bytes shown]"
+        ));
+        (text, true)
     }
 }
 
@@ -104,8 +115,10 @@ impl SandboxRunner for LocalSandboxRunner {
             Ok(output_result) => {
                 let output = output_result?;
                 let ended_at = Utc::now();
-                let stdout = Self::truncate(output.stdout, request.limits.max_output_bytes);
-                let stderr = Self::truncate(output.stderr, request.limits.max_output_bytes);
+                let (stdout, stdout_truncated) =
+                    Self::truncate(output.stdout, request.limits.max_output_bytes);
+                let (stderr, stderr_truncated) =
+                    Self::truncate(output.stderr, request.limits.max_output_bytes);
                 let execution = SandboxExecution {
                     started_at,
                     ended_at,
@@ -114,6 +127,7 @@ impl SandboxRunner for LocalSandboxRunner {
                     stdout,
                     stderr,
                     timed_out: false,
+                    truncated: stdout_truncated || stderr_truncated,
                 };
                 debug!(
                     exit_code = execution.exit_code,
@@ -139,6 +153,7 @@ impl SandboxRunner for LocalSandboxRunner {
                         request.limits.max_runtime_secs
                     ),
                     timed_out: true,
+                    truncated: false,
                 })
             }
         }
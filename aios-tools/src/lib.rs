@@ -4,7 +4,10 @@ use std::sync::Arc;
 
 use aios_policy::{PolicyEngine, PolicyEvaluation};
 use aios_protocol::KernelError;
-use aios_protocol::{Capability, SessionId, ToolCall, ToolOutcome, ToolRunId};
+use aios_protocol::ports::BlobStorePort;
+use aios_protocol::{
+    BlobHash, Capability, SessionId, ToolAnnotations, ToolCall, ToolOutcome, ToolRunId,
+};
 use aios_protocol::{
     ToolExecutionReport as PortToolExecutionReport, ToolExecutionRequest, ToolHarnessPort,
 };
@@ -21,14 +24,72 @@ pub enum ToolKind {
     FsRead,
     FsWrite,
     ShellExec,
+    BlobRead,
+}
+
+/// Root the workspace-relative paths of the filesystem tools are mapped
+/// under in capabilities.
+const SESSION_ROOT: &str = "/session";
+
+impl ToolKind {
+    /// The concrete capabilities a call with `input` exercises:
+    /// `fs:read:/session/<path>`, `fs:write:/session/<path>` or
+    /// `exec:cmd:<command>`. `None` for kinds whose input names no
+    /// capability resource.
+    pub fn derive_capabilities(&self, input: &Value) -> Result<Option<Vec<Capability>>> {
+        let capability = match self {
+            Self::FsRead => Capability::fs_read(&session_path(input, "fs.read")?),
+            Self::FsWrite => Capability::fs_write(&session_path(input, "fs.write")?),
+            Self::ShellExec => {
+                let command = input
+                    .get("command")
+                    .and_then(Value::as_str)
+                    .context("shell.exec requires input.command")?;
+                // `/bin/rm` or `rm -rf` would slip past a rule on `rm`.
+                if command.is_empty()
+                    || command.contains('/')
+                    || command.contains(char::is_whitespace)
+                {
+                    bail!("shell.exec input.command must be a bare program name, got {command:?}");
+                }
+                Capability::exec(command)
+            }
+            Self::BlobRead => return Ok(None),
+        };
+        let spec = capability
+            .parse()
+            .with_context(|| format!("invalid capability {}", capability.as_str()))?;
+        if !spec.is_exact() {
+            bail!(
+                "tool input must name a single resource, not the pattern {}",
+                capability.as_str()
+            );
+        }
+        Ok(Some(vec![spec.to_capability()]))
+    }
+}
+
+fn session_path(input: &Value, tool: &str) -> Result<String> {
+    let path = input
+        .get("path")
+        .and_then(Value::as_str)
+        .with_context(|| format!("{tool} requires input.path"))?;
+    Ok(format!("{SESSION_ROOT}/{}", path.trim_start_matches('/')))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
+    /// The widest capabilities the tool may exercise. Calls are evaluated
+    /// against the capabilities derived from their input instead, except
+    /// for kinds that cannot derive any.
     pub required_capabilities: Vec<Capability>,
     pub kind: ToolKind,
+    /// Behavioral hints surfaced to the runtime. Read-only / idempotent
+    /// tools may be dispatched concurrently within one completion.
+    #[serde(default)]
+    pub annotations: ToolAnnotations,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -36,6 +97,32 @@ pub struct ToolRegistry {
     tools: HashMap<String, ToolDefinition>,
 }
 
+impl ToolDefinition {
+    /// Capabilities a call with `input` is evaluated against: those derived
+    /// from the input, or the static declaration when the kind derives none.
+    pub fn capabilities_for(&self, input: &Value) -> Result<Vec<Capability>> {
+        Ok(self
+            .kind
+            .derive_capabilities(input)?
+            .unwrap_or_else(|| self.required_capabilities.clone()))
+    }
+
+    /// [`Self::capabilities_for`] the call's input plus whatever the caller
+    /// claims. The policy must pass every one of them, so a claim adds to
+    /// what the call is checked against and never replaces the derived
+    /// capabilities; an input that derives a pattern rather than an exact
+    /// resource rejects the call.
+    pub fn call_capabilities(&self, call: &ToolCall) -> Result<Vec<Capability>> {
+        let mut capabilities = self.capabilities_for(&call.input)?;
+        for claimed in &call.requested_capabilities {
+            if !capabilities.contains(claimed) {
+                capabilities.push(claimed.clone());
+            }
+        }
+        Ok(capabilities)
+    }
+}
+
 impl ToolRegistry {
     pub fn register(&mut self, definition: ToolDefinition) {
         self.tools.insert(definition.name.clone(), definition);
@@ -49,6 +136,15 @@ impl ToolRegistry {
         self.tools.values()
     }
 
+    /// Annotations for every registered tool, keyed by tool name. Fed to
+    /// `KernelRuntime::with_tool_annotations` so the runtime can decide
+    /// which calls are safe to run concurrently.
+    pub fn annotations(&self) -> impl Iterator<Item = (String, ToolAnnotations)> + '_ {
+        self.tools
+            .values()
+            .map(|definition| (definition.name.clone(), definition.annotations.clone()))
+    }
+
     pub fn with_core_tools() -> Self {
         let mut registry = Self::default();
 
@@ -57,6 +153,11 @@ impl ToolRegistry {
             description: "Read a UTF-8 text file from the session workspace".to_owned(),
             required_capabilities: vec![Capability::fs_read("/session/**")],
             kind: ToolKind::FsRead,
+            annotations: ToolAnnotations {
+                read_only: true,
+                idempotent: true,
+                ..ToolAnnotations::default()
+            },
         });
 
         registry.register(ToolDefinition {
@@ -64,6 +165,10 @@ impl ToolRegistry {
             description: "Write a UTF-8 text file to the session workspace".to_owned(),
             required_capabilities: vec![Capability::fs_write("/session/artifacts/**")],
             kind: ToolKind::FsWrite,
+            annotations: ToolAnnotations {
+                destructive: true,
+                ..ToolAnnotations::default()
+            },
         });
 
         registry.register(ToolDefinition {
@@ -71,12 +176,37 @@ impl ToolRegistry {
             description: "Execute a constrained command through the sandbox runner".to_owned(),
             required_capabilities: vec![Capability::exec("*")],
             kind: ToolKind::ShellExec,
+            annotations: ToolAnnotations {
+                destructive: true,
+                open_world: true,
+                ..ToolAnnotations::default()
+            },
+        });
+
+        registry.register(ToolDefinition {
+            name: BLOB_READ_TOOL.to_owned(),
+            description: "Page through a tool output that was spilled to blob storage".to_owned(),
+            required_capabilities: vec![Capability::fs_read("/session/**")],
+            kind: ToolKind::BlobRead,
+            annotations: ToolAnnotations {
+                read_only: true,
+                idempotent: true,
+                ..ToolAnnotations::default()
+            },
         });
 
         registry
     }
 }
 
+pub use aios_protocol::blob::BLOB_READ_TOOL;
+
+/// Page size `blob.read` uses when the call gives no `length`.
+const BLOB_READ_DEFAULT_LENGTH: usize = 8 * 1024;
+
+/// Largest page `blob.read` returns in one call.
+const BLOB_READ_MAX_LENGTH: usize = 32 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct ToolContext {
     pub workspace_root: PathBuf,
@@ -105,6 +235,7 @@ pub struct ToolDispatcher {
     registry: Arc<ToolRegistry>,
     policy: Arc<dyn PolicyEngine>,
     sandbox: Arc<dyn SandboxRunner>,
+    blob_store: Option<Arc<dyn BlobStorePort>>,
 }
 
 impl ToolDispatcher {
@@ -117,9 +248,17 @@ impl ToolDispatcher {
             registry,
             policy,
             sandbox,
+            blob_store: None,
         }
     }
 
+    /// Serve `blob.read` from `blob_store`. Wire the same store the runtime
+    /// spills large tool outputs into; without one, `blob.read` fails.
+    pub fn with_blob_store(mut self, blob_store: Arc<dyn BlobStorePort>) -> Self {
+        self.blob_store = Some(blob_store);
+        self
+    }
+
     pub fn registry(&self) -> Arc<ToolRegistry> {
         self.registry.clone()
     }
@@ -144,8 +283,7 @@ impl ToolDispatcher {
             .with_context(|| format!("unknown tool: {}", call.tool_name))?
             .clone();
 
-        let mut requested_capabilities = definition.required_capabilities.clone();
-        requested_capabilities.extend(call.requested_capabilities.clone());
+        let requested_capabilities = definition.call_capabilities(&call)?;
 
         let evaluation = self
             .policy
@@ -172,7 +310,11 @@ impl ToolDispatcher {
         let (exit_status, outcome) = match definition.kind {
             ToolKind::FsRead => self.execute_fs_read(context, &call.input).await?,
             ToolKind::FsWrite => self.execute_fs_write(context, &call.input).await?,
-            ToolKind::ShellExec => self.execute_shell_exec(context, &call).await?,
+            ToolKind::ShellExec => {
+                self.execute_shell_exec(context, &call, requested_capabilities)
+                    .await?
+            }
+            ToolKind::BlobRead => self.execute_blob_read(&call.input).await?,
         };
         debug!(exit_status, "tool execution finished");
 
@@ -236,11 +378,12 @@ impl ToolDispatcher {
         ))
     }
 
-    #[instrument(skip(self, context, call), fields(tool = "shell.exec"))]
+    #[instrument(skip(self, context, call, capabilities), fields(tool = "shell.exec"))]
     async fn execute_shell_exec(
         &self,
         context: &ToolContext,
         call: &ToolCall,
+        capabilities: Vec<Capability>,
     ) -> Result<(i32, ToolOutcome)> {
         let command = call
             .input
@@ -267,7 +410,7 @@ impl ToolDispatcher {
                 args,
                 cwd: context.workspace_root.clone(),
                 env: Default::default(),
-                required_capabilities: call.requested_capabilities.clone(),
+                required_capabilities: capabilities,
                 limits: SandboxLimits::default(),
             })
             .await?;
@@ -279,6 +422,7 @@ impl ToolDispatcher {
                     "stderr": execution.stderr,
                     "duration_ms": execution.duration_ms,
                     "timed_out": execution.timed_out,
+                    "truncated": execution.truncated,
                 }),
             }
         } else {
@@ -292,6 +436,47 @@ impl ToolDispatcher {
 
         Ok((execution.exit_code, outcome))
     }
+
+    #[instrument(skip(self, input), fields(tool = "blob.read"))]
+    async fn execute_blob_read(&self, input: &Value) -> Result<(i32, ToolOutcome)> {
+        let blob_store = self
+            .blob_store
+            .as_ref()
+            .context("blob.read is unavailable: no blob store configured")?;
+        let blob_id = input
+            .get("blob_id")
+            .and_then(Value::as_str)
+            .context("blob.read requires input.blob_id")?;
+        let offset = input.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let length = input
+            .get("length")
+            .and_then(Value::as_u64)
+            .map_or(BLOB_READ_DEFAULT_LENGTH, |length| length as usize)
+            .clamp(1, BLOB_READ_MAX_LENGTH);
+
+        let payload = blob_store
+            .get(BlobHash::from_hex(blob_id))
+            .await
+            .map_err(|error| anyhow::anyhow!(error.to_string()))?;
+        let total = payload.len();
+        let start = offset.min(total);
+        let end = start.saturating_add(length).min(total);
+        let content = String::from_utf8_lossy(&payload[start..end]).into_owned();
+
+        Ok((
+            0,
+            ToolOutcome::Success {
+                output: json!({
+                    "blob_id": blob_id,
+                    "offset": start,
+                    "length": end - start,
+                    "total_bytes": total,
+                    "next_offset": (end < total).then_some(end),
+                    "content": content,
+                }),
+            },
+        ))
+    }
 }
 
 fn canonical_session_path(root: &Path, relative_path: &str) -> Result<PathBuf> {
@@ -346,4 +531,83 @@ impl ToolHarnessPort for ToolDispatcher {
             )),
         }
     }
+
+    fn capabilities(&self, call: &ToolCall) -> std::result::Result<Vec<Capability>, KernelError> {
+        match self.registry.get(&call.tool_name) {
+            Some(definition) => definition.call_capabilities(call).map_err(to_kernel_error),
+            // Execution reports the unknown tool.
+            None => Ok(call.requested_capabilities.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derived(kind: ToolKind, input: Value) -> Result<Option<Vec<String>>> {
+        Ok(kind.derive_capabilities(&input)?.map(|capabilities| {
+            capabilities
+                .iter()
+                .map(|capability| capability.as_str().to_owned())
+                .collect()
+        }))
+    }
+
+    #[test]
+    fn paths_are_mapped_under_the_session_root_and_normalized() {
+        for (path, expected) in [
+            ("notes/a.md", "fs:read:/session/notes/a.md"),
+            ("/notes/a.md", "fs:read:/session/notes/a.md"),
+            ("notes/./drafts/../a.md", "fs:read:/session/notes/a.md"),
+        ] {
+            assert_eq!(
+                derived(ToolKind::FsRead, json!({ "path": path })).unwrap(),
+                Some(vec![expected.to_owned()]),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn dot_dot_out_of_the_session_is_resolved_or_rejected() {
+        assert_eq!(
+            derived(ToolKind::FsWrite, json!({ "path": "../etc/passwd" })).unwrap(),
+            Some(vec!["fs:write:/etc/passwd".to_owned()])
+        );
+        assert!(derived(ToolKind::FsWrite, json!({ "path": "../../../etc" })).is_err());
+    }
+
+    #[test]
+    fn paths_naming_a_pattern_are_rejected() {
+        for path in ["notes/*.md", "notes/**", "notes/{a,b}.md"] {
+            assert!(
+                derived(ToolKind::FsRead, json!({ "path": path })).is_err(),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn commands_must_be_bare_program_names() {
+        assert_eq!(
+            derived(ToolKind::ShellExec, json!({ "command": "ls" })).unwrap(),
+            Some(vec!["exec:cmd:ls".to_owned()])
+        );
+        for command in ["/bin/rm", "./rm", "rm -rf", "rm\t", ""] {
+            assert!(
+                derived(ToolKind::ShellExec, json!({ "command": command })).is_err(),
+                "{command:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn kinds_without_a_resource_derive_nothing() {
+        assert_eq!(
+            derived(ToolKind::BlobRead, json!({ "hash": "abc" })).unwrap(),
+            None
+        );
+        assert!(derived(ToolKind::FsRead, json!({})).is_err());
+    }
 }
@@ -1,14 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use aios_kernel::{AiosKernel, KernelBuilder};
+use aios_kernel::{
+    AiosKernel, BaselineModelProvider, KernelBuilder, PromptInjectionGuard, RedactionConfig,
+    Redactor, TickConcurrency, TickInProgress,
+};
 use aios_protocol::{
-    AgentStateVector, BranchId, BranchInfo, BranchMergeResult, Capability, EventKind, EventRecord,
-    ModelRouting, OperatingMode, PolicySet, SessionId, SessionManifest, ToolCall,
+    AgentStateVector, BranchId, BranchInfo, BranchMergeResult, CanonicalState, Capability,
+    EventKind, EventRecord, ModelRouting, OperatingMode, PolicySet, SessionId, SessionManifest,
+    ToolCall,
 };
 use anyhow::Result;
 use async_stream::stream;
@@ -37,11 +41,14 @@ mod voice;
 
 use crate::openapi::{openapi_spec, scalar_docs_html};
 use crate::vercel_v6::{
-    VERCEL_AI_SDK_V6_STREAM_HEADER, VERCEL_AI_SDK_V6_STREAM_VERSION, kernel_event_parts,
-    part_as_sse_event,
+    VERCEL_AI_SDK_V6_STREAM_HEADER, VERCEL_AI_SDK_V6_STREAM_VERSION, ephemeral_event_part,
+    kernel_event_parts, part_as_sse_event,
 };
 use crate::voice::{PersonaplexProcessContract, StubPersonaplexAdapter, VoiceSessionConfig};
 
+/// Events fetched per journal read when a handler has to scan a branch.
+const EVENT_SCAN_PAGE: usize = 1_000;
+
 #[derive(Debug, Parser)]
 #[command(name = "aios-api")]
 #[command(about = "aiOS control-plane API")]
@@ -50,6 +57,10 @@ struct Cli {
     root: PathBuf,
     #[arg(long, default_value = "127.0.0.1:8787")]
     listen: SocketAddr,
+    /// Handling of a tick that arrives while another runs on the same
+    /// session/branch: `queue`, `reject` (409) or `coalesce`.
+    #[arg(long, default_value = "queue")]
+    tick_concurrency: TickConcurrency,
 }
 
 #[derive(Clone)]
@@ -114,6 +125,8 @@ struct EventListQuery {
     branch: Option<String>,
     from_sequence: Option<u64>,
     limit: Option<usize>,
+    /// Only return events with this `correlation_id` (a tick's run id).
+    correlation_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -124,6 +137,40 @@ struct EventListResponse {
     events: Vec<EventRecord>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct CanonicalStateQuery {
+    branch: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CanonicalStateResponse {
+    session_id: SessionId,
+    branch: BranchId,
+    version: u64,
+    state: CanonicalState,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CausalTreeQuery {
+    branch: Option<String>,
+}
+
+/// An event and, recursively, the events that name it as their cause.
+#[derive(Debug, Serialize)]
+struct CausalNode {
+    event: EventRecord,
+    children: Vec<CausalNode>,
+}
+
+#[derive(Debug, Serialize)]
+struct CausalTreeResponse {
+    session_id: SessionId,
+    branch: BranchId,
+    /// Causation chain above the requested event, root cause first.
+    ancestors: Vec<EventRecord>,
+    tree: CausalNode,
+}
+
 #[derive(Debug, Serialize)]
 struct BranchListResponse {
     session_id: SessionId,
@@ -187,6 +234,20 @@ impl ApiError {
         }
     }
 
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+
+    fn conflict(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::CONFLICT,
+            message: message.into(),
+        }
+    }
+
     fn internal(error: impl std::fmt::Display) -> Self {
         Self {
             status: StatusCode::INTERNAL_SERVER_ERROR,
@@ -211,7 +272,12 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
-    let kernel = KernelBuilder::new(&cli.root).build();
+    let kernel = KernelBuilder::new(&cli.root)
+        .tick_concurrency(cli.tick_concurrency)
+        .streaming_provider(Some(Arc::new(BaselineModelProvider)))
+        .redactor(Some(Redactor::new(RedactionConfig::default())?))
+        .prompt_injection_guard(Some(PromptInjectionGuard::default()))
+        .build();
     let voice_adapter = StubPersonaplexAdapter::new(PersonaplexProcessContract::default());
 
     let state = AppState {
@@ -236,6 +302,11 @@ async fn main() -> Result<()> {
             post(merge_branch),
         )
         .route("/sessions/{session_id}/events", get(list_events))
+        .route(
+            "/sessions/{session_id}/events/{event_id}/causal-tree",
+            get(causal_tree),
+        )
+        .route("/sessions/{session_id}/state", get(canonical_state))
         .route("/sessions/{session_id}/events/stream", get(stream_events))
         .route(
             "/sessions/{session_id}/events/stream/vercel-ai-sdk-v6",
@@ -318,7 +389,10 @@ async fn tick_session(
             }),
         )
         .await
-        .map_err(ApiError::internal)?;
+        .map_err(|error| match error.downcast_ref::<TickInProgress>() {
+            Some(in_progress) => ApiError::conflict(in_progress.to_string()),
+            None => ApiError::internal(error),
+        })?;
 
     Ok(Json(TickResponse {
         session_id: result.session_id,
@@ -395,11 +469,17 @@ async fn list_events(
     let from_sequence = query.from_sequence.unwrap_or(1).max(1);
     let limit = query.limit.unwrap_or(200).clamp(1, 5000);
 
-    let events = state
-        .kernel
-        .read_events_on_branch(&session_id, &branch_id, from_sequence, limit)
-        .await
-        .map_err(ApiError::internal)?;
+    let correlation_id = query.correlation_id.as_deref();
+    let events = scan_events(
+        &state.kernel,
+        &session_id,
+        &branch_id,
+        from_sequence,
+        limit,
+        |event| correlation_id.is_none_or(|id| event.correlation_id.as_deref() == Some(id)),
+    )
+    .await
+    .map_err(ApiError::internal)?;
 
     Ok(Json(EventListResponse {
         session_id,
@@ -409,6 +489,151 @@ async fn list_events(
     }))
 }
 
+async fn canonical_state(
+    Path(session_id): Path<String>,
+    Query(query): Query<CanonicalStateQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<CanonicalStateResponse>> {
+    let session_id = parse_session_id(&session_id)?;
+    let branch_id = parse_branch_id(query.branch.as_deref())?;
+
+    let canonical = state
+        .kernel
+        .canonical_state(&session_id, &branch_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(CanonicalStateResponse {
+        session_id,
+        branch: branch_id,
+        version: canonical.version,
+        state: canonical.state,
+    }))
+}
+
+async fn causal_tree(
+    Path((session_id, event_id)): Path<(String, String)>,
+    Query(query): Query<CausalTreeQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<CausalTreeResponse>> {
+    let session_id = parse_session_id(&session_id)?;
+    let branch_id = parse_branch_id(query.branch.as_deref())?;
+
+    let events = scan_events(
+        &state.kernel,
+        &session_id,
+        &branch_id,
+        1,
+        usize::MAX,
+        |_| true,
+    )
+    .await
+    .map_err(ApiError::internal)?;
+    let (ancestors, tree) = build_causal_tree(events, &event_id)
+        .ok_or_else(|| ApiError::not_found(format!("event not found: {event_id}")))?;
+
+    Ok(Json(CausalTreeResponse {
+        session_id,
+        branch: branch_id,
+        ancestors,
+        tree,
+    }))
+}
+
+/// Up to `limit` events from `from_sequence` on that satisfy `keep`, read
+/// page by page so filtering happens before the limit is applied.
+async fn scan_events(
+    kernel: &AiosKernel,
+    session_id: &SessionId,
+    branch_id: &BranchId,
+    from_sequence: u64,
+    limit: usize,
+    keep: impl Fn(&EventRecord) -> bool,
+) -> Result<Vec<EventRecord>> {
+    let mut events = Vec::new();
+    let mut next = from_sequence;
+    while events.len() < limit {
+        let page = kernel
+            .read_events_on_branch(session_id, branch_id, next, EVENT_SCAN_PAGE)
+            .await?;
+        let exhausted = page.len() < EVENT_SCAN_PAGE;
+        if let Some(last) = page.last() {
+            next = last.sequence.saturating_add(1);
+        }
+        events.extend(page.into_iter().filter(|event| keep(event)));
+        if exhausted {
+            break;
+        }
+    }
+    events.truncate(limit);
+    Ok(events)
+}
+
+/// Causation chain above `event_id` (root first) and the subtree of
+/// events caused by it, or `None` when the event is not in `events`.
+fn build_causal_tree(
+    events: Vec<EventRecord>,
+    event_id: &str,
+) -> Option<(Vec<EventRecord>, CausalNode)> {
+    let mut by_id: HashMap<String, EventRecord> = HashMap::new();
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    for event in events {
+        if let Some(cause) = &event.causation_id {
+            children
+                .entry(cause.as_str().to_owned())
+                .or_default()
+                .push(event.event_id.as_str().to_owned());
+        }
+        by_id.insert(event.event_id.as_str().to_owned(), event);
+    }
+
+    let mut ancestors = Vec::new();
+    let mut cursor = by_id.get(event_id)?.causation_id.clone();
+    while let Some(cause) = cursor {
+        // Stop on a dangling pointer or a (corrupt) cycle.
+        let Some(parent) = by_id.get(cause.as_str()) else {
+            break;
+        };
+        if parent.event_id.as_str() == event_id
+            || ancestors
+                .iter()
+                .any(|seen: &EventRecord| seen.event_id == parent.event_id)
+        {
+            break;
+        }
+        cursor = parent.causation_id.clone();
+        ancestors.push(parent.clone());
+    }
+    ancestors.reverse();
+
+    fn subtree(
+        id: &str,
+        by_id: &HashMap<String, EventRecord>,
+        children: &HashMap<String, Vec<String>>,
+        visited: &mut HashSet<String>,
+    ) -> Option<CausalNode> {
+        // A (corrupt) causation cycle would otherwise recurse forever.
+        if !visited.insert(id.to_owned()) {
+            return None;
+        }
+        let event = by_id.get(id)?.clone();
+        let mut nodes: Vec<CausalNode> = children
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| subtree(child, by_id, children, visited))
+            .collect();
+        nodes.sort_by_key(|node| node.event.sequence);
+        Some(CausalNode {
+            event,
+            children: nodes,
+        })
+    }
+
+    let tree = subtree(event_id, &by_id, &children, &mut HashSet::new())?;
+    Some((ancestors, tree))
+}
+
 fn replay_start_sequence(cursor: Option<u64>) -> u64 {
     cursor.map_or(1, |value| value.saturating_add(1))
 }
@@ -482,6 +707,15 @@ async fn stream_events(
         loop {
             match subscription.recv().await {
                 Ok(event) => {
+                    // Ephemeral records (streamed text deltas) have no
+                    // journal slot: forward them live, outside the
+                    // sequence/backfill bookkeeping.
+                    if event.is_ephemeral() {
+                        if event.session_id == session_id_stream && event.branch_id == branch_id {
+                            yield Ok(as_ephemeral_sse_event(&event));
+                        }
+                        continue;
+                    }
                     if event.session_id != session_id_stream
                         || event.branch_id != branch_id
                         || event.sequence < expected_sequence
@@ -623,6 +857,12 @@ async fn stream_events_vercel_ai_sdk_v6(
         loop {
             match subscription.recv().await {
                 Ok(event) => {
+                    if event.is_ephemeral() {
+                        if event.session_id == session_id_stream && event.branch_id == branch_id {
+                            yield Ok(part_as_sse_event(&ephemeral_event_part(&event)));
+                        }
+                        continue;
+                    }
                     if event.session_id != session_id_stream
                         || event.branch_id != branch_id
                         || event.sequence < expected_sequence
@@ -1004,6 +1244,14 @@ fn parse_branch_id(raw: Option<&str>) -> ApiResult<BranchId> {
     Ok(BranchId::from_string(value))
 }
 
+/// SSE frame for an ephemeral record. Carries no `id` so a reconnecting
+/// client's `Last-Event-ID` keeps pointing at the last journaled event.
+fn as_ephemeral_sse_event(event: &EventRecord) -> Event {
+    let payload = serde_json::to_string(event)
+        .unwrap_or_else(|error| json!({ "error": error.to_string() }).to_string());
+    Event::default().event("kernel.ephemeral").data(payload)
+}
+
 fn as_sse_event(event_name: &str, event: &EventRecord) -> Event {
     let payload = serde_json::to_string(event)
         .unwrap_or_else(|error| json!({ "error": error.to_string() }).to_string());
@@ -1053,17 +1301,26 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use aios_kernel::KernelBuilder;
-    use aios_protocol::{BranchId, PolicySet};
+    use aios_protocol::{
+        BranchId, Capability, EventKind, EventRecord, KernelResult, ModelCompletion,
+        ModelCompletionRequest, ModelCompletionStream, ModelDirective, ModelProviderPort,
+        ModelStopReason, ModelStreamChunk, PolicySet, SessionId, StreamingModelProviderPort,
+        ToolCall,
+    };
+    use async_trait::async_trait;
     use axum::Json;
-    use axum::extract::{Path, State};
+    use axum::extract::{Path, Query, State};
     use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use futures_util::StreamExt;
     use tokio::fs;
-    use tokio::sync::RwLock;
+    use tokio::sync::{Notify, RwLock};
 
     use super::{
-        AppState, CreateBranchRequest, MergeBranchRequest, PersonaplexProcessContract,
-        StubPersonaplexAdapter, create_branch, list_branches, merge_branch, parse_branch_id,
-        parse_session_id, replay_start_sequence, replay_window_limit,
+        AppState, CausalTreeQuery, CreateBranchRequest, EventListQuery, EventStreamQuery,
+        MergeBranchRequest, PersonaplexProcessContract, StubPersonaplexAdapter, build_causal_tree,
+        causal_tree, create_branch, list_branches, list_events, merge_branch, parse_branch_id,
+        parse_session_id, replay_start_sequence, replay_window_limit, stream_events,
     };
 
     fn unique_test_root(name: &str) -> PathBuf {
@@ -1082,6 +1339,56 @@ mod tests {
         }
     }
 
+    /// Streams one delta, then holds the completion until released.
+    struct HeldStreamingProvider {
+        release: Arc<Notify>,
+    }
+
+    fn held_completion() -> ModelCompletion {
+        ModelCompletion {
+            provider: "held".to_owned(),
+            model: "held".to_owned(),
+            llm_call_record: None,
+            directives: Vec::new(),
+            stop_reason: ModelStopReason::Completed,
+            usage: None,
+            final_answer: Some("hello".to_owned()),
+        }
+    }
+
+    #[async_trait]
+    impl ModelProviderPort for HeldStreamingProvider {
+        async fn complete(
+            &self,
+            _request: ModelCompletionRequest,
+        ) -> KernelResult<ModelCompletion> {
+            Ok(held_completion())
+        }
+    }
+
+    #[async_trait]
+    impl StreamingModelProviderPort for HeldStreamingProvider {
+        async fn stream(
+            &self,
+            _request: ModelCompletionRequest,
+        ) -> KernelResult<ModelCompletionStream> {
+            let release = self.release.clone();
+            Ok(async_stream::stream! {
+                yield Ok(ModelStreamChunk::Directive {
+                    directive: ModelDirective::TextDelta {
+                        delta: "hel".to_owned(),
+                        index: Some(0),
+                    },
+                });
+                release.notified().await;
+                yield Ok(ModelStreamChunk::Finished {
+                    completion: held_completion(),
+                });
+            }
+            .boxed())
+        }
+    }
+
     #[test]
     fn parse_session_id_rejects_invalid_uuid() {
         let result = parse_session_id("not-a-uuid");
@@ -1115,6 +1422,59 @@ mod tests {
         assert_eq!(replay_window_limit(Some(20_000)), 5_000);
     }
 
+    #[tokio::test]
+    async fn sse_stream_delivers_text_deltas_before_the_tick_finishes() {
+        let root = unique_test_root("aios-api-live-deltas");
+        let release = Arc::new(Notify::new());
+        let state = AppState {
+            kernel: KernelBuilder::new(&root)
+                .streaming_provider(Some(Arc::new(HeldStreamingProvider {
+                    release: release.clone(),
+                })))
+                .build(),
+            ..test_state(&root)
+        };
+        let session = state
+            .kernel
+            .create_session("api-test", PolicySet::default(), None)
+            .await
+            .expect("create session");
+
+        let sse = stream_events(
+            Path(session.session_id.to_string()),
+            Query(EventStreamQuery {
+                branch: None,
+                cursor: None,
+                replay_limit: None,
+            }),
+            State(state.clone()),
+        )
+        .await
+        .expect("open stream");
+        let mut body = sse.into_response().into_body().into_data_stream();
+
+        let kernel = state.kernel.clone();
+        let session_id = session.session_id.clone();
+        let tick = tokio::spawn(async move { kernel.tick(&session_id, "say hello", None).await });
+
+        let mut received = String::new();
+        while !received.contains("kernel.ephemeral") {
+            let frame = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+                .await
+                .expect("delta before timeout")
+                .expect("stream open")
+                .expect("frame");
+            received.push_str(&String::from_utf8_lossy(&frame));
+        }
+        assert!(received.contains("\"delta\":\"hel\""));
+        assert!(!tick.is_finished());
+
+        release.notify_one();
+        tick.await.expect("tick task").expect("tick");
+
+        let _ = fs::remove_dir_all(root).await;
+    }
+
     #[tokio::test]
     async fn branch_handlers_create_list_merge_round_trip() {
         let root = unique_test_root("aios-api-branches");
@@ -1207,4 +1567,174 @@ mod tests {
 
         let _ = fs::remove_dir_all(root).await;
     }
+
+    #[tokio::test]
+    async fn causal_tree_links_tool_call_to_its_approval_chain() {
+        let root = unique_test_root("aios-api-causal-tree");
+        let state = test_state(&root);
+
+        let policy = PolicySet {
+            allow_capabilities: vec![Capability::fs_read("/session/**")],
+            gate_capabilities: vec![Capability::fs_write("/session/**")],
+            deny_capabilities: vec![],
+            max_tool_runtime_secs: 10,
+            max_events_per_turn: 128,
+        };
+        let session = state
+            .kernel
+            .create_session("api-test", policy, None)
+            .await
+            .expect("create session");
+        let call = ToolCall::new(
+            "fs.write",
+            serde_json::json!({ "path": "artifacts/out.txt", "content": "ok" }),
+            vec![Capability::fs_write("/session/artifacts/**")],
+        );
+        state
+            .kernel
+            .tick(&session.session_id, "write behind approval", Some(call))
+            .await
+            .expect("tick");
+
+        let events = state
+            .kernel
+            .read_events_on_branch(&session.session_id, &BranchId::main(), 1, 1_000)
+            .await
+            .expect("read events");
+        let (approval_id, requested_id, correlation) = events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::ApprovalRequested { approval_id, .. } => Some((
+                    approval_id.clone(),
+                    event
+                        .causation_id
+                        .clone()
+                        .expect("approval names its tool call"),
+                    event
+                        .correlation_id
+                        .clone()
+                        .expect("tick events are correlated"),
+                )),
+                _ => None,
+            })
+            .expect("approval requested");
+        state
+            .kernel
+            .resolve_approval(
+                &session.session_id,
+                uuid::Uuid::parse_str(approval_id.as_str()).expect("uuid approval id"),
+                true,
+                "tester".to_owned(),
+            )
+            .await
+            .expect("resolve approval");
+
+        let Json(response) = causal_tree(
+            Path((
+                session.session_id.to_string(),
+                requested_id.as_str().to_owned(),
+            )),
+            Query(CausalTreeQuery::default()),
+            State(state.clone()),
+        )
+        .await
+        .expect("causal tree");
+
+        assert!(response.ancestors.is_empty());
+        assert!(matches!(
+            response.tree.event.kind,
+            EventKind::ToolCallRequested { .. }
+        ));
+        let approval = response
+            .tree
+            .children
+            .iter()
+            .find(|node| matches!(node.event.kind, EventKind::ApprovalRequested { .. }))
+            .expect("approval under its tool call");
+        let resolved = &approval.children[0].event;
+        assert!(matches!(resolved.kind, EventKind::ApprovalResolved { .. }));
+        assert_eq!(
+            resolved.correlation_id.as_deref(),
+            Some(correlation.as_str())
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+    }
+
+    #[tokio::test]
+    async fn correlation_filter_applies_before_the_limit() {
+        let root = unique_test_root("aios-api-correlation-limit");
+        let state = test_state(&root);
+        let session = state
+            .kernel
+            .create_session("api-test", PolicySet::default(), None)
+            .await
+            .expect("create session");
+        for objective in ["first", "second"] {
+            state
+                .kernel
+                .tick(&session.session_id, objective, None)
+                .await
+                .expect("tick");
+        }
+        let last = state
+            .kernel
+            .read_events_on_branch(&session.session_id, &BranchId::main(), 1, 1_000)
+            .await
+            .expect("read events")
+            .pop()
+            .expect("events");
+        let correlation = last.correlation_id.expect("tick events are correlated");
+
+        let Json(response) = list_events(
+            Path(session.session_id.to_string()),
+            Query(EventListQuery {
+                correlation_id: Some(correlation.clone()),
+                limit: Some(2),
+                ..EventListQuery::default()
+            }),
+            State(state.clone()),
+        )
+        .await
+        .expect("list events");
+
+        assert_eq!(response.events.len(), 2);
+        assert!(
+            response
+                .events
+                .iter()
+                .all(|event| event.correlation_id.as_deref() == Some(correlation.as_str()))
+        );
+
+        let _ = fs::remove_dir_all(root).await;
+    }
+
+    #[test]
+    fn causal_tree_survives_a_causation_cycle() {
+        let session = SessionId::default();
+        let mut first = EventRecord::new(
+            session.clone(),
+            BranchId::main(),
+            1,
+            EventKind::ErrorRaised {
+                message: "a".to_owned(),
+            },
+        );
+        let mut second = EventRecord::new(
+            session,
+            BranchId::main(),
+            2,
+            EventKind::ErrorRaised {
+                message: "b".to_owned(),
+            },
+        );
+        first.causation_id = Some(second.event_id.clone());
+        second.causation_id = Some(first.event_id.clone());
+        let id = first.event_id.as_str().to_owned();
+
+        let (ancestors, tree) = build_causal_tree(vec![first, second], &id).expect("tree");
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(tree.children.len(), 1);
+        assert!(tree.children[0].children.is_empty());
+    }
 }
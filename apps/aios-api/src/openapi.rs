@@ -77,6 +77,9 @@ pub fn openapi_spec() -> Value {
                                 },
                             },
                         },
+                        "409": {
+                            "description": "A tick is already running on this session/branch (`--tick-concurrency reject`)",
+                        },
                     },
                 },
             },
@@ -192,6 +195,13 @@ pub fn openapi_spec() -> Value {
                             "required": false,
                             "schema": { "type": "integer", "format": "int32", "minimum": 1, "maximum": 5000 },
                         },
+                        {
+                            "name": "correlation_id",
+                            "in": "query",
+                            "required": false,
+                            "description": "Only events of one tick (its run id)",
+                            "schema": { "type": "string" },
+                        },
                     ],
                     "responses": {
                         "200": {
@@ -205,6 +215,58 @@ pub fn openapi_spec() -> Value {
                     },
                 },
             },
+            "/sessions/{session_id}/events/{event_id}/causal-tree": {
+                "get": {
+                    "summary": "Causation chain and caused-event tree of one event",
+                    "parameters": [
+                        { "$ref": "#/components/parameters/SessionIdPath" },
+                        {
+                            "name": "event_id",
+                            "in": "path",
+                            "required": true,
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "branch",
+                            "in": "query",
+                            "required": false,
+                            "schema": { "type": "string", "default": "main" },
+                        },
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "Causal tree",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/CausalTreeResponse" },
+                                },
+                            },
+                        },
+                        "404": {
+                            "description": "Event not found on the branch",
+                        },
+                    },
+                },
+            },
+            "/sessions/{session_id}/state": {
+                "get": {
+                    "summary": "Current canonical state of a branch",
+                    "parameters": [
+                        { "$ref": "#/components/parameters/SessionIdPath" },
+                        { "$ref": "#/components/parameters/BranchQuery" },
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "Canonical state and the version to patch against",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/CanonicalStateResponse" },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
             "/sessions/{session_id}/events/stream": {
                 "get": {
                     "summary": "Stream raw kernel events over SSE",
@@ -535,6 +597,43 @@ pub fn openapi_spec() -> Value {
                         "events": { "type": "array", "items": { "$ref": "#/components/schemas/EventRecord" } },
                     },
                 },
+                "CausalNode": {
+                    "type": "object",
+                    "required": ["event", "children"],
+                    "properties": {
+                        "event": { "$ref": "#/components/schemas/EventRecord" },
+                        "children": { "type": "array", "items": { "$ref": "#/components/schemas/CausalNode" } },
+                    },
+                },
+                "CausalTreeResponse": {
+                    "type": "object",
+                    "required": ["session_id", "branch", "ancestors", "tree"],
+                    "properties": {
+                        "session_id": { "type": "string", "format": "uuid" },
+                        "branch": { "type": "string" },
+                        "ancestors": { "type": "array", "items": { "$ref": "#/components/schemas/EventRecord" } },
+                        "tree": { "$ref": "#/components/schemas/CausalNode" },
+                    },
+                },
+                "CanonicalStateResponse": {
+                    "type": "object",
+                    "required": ["session_id", "branch", "version", "state"],
+                    "properties": {
+                        "session_id": { "type": "string", "format": "uuid" },
+                        "branch": { "type": "string" },
+                        "version": {
+                            "type": "integer",
+                            "format": "int64",
+                            "minimum": 0,
+                            "description": "Pass as `base_version` of the next `state.patch`",
+                        },
+                        "state": {
+                            "type": "object",
+                            "description": "`session`, `agent`, `os` and `memory` namespaces",
+                            "additionalProperties": true,
+                        },
+                    },
+                },
                 "VoiceStartRequest": {
                     "type": "object",
                     "properties": {
@@ -606,7 +705,9 @@ mod tests {
         assert!(spec["paths"]["/docs"].is_object());
         assert!(spec["paths"]["/sessions/{session_id}/events/stream/vercel-ai-sdk-v6"].is_object());
         assert!(spec["paths"]["/sessions/{session_id}/branches"].is_object());
+        assert!(spec["paths"]["/sessions/{session_id}/events/{event_id}/causal-tree"].is_object());
         assert!(spec["paths"]["/sessions/{session_id}/branches/{branch_id}/merge"].is_object());
+        assert!(spec["paths"]["/sessions/{session_id}/state"].is_object());
         assert!(spec["components"]["parameters"]["BranchPath"].is_object());
         assert!(spec["components"]["schemas"]["BranchInfo"].is_object());
     }
@@ -57,6 +57,18 @@ pub fn kernel_event_parts(event: &EventRecord) -> [VercelAiSdkV6Part; 5] {
     ]
 }
 
+/// Live-only records (e.g. streamed text deltas) are sent as a single
+/// transient data part so clients render them without persisting them.
+pub fn ephemeral_event_part(event: &EventRecord) -> VercelAiSdkV6Part {
+    let data =
+        serde_json::to_value(event).unwrap_or_else(|error| json!({ "error": error.to_string() }));
+    VercelAiSdkV6Part::DataAiosEvent {
+        id: format!("ephemeral-{}", event.event_id),
+        data,
+        transient: true,
+    }
+}
+
 pub fn part_as_sse_event(part: &VercelAiSdkV6Part) -> Event {
     let payload = serde_json::to_string(part).unwrap_or_else(|error| {
         json!({
@@ -76,7 +88,7 @@ mod tests {
     use aios_protocol::{BranchId, EventKind, EventRecord, SessionId};
     use serde_json::{Value, json};
 
-    use super::{VercelAiSdkV6Part, kernel_event_parts};
+    use super::{VercelAiSdkV6Part, ephemeral_event_part, kernel_event_parts};
 
     #[test]
     fn start_part_serializes_to_v6_shape() {
@@ -114,4 +126,23 @@ mod tests {
         assert_eq!(value["transient"], false);
         assert!(matches!(value["data"], Value::Object(_)));
     }
+
+    #[test]
+    fn ephemeral_event_maps_to_transient_data_part() {
+        let session_id = SessionId::from_string("00000000-0000-0000-0000-000000000000");
+        let event = EventRecord::ephemeral(
+            session_id,
+            BranchId::main(),
+            EventKind::TextDelta {
+                delta: "hel".to_owned(),
+                index: None,
+            },
+        );
+
+        let value = serde_json::to_value(ephemeral_event_part(&event)).expect("serialize part");
+
+        assert_eq!(value["type"], "data-aios-event");
+        assert_eq!(value["transient"], true);
+        assert!(value["id"].as_str().unwrap().starts_with("ephemeral-"));
+    }
 }
@@ -1,6 +1,9 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use aios_kernel::KernelBuilder;
+use aios_kernel::{
+    BaselineModelProvider, KernelBuilder, PromptInjectionGuard, RedactionConfig, Redactor,
+};
 use aios_protocol::{Capability, PolicySet, ToolCall};
 use anyhow::Result;
 use clap::Parser;
@@ -28,6 +31,9 @@ async fn main() -> Result<()> {
 
     let kernel = KernelBuilder::new(&cli.root)
         .allowed_commands(vec!["echo".to_owned(), "git".to_owned()])
+        .streaming_provider(Some(Arc::new(BaselineModelProvider)))
+        .redactor(Some(Redactor::new(RedactionConfig::default())?))
+        .prompt_injection_guard(Some(PromptInjectionGuard::default()))
         .build();
 
     let policy = PolicySet {
@@ -37,6 +43,7 @@ async fn main() -> Result<()> {
             Capability::exec("*"),
         ],
         gate_capabilities: vec![Capability::new("payments:initiate")],
+        deny_capabilities: vec![],
         max_tool_runtime_secs: 20,
         max_events_per_turn: 512,
     };